@@ -0,0 +1,91 @@
+use std::path::{Path, PathBuf};
+
+use ergogen_layout::effort::{EffortReport, parse_keymap, score};
+use ergogen_outline::sample::parse_points_with_outline_samples;
+use ergogen_parser::Value;
+use serde::Serialize;
+
+use crate::error::CliError;
+use crate::render::load_prepared;
+
+#[derive(Serialize)]
+struct RankedVariant {
+    rank: usize,
+    config: String,
+    #[serde(flatten)]
+    report: EffortReport,
+}
+
+/// Scores every config against the same keymap and corpus and prints them best first.
+pub fn run_analyze(
+    configs: &[PathBuf],
+    keymap: &Path,
+    corpus: &Path,
+    output: Option<&Path>,
+) -> Result<(), CliError> {
+    let keymap_raw = std::fs::read_to_string(keymap)
+        .map_err(|e| CliError::input(format!("Could not read keymap {}: {e}", keymap.display())))?;
+    let keymap_value =
+        Value::from_yaml_str(&keymap_raw).map_err(|e| CliError::input(e.to_string()))?;
+    let corpus_text = std::fs::read_to_string(corpus)
+        .map_err(|e| CliError::input(format!("Could not read corpus {}: {e}", corpus.display())))?;
+
+    let mut variants = Vec::with_capacity(configs.len());
+    for config in configs {
+        let prepared = load_prepared(config)?;
+        let points = parse_points_with_outline_samples(&prepared)
+            .map_err(|e| CliError::processing(e.to_string()))?;
+        let keymap = parse_keymap(&keymap_value, &prepared.units)
+            .map_err(|e| CliError::input(e.to_string()))?;
+        let report = score(&points, &keymap, &corpus_text)
+            .map_err(|e| CliError::input(format!("{}: {e}", config.display())))?;
+        variants.push(RankedVariant {
+            rank: 0,
+            config: config.display().to_string(),
+            report,
+        });
+    }
+
+    variants.sort_by(|a, b| a.report.score.total_cmp(&b.report.score));
+    for (idx, v) in variants.iter_mut().enumerate() {
+        v.rank = idx + 1;
+    }
+
+    println!(
+        "{:>4}  {:>9}  {:>12}  {:>6}  {:>9}  {:>9}  config",
+        "rank", "score", "travel/key", "sfb%", "row jumps", "left/right"
+    );
+    for v in &variants {
+        let r = &v.report;
+        let per_key = |n: f64| {
+            if r.presses == 0 {
+                0.0
+            } else {
+                n / r.presses as f64
+            }
+        };
+        let sfb = if r.bigrams == 0 {
+            0.0
+        } else {
+            100.0 * r.same_finger_bigrams as f64 / r.bigrams as f64
+        };
+        println!(
+            "{:>4}  {:>9.3}  {:>12.2}  {:>6.2}  {:>9}  {:>4.0}/{:<4.0}  {}",
+            v.rank,
+            r.score,
+            per_key(r.travel),
+            sfb,
+            r.row_jumps,
+            r.hand_balance.left * 100.0,
+            r.hand_balance.right * 100.0,
+            v.config
+        );
+    }
+
+    if let Some(output) = output {
+        let json = serde_json::to_string_pretty(&variants)
+            .map_err(|e| CliError::processing(e.to_string()))?;
+        std::fs::write(output, json).map_err(|e| CliError::processing(e.to_string()))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use std::path::Path;
+
+use ergogen_outline::OutlineContext;
+use ergogen_outline::check::{CheckOptions, check_region};
+
+use crate::error::CliError;
+use crate::render::{collect_names, load_prepared};
+
+/// Checks the given outlines (all public ones when `outlines` is empty) and prints every
+/// violation. Returns whether the config passed.
+pub fn run_check(
+    input: &Path,
+    outlines: &[String],
+    options: &CheckOptions,
+) -> Result<bool, CliError> {
+    let prepared = load_prepared(input)?;
+    let names = if outlines.is_empty() {
+        collect_names(&prepared.canonical, "outlines", false)
+    } else {
+        outlines.to_vec()
+    };
+
+    let ctx = OutlineContext::new(&prepared);
+    let mut count = 0;
+    for name in &names {
+        if prepared
+            .canonical
+            .get_path(&format!("outlines.{name}"))
+            .is_none()
+        {
+            return Err(CliError::input(format!("Unknown outline \"{name}\"")));
+        }
+        let region = ctx
+            .region(name)
+            .map_err(|e| CliError::processing(e.to_string()))?;
+        for violation in check_region(&region, options) {
+            println!("{name}: {violation}");
+            count += 1;
+        }
+    }
+
+    if count == 0 {
+        println!("No violations in {} outline(s).", names.len());
+    } else {
+        println!("{count} violation(s).");
+    }
+    Ok(count == 0)
+}
@@ -0,0 +1,87 @@
+use std::path::Path;
+
+use ergogen_export::svg::{SvgShape, svg_from_shapes};
+use ergogen_layout::PointsOutput;
+use ergogen_layout::diff::{DiffOptions, diff_points};
+use ergogen_outline::sample::parse_points_with_outline_samples;
+use ergogen_parser::Value;
+
+use crate::error::CliError;
+use crate::render::{key_corners, load_prepared};
+
+const BEFORE_COLOR: &str = "#d94a4a";
+const AFTER_COLOR: &str = "#4a90d9";
+
+/// Prints which keys were added, removed, moved or changed between two configs.
+///
+/// With `svg`, also writes both layouts on top of each other (`before` in red, `after` in blue).
+pub fn run_diff_points(
+    before: &Path,
+    after: &Path,
+    options: DiffOptions,
+    svg: Option<&Path>,
+) -> Result<(), CliError> {
+    let before_points = load_points(before)?;
+    let after_points = load_points(after)?;
+    let diff = diff_points(&before_points, &after_points, options);
+
+    if diff.is_empty() {
+        println!("No differences.");
+    }
+    for name in &diff.added {
+        println!("+ {name}");
+    }
+    for name in &diff.removed {
+        println!("- {name}");
+    }
+    for m in &diff.moved {
+        println!(
+            "~ {}: moved [{:.3}, {:.3}] ({:.3} mm), rotated {:.3} deg",
+            m.name, m.dx, m.dy, m.distance, m.dr
+        );
+    }
+    for c in &diff.changed {
+        println!(
+            "~ {}: {} {} -> {}",
+            c.name,
+            c.field,
+            fmt_value(&c.before),
+            fmt_value(&c.after)
+        );
+    }
+
+    if let Some(svg) = svg {
+        let mut shapes = Vec::new();
+        for (points, color, label) in [
+            (&before_points, BEFORE_COLOR, "before"),
+            (&after_points, AFTER_COLOR, "after"),
+        ] {
+            for (name, p) in points {
+                shapes.push(SvgShape {
+                    points: key_corners(p).to_vec(),
+                    closed: true,
+                    stroke: color.to_string(),
+                    fill: None,
+                    label: Some(format!("{label}: {name}")),
+                });
+            }
+        }
+        let out = svg_from_shapes(&shapes).map_err(|e| CliError::processing(e.to_string()))?;
+        std::fs::write(svg, out).map_err(|e| CliError::processing(e.to_string()))?;
+    }
+
+    Ok(())
+}
+
+fn load_points(input: &Path) -> Result<PointsOutput, CliError> {
+    let prepared = load_prepared(input)?;
+    parse_points_with_outline_samples(&prepared)
+        .map_err(|e| CliError::processing(format!("{}: {e}", input.display())))
+}
+
+fn fmt_value(v: &Value) -> String {
+    match v {
+        Value::Number(n) => n.to_string(),
+        other => serde_json::to_string(other).unwrap_or_else(|_| format!("{other:?}")),
+    }
+}
@@ -8,6 +8,8 @@ pub enum ErrorCode {
     Input = 2,
     /// Processing error (internal failure while generating outputs).
     Processing = 3,
+    /// `check` found manufacturability violations.
+    Violations = 4,
 }
 
 #[derive(Debug)]
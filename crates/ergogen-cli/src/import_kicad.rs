@@ -0,0 +1,24 @@
+use std::path::Path;
+
+use ergogen_pcb::kicad_import::points_config_from_kicad;
+
+use crate::error::CliError;
+use crate::render::serialize_yaml_no_doc;
+
+/// Converts footprint positions from a `.kicad_pcb` into a `points` config.
+///
+/// Writes to `output` when given, otherwise prints the YAML.
+pub fn run_import_kicad(input: &Path, refs: &str, output: Option<&Path>) -> Result<(), CliError> {
+    let source = std::fs::read_to_string(input)
+        .map_err(|e| CliError::input(format!("Could not read {}: {e}", input.display())))?;
+    let config =
+        points_config_from_kicad(&source, refs).map_err(|e| CliError::input(e.to_string()))?;
+    let yaml = serialize_yaml_no_doc(&config)?;
+    match output {
+        Some(path) => std::fs::write(path, yaml).map_err(|e| CliError::processing(e.to_string())),
+        None => {
+            print!("{yaml}");
+            Ok(())
+        }
+    }
+}
@@ -4,8 +4,14 @@ use std::process::ExitCode;
 
 use clap::{Args, Parser, Subcommand};
 use ergogen_dxf2png::{RenderOptions, save_dxf_as_png};
+use ergogen_layout::diff::DiffOptions;
+use ergogen_outline::check::CheckOptions;
 
+mod analyze;
+mod check;
+mod diff_points;
 mod error;
+mod import_kicad;
 mod render;
 
 use error::{CliError, ErrorCode};
@@ -55,6 +61,14 @@ enum Commands {
     },
     /// Render a config (YAML) into outlines/pcbs/cases outputs
     Render(RenderArgs),
+    /// Score layouts by typing effort over a text corpus and rank them
+    Analyze(AnalyzeArgs),
+    /// Extract switch positions from a KiCad PCB into a points config
+    ImportKicad(ImportKicadArgs),
+    /// List keys added, removed, moved or changed between two configs
+    DiffPoints(DiffPointsArgs),
+    /// Check outlines for features a laser or CNC shop cannot cut
+    Check(CheckArgs),
 }
 
 #[derive(Args)]
@@ -83,6 +97,82 @@ struct RenderArgs {
     svg: bool,
 }
 
+#[derive(Args)]
+struct AnalyzeArgs {
+    /// Config paths (files or bundles) to compare
+    #[arg(required = true)]
+    configs: Vec<PathBuf>,
+
+    /// Keymap YAML mapping characters to point names and columns to fingers
+    #[arg(short, long)]
+    keymap: PathBuf,
+
+    /// Text corpus to replay
+    #[arg(short, long)]
+    corpus: PathBuf,
+
+    /// Write the full ranked report as JSON
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct DiffPointsArgs {
+    /// Config path (file or bundle) to compare from
+    before: PathBuf,
+
+    /// Config path (file or bundle) to compare to
+    after: PathBuf,
+
+    /// Ignore moves up to this distance (mm)
+    #[arg(long, default_value = "0.01")]
+    threshold: f64,
+
+    /// Ignore rotations up to this angle (degrees)
+    #[arg(long, default_value = "0.01")]
+    angle_threshold: f64,
+
+    /// Also write an SVG overlay of both layouts
+    #[arg(long)]
+    svg: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct CheckArgs {
+    /// Input config path (file) or bundle folder (containing config.yaml)
+    input: PathBuf,
+
+    /// Outline to check (repeatable; defaults to all outlines not starting with `_`)
+    #[arg(long = "outline")]
+    outlines: Vec<String>,
+
+    /// Thinnest material the tool can leave (mm, 0 disables)
+    #[arg(long, default_value = "0")]
+    min_feature: f64,
+
+    /// Tightest inside corner radius the tool can cut (mm, 0 disables)
+    #[arg(long, default_value = "0")]
+    min_radius: f64,
+
+    /// Smallest island or hole area (mm², 0 disables)
+    #[arg(long, default_value = "0")]
+    min_area: f64,
+}
+
+#[derive(Args)]
+struct ImportKicadArgs {
+    /// Input .kicad_pcb file
+    input: PathBuf,
+
+    /// Reference pattern of the footprints to import (`*` and `?` wildcards)
+    #[arg(short, long, default_value = "S*")]
+    refs: String,
+
+    /// Output YAML file (defaults to stdout)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
 fn main() -> ExitCode {
     run()
 }
@@ -170,6 +260,69 @@ fn run() -> ExitCode {
                 ExitCode::from(err.code as u8)
             }
         },
+        Commands::Analyze(AnalyzeArgs {
+            configs,
+            keymap,
+            corpus,
+            output,
+        }) => match analyze::run_analyze(&configs, &keymap, &corpus, output.as_deref()) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("Error: {err}");
+                ExitCode::from(err.code as u8)
+            }
+        },
+        Commands::ImportKicad(ImportKicadArgs {
+            input,
+            refs,
+            output,
+        }) => match import_kicad::run_import_kicad(&input, &refs, output.as_deref()) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("Error: {err}");
+                ExitCode::from(err.code as u8)
+            }
+        },
+        Commands::DiffPoints(DiffPointsArgs {
+            before,
+            after,
+            threshold,
+            angle_threshold,
+            svg,
+        }) => {
+            let options = DiffOptions {
+                translation: threshold,
+                rotation: angle_threshold,
+            };
+            match diff_points::run_diff_points(&before, &after, options, svg.as_deref()) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(err) => {
+                    eprintln!("Error: {err}");
+                    ExitCode::from(err.code as u8)
+                }
+            }
+        }
+        Commands::Check(CheckArgs {
+            input,
+            outlines,
+            min_feature,
+            min_radius,
+            min_area,
+        }) => {
+            let options = CheckOptions {
+                min_feature,
+                min_inside_radius: min_radius,
+                min_area,
+            };
+            match check::run_check(&input, &outlines, &options) {
+                Ok(true) => ExitCode::SUCCESS,
+                Ok(false) => ExitCode::from(ErrorCode::Violations as u8),
+                Err(err) => {
+                    eprintln!("Error: {err}");
+                    ExitCode::from(err.code as u8)
+                }
+            }
+        }
     }
 }
 
@@ -191,7 +344,10 @@ fn should_insert_render(args: &[OsString]) -> bool {
         if !saw_double_dash && s.starts_with('-') {
             continue;
         }
-        if s == "render" || s == "dxf2png" {
+        if matches!(
+            s.as_ref(),
+            "render" | "dxf2png" | "analyze" | "import-kicad" | "diff-points" | "check"
+        ) {
             return false;
         }
         return true;
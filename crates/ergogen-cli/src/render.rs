@@ -2,14 +2,19 @@ use std::collections::BTreeMap;
 use std::fs::File;
 use std::path::{Path, PathBuf};
 
-use ergogen_export::dxf::{Dxf, Entity, Line, NormalizeOptions};
+use ergogen_export::dxf::{Dxf, Entity, Line, NormalizeOptions, Point2};
 use ergogen_export::dxf_geom::dxf_from_region;
-use ergogen_export::jscad::{generate_cases_jscad, generate_cases_jscad_v2};
-use ergogen_export::svg::{SvgError, svg_from_dxf};
-use ergogen_layout::{PointsOutput, parse_points};
-use ergogen_outline::generate_outline_region;
+use ergogen_export::jscad::{
+    generate_cases_jscad_v2_with_context, generate_cases_jscad_with_context,
+};
+use ergogen_export::svg::{SvgError, SvgShape, svg_from_dxf, svg_from_shapes};
+use ergogen_layout::ergonomics::{ErgonomicsReport, analyze_config};
+use ergogen_layout::{BindSource, BindTrace, PlacedPoint, PointsOutput};
+use ergogen_outline::OutlineContext;
+use ergogen_outline::compensation::{compensate, export_options};
+use ergogen_outline::measure::measure;
 use ergogen_parser::{PreparedConfig, Value, convert_kle};
-use ergogen_pcb::generate_kicad_pcb;
+use ergogen_pcb::generate_kicad_pcb_with_context;
 use serde::Serialize;
 use tempfile::TempDir;
 use zip::ZipArchive;
@@ -49,24 +54,8 @@ pub fn run_render(
         ))
     })?;
 
-    let parsed = Value::from_yaml_str(&raw).map_err(|e| CliError::input(e.to_string()))?;
-    let (prepared, auto_debug, is_kle) = match parsed {
-        Value::Map(_) => (
-            PreparedConfig::from_value(&parsed).map_err(|e| CliError::input(e.to_string()))?,
-            false,
-            false,
-        ),
-        _ => {
-            let converted = convert_kle(&parsed).map_err(|e| CliError::input(e.to_string()))?;
-            (
-                PreparedConfig::from_value(&converted)
-                    .map_err(|e| CliError::input(e.to_string()))?,
-                true,
-                true,
-            )
-        }
-    };
-    let debug = debug || auto_debug;
+    let (prepared, is_kle) = prepare_config(&raw)?;
+    let debug = debug || is_kle;
 
     let outline_names = collect_names(&prepared.canonical, "outlines", debug);
     let pcb_names = collect_names(&prepared.canonical, "pcbs", debug);
@@ -95,26 +84,63 @@ pub fn run_render(
     }
     std::fs::create_dir_all(&output).map_err(|e| CliError::processing(e.to_string()))?;
 
+    // Points, outlines, PCBs and cases share the points and the generated outlines.
+    let ctx = OutlineContext::new(&prepared);
     if debug || !has_primary_outputs {
         write_source_outputs(&output, &raw, &prepared)?;
-        write_points_outputs(&output, &prepared)?;
+        write_points_outputs(&output, &prepared, &ctx)?;
     }
 
     if !outline_names.is_empty() {
         let write_svg = debug || svg;
-        write_outline_outputs(&output, &prepared, &outline_names, debug, write_svg)?;
+        write_outline_outputs(&output, &ctx, &outline_names, debug, write_svg)?;
     }
     if !pcb_names.is_empty() {
-        write_pcb_outputs(&output, &prepared, &pcb_names)?;
+        write_pcb_outputs(&output, &ctx, &pcb_names)?;
     }
     if !case_names.is_empty() {
-        write_case_outputs(&output, &prepared, &case_names, jscad_v2)?;
+        write_case_outputs(&output, &ctx, &case_names, jscad_v2)?;
     }
 
     println!("Done.");
     Ok(())
 }
 
+/// Parses a YAML config, or converts a KLE layout; the flag reports the latter.
+fn prepare_config(raw: &str) -> Result<(PreparedConfig, bool), CliError> {
+    let parsed = Value::from_yaml_str(raw).map_err(|e| CliError::input(e.to_string()))?;
+    match parsed {
+        Value::Map(_) => Ok((
+            PreparedConfig::from_value(&parsed).map_err(|e| CliError::input(e.to_string()))?,
+            false,
+        )),
+        _ => {
+            let converted = convert_kle(&parsed).map_err(|e| CliError::input(e.to_string()))?;
+            Ok((
+                PreparedConfig::from_value(&converted)
+                    .map_err(|e| CliError::input(e.to_string()))?,
+                true,
+            ))
+        }
+    }
+}
+
+/// Loads a config file or bundle the same way `render` does, without writing anything.
+pub(crate) fn load_prepared(input: &Path) -> Result<PreparedConfig, CliError> {
+    let cwd = std::env::current_dir().map_err(|e| CliError::processing(e.to_string()))?;
+    let input = absolutize_path(&cwd, input);
+    let resolved = resolve_config_path(&input)?;
+    let _bundle_guard = resolved.tempdir;
+    let _cwd_guard = CwdGuard::set(&resolved.bundle_root)?;
+    let raw = std::fs::read_to_string(&resolved.config_path).map_err(|e| {
+        CliError::input(format!(
+            "Could not read config {}: {e}",
+            resolved.config_path.display()
+        ))
+    })?;
+    prepare_config(&raw).map(|(prepared, _)| prepared)
+}
+
 fn log_header(debug: bool) {
     if debug {
         println!("Ergogen <version> CLI (Debug Mode)\n");
@@ -253,7 +279,7 @@ fn extract_bundle_archive(input: &Path) -> Result<(PathBuf, TempDir), CliError>
     Ok((dir.path().to_path_buf(), dir))
 }
 
-fn collect_names(canonical: &Value, key: &str, debug: bool) -> Vec<String> {
+pub(crate) fn collect_names(canonical: &Value, key: &str, debug: bool) -> Vec<String> {
     let Some(Value::Map(map)) = canonical.get_path(key) else {
         return Vec::new();
     };
@@ -280,11 +306,20 @@ fn write_source_outputs(
     Ok(())
 }
 
-fn write_points_outputs(output: &Path, prepared: &PreparedConfig) -> Result<(), CliError> {
+fn write_points_outputs(
+    output: &Path,
+    prepared: &PreparedConfig,
+    ctx: &OutlineContext<'_>,
+) -> Result<(), CliError> {
     let dir = output.join("points");
     std::fs::create_dir_all(&dir).map_err(|e| CliError::processing(e.to_string()))?;
 
-    let points = parse_points(&prepared.canonical, &prepared.units)
+    // Includes the `from_outline` zones.
+    let points = ctx
+        .points()
+        .map_err(|e| CliError::processing(e.to_string()))?;
+    let binds = ctx
+        .bind_trace()
         .map_err(|e| CliError::processing(e.to_string()))?;
 
     let units_vars = prepared.units.vars();
@@ -298,10 +333,11 @@ fn write_points_outputs(output: &Path, prepared: &PreparedConfig) -> Result<(),
         serialize_yaml_no_doc(&units_sorted)?,
     )
     .map_err(|e| CliError::processing(e.to_string()))?;
-    std::fs::write(dir.join("points.yaml"), serialize_yaml_no_doc(&points)?)
+    std::fs::write(dir.join("points.yaml"), serialize_yaml_no_doc(points)?)
         .map_err(|e| CliError::processing(e.to_string()))?;
+    write_bind_outputs(&dir, points, binds)?;
 
-    let demo_lines = points_demo_lines(&points);
+    let demo_lines = points_demo_lines(points);
     let demo_dxf = Dxf {
         entities: demo_lines.iter().cloned().map(Entity::Line).collect(),
     };
@@ -317,12 +353,18 @@ fn write_points_outputs(output: &Path, prepared: &PreparedConfig) -> Result<(),
     )
     .map_err(|e| CliError::processing(e.to_string()))?;
 
+    if let Some(report) = analyze_config(&prepared.canonical, &prepared.units, points)
+        .map_err(|e| CliError::processing(e.to_string()))?
+    {
+        write_ergonomics_outputs(&dir, points, &report)?;
+    }
+
     Ok(())
 }
 
 fn write_outline_outputs(
     output: &Path,
-    prepared: &PreparedConfig,
+    ctx: &OutlineContext<'_>,
     names: &[String],
     debug: bool,
     write_svg: bool,
@@ -330,9 +372,28 @@ fn write_outline_outputs(
     let dir = output.join("outlines");
     std::fs::create_dir_all(&dir).map_err(|e| CliError::processing(e.to_string()))?;
 
+    let mut measurements = serde_json::Map::new();
     for name in names {
-        let region = generate_outline_region(prepared, name)
+        let region = ctx
+            .region(name)
             .map_err(|e| CliError::processing(e.to_string()))?;
+        // Measured as designed, before compensation.
+        let fields = measure(&region)
+            .fields()
+            .into_iter()
+            .map(|(field, v)| {
+                let v = match field {
+                    "islands" | "holes" => serde_json::json!(v as u64),
+                    _ => serde_json::json!(v),
+                };
+                (field.to_string(), v)
+            })
+            .collect();
+        measurements.insert(name.clone(), serde_json::Value::Object(fields));
+        let options =
+            export_options(ctx.prepared(), name).map_err(|e| CliError::input(e.to_string()))?;
+        let region =
+            compensate(&region, &options).map_err(|e| CliError::processing(e.to_string()))?;
         let dxf = dxf_from_region(&region).map_err(|e| CliError::processing(e.to_string()))?;
 
         write_dxf(&dir.join(format!("{name}.dxf")), &dxf)?;
@@ -356,20 +417,24 @@ fn write_outline_outputs(
         }
     }
 
+    let json = serde_json::to_string_pretty(&measurements)
+        .map_err(|e| CliError::processing(e.to_string()))?;
+    std::fs::write(dir.join("measurements.json"), json)
+        .map_err(|e| CliError::processing(e.to_string()))?;
     Ok(())
 }
 
 fn write_pcb_outputs(
     output: &Path,
-    prepared: &PreparedConfig,
+    ctx: &OutlineContext<'_>,
     names: &[String],
 ) -> Result<(), CliError> {
     let dir = output.join("pcbs");
     std::fs::create_dir_all(&dir).map_err(|e| CliError::processing(e.to_string()))?;
 
     for name in names {
-        let pcb =
-            generate_kicad_pcb(prepared, name).map_err(|e| CliError::processing(e.to_string()))?;
+        let pcb = generate_kicad_pcb_with_context(ctx, name)
+            .map_err(|e| CliError::processing(e.to_string()))?;
         std::fs::write(dir.join(format!("{name}.kicad_pcb")), pcb)
             .map_err(|e| CliError::processing(e.to_string()))?;
     }
@@ -379,7 +444,7 @@ fn write_pcb_outputs(
 
 fn write_case_outputs(
     output: &Path,
-    prepared: &PreparedConfig,
+    ctx: &OutlineContext<'_>,
     names: &[String],
     write_v2: bool,
 ) -> Result<(), CliError> {
@@ -387,13 +452,13 @@ fn write_case_outputs(
     std::fs::create_dir_all(&dir).map_err(|e| CliError::processing(e.to_string()))?;
 
     for name in names {
-        let jscad = generate_cases_jscad(prepared, name)
+        let jscad = generate_cases_jscad_with_context(ctx, name)
             .map_err(|e| CliError::processing(e.to_string()))?;
         std::fs::write(dir.join(format!("{name}.jscad")), jscad)
             .map_err(|e| CliError::processing(e.to_string()))?;
 
         if write_v2 {
-            let jscad_v2 = generate_cases_jscad_v2(prepared, name)
+            let jscad_v2 = generate_cases_jscad_v2_with_context(ctx, name)
                 .map_err(|e| CliError::processing(e.to_string()))?;
             std::fs::write(dir.join(format!("{name}.v2.jscad")), jscad_v2)
                 .map_err(|e| CliError::processing(e.to_string()))?;
@@ -414,7 +479,7 @@ fn write_dxf(path: &Path, dxf: &Dxf) -> Result<(), CliError> {
     std::fs::write(path, out_str).map_err(|e| CliError::processing(e.to_string()))
 }
 
-fn serialize_yaml_no_doc<T: Serialize>(value: &T) -> Result<String, CliError> {
+pub(crate) fn serialize_yaml_no_doc<T: Serialize>(value: &T) -> Result<String, CliError> {
     let mut s = serde_yaml::to_string(value).map_err(|e| CliError::processing(e.to_string()))?;
     if let Some(rest) = s.strip_prefix("---\n") {
         s = rest.to_string();
@@ -500,19 +565,7 @@ fn collect_line_entities(dxf: &Dxf) -> Result<Vec<Line>, String> {
 fn points_demo_lines(points: &PointsOutput) -> Vec<Line> {
     let mut entities: Vec<Line> = Vec::new();
     for p in points.values() {
-        let hw = p.meta.width / 2.0;
-        let hh = p.meta.height / 2.0;
-        let corners = [(-hw, hh), (hw, hh), (hw, -hh), (-hw, -hh)];
-        let (sin, cos) = p.r.to_radians().sin_cos();
-        let mut pts = Vec::with_capacity(4);
-        for (x, y) in corners {
-            let rx = x * cos - y * sin;
-            let ry = x * sin + y * cos;
-            pts.push(ergogen_export::dxf::Point2 {
-                x: rx + p.x,
-                y: ry + p.y,
-            });
-        }
+        let pts = key_corners(p);
         for i in 0..4 {
             entities.push(Line {
                 start: pts[i],
@@ -522,3 +575,168 @@ fn points_demo_lines(points: &PointsOutput) -> Vec<Line> {
     }
     entities
 }
+
+pub(crate) fn key_corners(p: &PlacedPoint) -> [Point2; 4] {
+    let hw = p.meta.width / 2.0;
+    let hh = p.meta.height / 2.0;
+    let corners = [(-hw, hh), (hw, hh), (hw, -hh), (-hw, -hh)];
+    let (sin, cos) = p.r.to_radians().sin_cos();
+    corners.map(|(x, y)| Point2 {
+        x: x * cos - y * sin + p.x,
+        y: x * sin + y * cos + p.y,
+    })
+}
+
+/// `binds.txt` lists every key's bind per side and where it came from; `binds.svg` draws the key
+/// rectangles, the area their binds extend over and a line to each autobind neighbour.
+fn write_bind_outputs(
+    dir: &Path,
+    points: &PointsOutput,
+    binds: &BindTrace,
+) -> Result<(), CliError> {
+    const SIDES: [&str; 4] = ["top", "right", "bottom", "left"];
+    let describe = |source: &BindSource| match source {
+        BindSource::Explicit => "explicit".to_string(),
+        BindSource::Auto { neighbour } => format!("auto <- {neighbour}"),
+        BindSource::None => "-".to_string(),
+    };
+
+    let header = ["key", SIDES[0], SIDES[1], SIDES[2], SIDES[3]].map(str::to_string);
+    let rows: Vec<[String; 5]> = std::iter::once(header)
+        .chain(binds.iter().map(|(name, sides)| {
+            let mut row: [String; 5] = Default::default();
+            row[0] = name.clone();
+            for (cell, side) in row[1..].iter_mut().zip(sides) {
+                *cell = format!("{} ({})", side.amount, describe(&side.source));
+            }
+            row
+        }))
+        .collect();
+    let mut widths = [0; 5];
+    for row in &rows {
+        for (w, cell) in widths.iter_mut().zip(row) {
+            *w = (*w).max(cell.len());
+        }
+    }
+    let mut table = String::new();
+    for row in &rows {
+        let line: Vec<String> = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, w)| format!("{cell:<w$}"))
+            .collect();
+        table.push_str(line.join("  ").trim_end());
+        table.push('\n');
+    }
+    std::fs::write(dir.join("binds.txt"), table)
+        .map_err(|e| CliError::processing(e.to_string()))?;
+
+    let mut shapes = Vec::new();
+    for (name, p) in points {
+        let Some(sides) = binds.get(name) else {
+            continue;
+        };
+        // Unset sides (`-1` with autobind off) bind nothing, as in outlines.
+        let [t, r, b, l] = sides.each_ref().map(|s| s.amount.max(0.0));
+        if t + r + b + l > 0.0 {
+            let mut bound = p.clone();
+            bound.meta.width += l + r;
+            bound.meta.height += t + b;
+            let (sin, cos) = p.r.to_radians().sin_cos();
+            let (dx, dy) = ((r - l) / 2.0, (t - b) / 2.0);
+            bound.x += dx * cos - dy * sin;
+            bound.y += dx * sin + dy * cos;
+            shapes.push(SvgShape {
+                points: key_corners(&bound).to_vec(),
+                closed: true,
+                stroke: "#4a90d9".to_string(),
+                fill: Some("#cfe3f7".to_string()),
+                label: Some(format!("{name} bind [{t}, {r}, {b}, {l}]")),
+            });
+        }
+    }
+    for (name, p) in points {
+        shapes.push(SvgShape {
+            points: key_corners(p).to_vec(),
+            closed: true,
+            stroke: "#333333".to_string(),
+            fill: Some("#ffffff".to_string()),
+            label: Some(name.clone()),
+        });
+    }
+    for (name, p) in points {
+        let Some(sides) = binds.get(name) else {
+            continue;
+        };
+        for (side, label) in sides.iter().zip(SIDES) {
+            if let BindSource::Auto { neighbour } = &side.source
+                && let Some(n) = points.get(neighbour)
+            {
+                shapes.push(SvgShape {
+                    points: vec![Point2 { x: p.x, y: p.y }, Point2 { x: n.x, y: n.y }],
+                    closed: false,
+                    stroke: "#d94a4a".to_string(),
+                    fill: None,
+                    label: Some(format!("{name} {label} <- {neighbour}")),
+                });
+            }
+        }
+    }
+    std::fs::write(
+        dir.join("binds.svg"),
+        svg_from_shapes(&shapes).map_err(|e| CliError::processing(e.to_string()))?,
+    )
+    .map_err(|e| CliError::processing(e.to_string()))
+}
+
+fn write_ergonomics_outputs(
+    dir: &Path,
+    points: &PointsOutput,
+    report: &ErgonomicsReport,
+) -> Result<(), CliError> {
+    let json =
+        serde_json::to_string_pretty(report).map_err(|e| CliError::processing(e.to_string()))?;
+    std::fs::write(dir.join("ergonomics.json"), json)
+        .map_err(|e| CliError::processing(e.to_string()))?;
+
+    let mut shapes = Vec::new();
+    for (name, p) in points {
+        let (fill, label) = match report.keys.get(name) {
+            Some(k) => (
+                strain_color(k.strain),
+                format!(
+                    "{name} ({}): reach {:.1}, flex {:.1}, strain {:.2}",
+                    k.finger, k.reach, k.flex, k.strain
+                ),
+            ),
+            None => ("#dddddd".to_string(), name.clone()),
+        };
+        shapes.push(SvgShape {
+            points: key_corners(p).to_vec(),
+            closed: true,
+            stroke: "#333333".to_string(),
+            fill: Some(fill),
+            label: Some(label),
+        });
+    }
+    std::fs::write(
+        dir.join("ergonomics.svg"),
+        svg_from_shapes(&shapes).map_err(|e| CliError::processing(e.to_string()))?,
+    )
+    .map_err(|e| CliError::processing(e.to_string()))
+}
+
+/// Green at zero strain, through yellow, to red at the edge of the comfortable arc and beyond.
+fn strain_color(strain: f64) -> String {
+    let t = strain.clamp(0.0, 1.0);
+    let (r, g) = if t < 0.5 {
+        (t * 2.0, 1.0)
+    } else {
+        (1.0, (1.0 - t) * 2.0)
+    };
+    format!(
+        "#{:02x}{:02x}40",
+        (r * 255.0).round() as u8,
+        (g * 200.0).round() as u8
+    )
+}
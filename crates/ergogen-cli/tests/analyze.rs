@@ -0,0 +1,107 @@
+use std::process::Command;
+
+const KEYMAP: &str = r#"
+keys:
+  a: matrix_pinky_home
+  q: matrix_pinky_top
+  s: matrix_ring_home
+fingers:
+  pinky: pinky
+  ring: ring
+home: [matrix_pinky_home, matrix_ring_home]
+"#;
+
+fn layout(row_spacing: &str) -> String {
+    format!(
+        r#"
+points:
+  zones:
+    matrix:
+      columns:
+        pinky:
+        ring:
+      rows:
+        home:
+          padding: {row_spacing}
+        top:
+"#
+    )
+}
+
+#[test]
+fn analyze_ranks_variants_by_score() {
+    let bin = env!("CARGO_BIN_EXE_ergogen");
+    let dir = tempfile::tempdir().expect("tempdir");
+    let wide = dir.path().join("wide.yaml");
+    let tight = dir.path().join("tight.yaml");
+    std::fs::write(&wide, layout("30")).unwrap();
+    std::fs::write(&tight, layout("17")).unwrap();
+    let keymap = dir.path().join("keymap.yaml");
+    std::fs::write(&keymap, KEYMAP).unwrap();
+    let corpus = dir.path().join("corpus.txt");
+    std::fs::write(&corpus, "qaqs sqa").unwrap();
+    let report = dir.path().join("report.json");
+
+    let out = Command::new(bin)
+        .args([
+            "analyze",
+            wide.to_string_lossy().as_ref(),
+            tight.to_string_lossy().as_ref(),
+            "--keymap",
+            keymap.to_string_lossy().as_ref(),
+            "--corpus",
+            corpus.to_string_lossy().as_ref(),
+            "--output",
+            report.to_string_lossy().as_ref(),
+        ])
+        .output()
+        .expect("run ergogen analyze");
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+
+    let ranked: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&report).unwrap()).unwrap();
+    assert_eq!(ranked[0]["rank"], 1);
+    assert!(
+        ranked[0]["config"]
+            .as_str()
+            .unwrap()
+            .ends_with("tight.yaml")
+    );
+    assert!(ranked[1]["config"].as_str().unwrap().ends_with("wide.yaml"));
+    assert!(ranked[0]["score"].as_f64().unwrap() < ranked[1]["score"].as_f64().unwrap());
+    assert_eq!(ranked[0]["presses"], 7);
+
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let first = stdout.lines().nth(1).unwrap();
+    assert!(first.trim_start().starts_with('1') && first.ends_with("tight.yaml"));
+}
+
+#[test]
+fn analyze_reports_unknown_keymap_points_as_input_errors() {
+    let bin = env!("CARGO_BIN_EXE_ergogen");
+    let dir = tempfile::tempdir().expect("tempdir");
+    let config = dir.path().join("config.yaml");
+    std::fs::write(&config, layout("19")).unwrap();
+    let keymap = dir.path().join("keymap.yaml");
+    std::fs::write(&keymap, KEYMAP.replace("matrix_ring_home\n", "nope\n")).unwrap();
+    let corpus = dir.path().join("corpus.txt");
+    std::fs::write(&corpus, "as").unwrap();
+
+    let out = Command::new(bin)
+        .args([
+            "analyze",
+            config.to_string_lossy().as_ref(),
+            "-k",
+            keymap.to_string_lossy().as_ref(),
+            "-c",
+            corpus.to_string_lossy().as_ref(),
+        ])
+        .output()
+        .expect("run ergogen analyze");
+    assert_eq!(out.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("nope"));
+}
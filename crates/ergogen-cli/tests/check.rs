@@ -0,0 +1,68 @@
+use std::process::Command;
+
+const CONFIG: &str = r#"
+points.zones.matrix:
+outlines:
+  plate:
+    - what: rectangle
+      size: [40, 20]
+      corner: 2
+  dumbbell:
+    - what: rectangle
+      size: 20
+      adjust.shift: [-20, 0]
+    - what: rectangle
+      size: 20
+      adjust.shift: [20, 0]
+    - what: rectangle
+      size: [20, 0.5]
+"#;
+
+fn run_check(args: &[&str]) -> (Option<i32>, String) {
+    let bin = env!("CARGO_BIN_EXE_ergogen");
+    let dir = tempfile::tempdir().expect("tempdir");
+    let config = dir.path().join("config.yaml");
+    std::fs::write(&config, CONFIG).unwrap();
+    let out = Command::new(bin)
+        .arg("check")
+        .arg(&config)
+        .args(args)
+        .output()
+        .expect("run ergogen check");
+    (
+        out.status.code(),
+        String::from_utf8_lossy(&out.stdout).into_owned(),
+    )
+}
+
+#[test]
+fn check_passes_clean_outlines() {
+    let (code, stdout) = run_check(&["--outline", "plate", "--min-feature", "1"]);
+    assert_eq!(code, Some(0), "{stdout}");
+    assert!(
+        stdout.contains("No violations in 1 outline(s)."),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn check_fails_on_violations_with_their_locations() {
+    let (code, stdout) = run_check(&["--min-feature", "1", "--min-radius", "0.5"]);
+    assert_eq!(code, Some(4), "{stdout}");
+    assert!(
+        stdout.contains("dumbbell: feature 0.500 wide at ("),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("dumbbell: sharp inside corner at (10.000, 0.250)"),
+        "{stdout}"
+    );
+    assert!(!stdout.contains("plate:"), "{stdout}");
+    assert!(stdout.contains("5 violation(s)."), "{stdout}");
+}
+
+#[test]
+fn check_rejects_unknown_outlines() {
+    let (code, _) = run_check(&["--outline", "nope"]);
+    assert_eq!(code, Some(2));
+}
@@ -0,0 +1,44 @@
+use std::process::Command;
+
+#[test]
+fn diff_points_reports_changes_and_writes_overlay() {
+    let bin = env!("CARGO_BIN_EXE_ergogen");
+    let dir = tempfile::tempdir().expect("tempdir");
+    let before = dir.path().join("before.yaml");
+    let after = dir.path().join("after.yaml");
+    std::fs::write(
+        &before,
+        "points.zones.matrix:\n  columns: { pinky: {}, ring: {} }\n  rows: { home: {} }\n",
+    )
+    .unwrap();
+    std::fs::write(
+        &after,
+        "points.zones.matrix:\n  columns: { pinky: {}, ring: { key.splay: 5 }, middle: {} }\n  rows: { home: {} }\n",
+    )
+    .unwrap();
+    let svg = dir.path().join("diff.svg");
+
+    let out = Command::new(bin)
+        .args([
+            "diff-points",
+            before.to_string_lossy().as_ref(),
+            after.to_string_lossy().as_ref(),
+            "--svg",
+            svg.to_string_lossy().as_ref(),
+        ])
+        .output()
+        .expect("run ergogen diff-points");
+    assert!(out.status.success());
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert!(stdout.contains("+ matrix_middle_home\n"), "{stdout}");
+    assert!(stdout.contains("~ matrix_ring_home: moved"), "{stdout}");
+    assert!(stdout.contains("rotated 5.000 deg"), "{stdout}");
+    assert!(
+        stdout.contains("~ matrix_ring_home: splay 0 -> 5"),
+        "{stdout}"
+    );
+
+    let overlay = std::fs::read_to_string(&svg).unwrap();
+    assert!(overlay.contains("<title>before: matrix_ring_home</title>"));
+    assert!(overlay.contains("<title>after: matrix_middle_home</title>"));
+}
@@ -0,0 +1,60 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+fn workspace_root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .and_then(|p| p.parent())
+        .expect("workspace root")
+        .to_path_buf()
+}
+
+#[test]
+fn import_kicad_writes_points_config() {
+    let bin = env!("CARGO_BIN_EXE_ergogen");
+    let input = workspace_root().join("fixtures/upstream/test/footprints/mx___pcbs_pcb.kicad_pcb");
+    let dir = tempfile::tempdir().expect("tempdir");
+    let output = dir.path().join("points.yaml");
+
+    let status = Command::new(bin)
+        .args([
+            "import-kicad",
+            input.to_string_lossy().as_ref(),
+            "--refs",
+            "S*",
+            "--output",
+            output.to_string_lossy().as_ref(),
+        ])
+        .status()
+        .expect("run ergogen import-kicad");
+    assert!(status.success());
+
+    let yaml = std::fs::read_to_string(&output).unwrap();
+    assert!(yaml.starts_with("points:\n  zones:\n    S1:\n"), "{yaml}");
+
+    // The generated config renders on its own.
+    let render_out = dir.path().join("render");
+    let status = Command::new(bin)
+        .args([
+            "render",
+            output.to_string_lossy().as_ref(),
+            "--output",
+            render_out.to_string_lossy().as_ref(),
+        ])
+        .status()
+        .expect("run ergogen render");
+    assert!(status.success());
+    let points = std::fs::read_to_string(render_out.join("points/points.yaml")).unwrap();
+    assert!(points.contains("S1:"));
+}
+
+#[test]
+fn import_kicad_without_matches_is_an_input_error() {
+    let bin = env!("CARGO_BIN_EXE_ergogen");
+    let input = workspace_root().join("fixtures/upstream/test/footprints/mx___pcbs_pcb.kicad_pcb");
+    let out = Command::new(bin)
+        .args(["import-kicad", input.to_string_lossy().as_ref(), "-r", "J*"])
+        .output()
+        .expect("run ergogen import-kicad");
+    assert_eq!(out.status.code(), Some(2));
+}
@@ -115,3 +115,207 @@ fn render_svg_flag_controls_outline_svgs() {
     assert!(status.success());
     assert!(output.join("outlines/export.svg").is_file());
 }
+
+#[test]
+fn render_debug_writes_ergonomics_report() {
+    let bin = env!("CARGO_BIN_EXE_ergogen");
+    let out_dir = tempfile::tempdir().expect("tempdir");
+    let input = out_dir.path().join("config.yaml");
+    std::fs::write(
+        &input,
+        r#"
+points:
+  zones:
+    matrix:
+      columns:
+        index:
+      rows:
+        bottom:
+        top:
+ergonomics:
+  palm:
+    ref: matrix_index_bottom
+    shift: [0, -50]
+  fingers:
+    index:
+      length: 80
+  columns:
+    index: index
+"#,
+    )
+    .expect("write config");
+    let output = out_dir.path().join("output");
+
+    let status = Command::new(bin)
+        .args([
+            "render",
+            input.to_string_lossy().as_ref(),
+            "--output",
+            output.to_string_lossy().as_ref(),
+            "--clean",
+        ])
+        .status()
+        .expect("run ergogen render");
+    assert!(status.success());
+
+    let json = std::fs::read_to_string(output.join("points/ergonomics.json")).unwrap();
+    let report: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(report["keys"]["matrix_index_bottom"]["finger"], "index");
+    assert_eq!(report["keys"]["matrix_index_top"]["comfortable"], true);
+    let svg = std::fs::read_to_string(output.join("points/ergonomics.svg")).unwrap();
+    assert!(svg.contains("<title>matrix_index_bottom (index)"));
+}
+
+#[test]
+fn render_debug_writes_bind_report() {
+    let bin = env!("CARGO_BIN_EXE_ergogen");
+    let out_dir = tempfile::tempdir().expect("tempdir");
+    let input = out_dir.path().join("config.yaml");
+    std::fs::write(
+        &input,
+        r#"
+points:
+  zones:
+    matrix:
+      columns:
+        pinky:
+        ring:
+      rows:
+        bottom:
+        top:
+"#,
+    )
+    .expect("write config");
+    let output = out_dir.path().join("output");
+
+    let status = Command::new(bin)
+        .args([
+            "render",
+            input.to_string_lossy().as_ref(),
+            "--output",
+            output.to_string_lossy().as_ref(),
+            "--clean",
+            "--debug",
+        ])
+        .status()
+        .expect("run ergogen render --debug");
+    assert!(status.success());
+
+    let table = std::fs::read_to_string(output.join("points/binds.txt")).unwrap();
+    assert!(table.starts_with("key "));
+    let line = table
+        .lines()
+        .find(|l| l.starts_with("matrix_pinky_bottom "))
+        .unwrap();
+    assert!(line.contains("10 (auto <- matrix_pinky_top)"));
+    assert!(line.contains("10 (auto <- matrix_ring_bottom)"));
+    let svg = std::fs::read_to_string(output.join("points/binds.svg")).unwrap();
+    assert!(svg.contains("<title>matrix_pinky_bottom top &lt;- matrix_pinky_top</title>"));
+}
+
+#[test]
+fn render_debug_lists_points_sampled_from_outlines() {
+    let bin = env!("CARGO_BIN_EXE_ergogen");
+    let out_dir = tempfile::tempdir().expect("tempdir");
+    let input = out_dir.path().join("config.yaml");
+    std::fs::write(
+        &input,
+        r#"
+points:
+  zones:
+    matrix:
+    screws:
+      from_outline:
+        name: board
+        corners: true
+        inset: 4
+outlines:
+  board:
+    - what: rectangle
+      size: [100, 60]
+"#,
+    )
+    .expect("write config");
+    let output = out_dir.path().join("output");
+
+    let status = Command::new(bin)
+        .args([
+            "render",
+            input.to_string_lossy().as_ref(),
+            "--output",
+            output.to_string_lossy().as_ref(),
+            "--clean",
+            "--debug",
+        ])
+        .status()
+        .expect("run ergogen render --debug");
+    assert!(status.success());
+
+    let points = std::fs::read_to_string(output.join("points/points.yaml")).unwrap();
+    for n in 1..=4 {
+        assert!(points.contains(&format!("\nscrews_{n}:")), "{points}");
+    }
+    assert!(!points.contains("screws_5:"));
+    let table = std::fs::read_to_string(output.join("points/binds.txt")).unwrap();
+    assert!(table.lines().any(|l| l.starts_with("screws_1 ")), "{table}");
+}
+
+#[test]
+fn render_applies_export_compensation_to_outline_files() {
+    let bin = env!("CARGO_BIN_EXE_ergogen");
+    let dir = tempfile::tempdir().expect("tempdir");
+    let config = dir.path().join("config.yaml");
+    std::fs::write(
+        &config,
+        "points.zones.matrix:\noutlines:\n  plate:\n    - what: rectangle\n      size: 40\nexport:\n  plate:\n    kerf: 0.2\n",
+    )
+    .unwrap();
+    let output = dir.path().join("output");
+
+    let status = Command::new(bin)
+        .args([
+            "render",
+            config.to_string_lossy().as_ref(),
+            "--output",
+            output.to_string_lossy().as_ref(),
+        ])
+        .status()
+        .expect("run ergogen render");
+    assert!(status.success());
+
+    let dxf = std::fs::read_to_string(output.join("outlines/plate.dxf")).unwrap();
+    // The edges move out by half the kerf, the corners become arcs around the original ones.
+    assert!(dxf.contains("\n20.1\n"), "{dxf}");
+}
+
+#[test]
+fn render_writes_outline_measurements() {
+    let bin = env!("CARGO_BIN_EXE_ergogen");
+    let dir = tempfile::tempdir().expect("tempdir");
+    let config = dir.path().join("config.yaml");
+    std::fs::write(
+        &config,
+        "points.zones.matrix:\noutlines:\n  plate:\n    - what: rectangle\n      size: [40, 20]\n  half:\n    - what: rectangle\n      size: [outlines.plate.width / 2, 10]\nexport:\n  plate:\n    kerf: 0.2\n",
+    )
+    .unwrap();
+    let output = dir.path().join("output");
+
+    let status = Command::new(bin)
+        .args([
+            "render",
+            config.to_string_lossy().as_ref(),
+            "--output",
+            output.to_string_lossy().as_ref(),
+        ])
+        .status()
+        .expect("run ergogen render");
+    assert!(status.success());
+
+    let json = std::fs::read_to_string(output.join("outlines/measurements.json")).unwrap();
+    let report: serde_json::Value = serde_json::from_str(&json).unwrap();
+    // Outlines are measured as designed, without the kerf.
+    assert_eq!(report["plate"]["width"], 40.0);
+    assert_eq!(report["plate"]["area"], 800.0);
+    assert_eq!(report["plate"]["islands"], 1);
+    assert_eq!(report["half"]["width"], 20.0);
+}
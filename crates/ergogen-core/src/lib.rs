@@ -1,5 +1,6 @@
 //! Core types shared across the Ergogen Rust workspace.
 
 pub mod point;
+pub mod vfs;
 
 pub use point::{Point, PointMeta};
@@ -0,0 +1,110 @@
+//! In-memory file overrides shared by everything that reads bundle files.
+//!
+//! WASM consumers have no filesystem, so they register file contents here; native callers can use
+//! it too (tests, bundles held in memory). Files are kept as bytes, so fonts and images fit next to
+//! the text files.
+
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+use indexmap::IndexMap;
+
+static VIRTUAL_FS: OnceLock<Mutex<IndexMap<String, Vec<u8>>>> = OnceLock::new();
+
+fn vfs() -> &'static Mutex<IndexMap<String, Vec<u8>>> {
+    VIRTUAL_FS.get_or_init(|| Mutex::new(IndexMap::new()))
+}
+
+fn normalize_key(raw: &str) -> String {
+    let mut s = raw.replace('\\', "/");
+    while let Some(rest) = s.strip_prefix("./") {
+        s = rest.to_string();
+    }
+    while s.contains("//") {
+        s = s.replace("//", "/");
+    }
+    s
+}
+
+/// Replaces the virtual file map. Keys are normalized to forward slashes without `./`.
+pub fn set(map: IndexMap<String, String>) {
+    let mut normalized = IndexMap::new();
+    for (k, v) in map {
+        normalized.insert(normalize_key(&k), v.into_bytes());
+    }
+    *vfs().lock().expect("vfs lock") = normalized;
+}
+
+/// Adds (or replaces) one binary file, such as a font, keeping the rest of the map.
+pub fn insert_bytes(path: &str, bytes: Vec<u8>) {
+    vfs()
+        .lock()
+        .expect("vfs lock")
+        .insert(normalize_key(path), bytes);
+}
+
+/// Removes every virtual file.
+pub fn clear() {
+    vfs().lock().expect("vfs lock").clear();
+}
+
+/// Whether `suffix` is `path` or its trailing path components: `a/b.yaml` ends `dir/a/b.yaml`,
+/// but not `my_a/b.yaml`.
+fn ends_with_components(path: &str, suffix: &str) -> bool {
+    path.strip_suffix(suffix)
+        .is_some_and(|rest| rest.is_empty() || rest.ends_with('/'))
+}
+
+/// Whether two keys name the same file, one of them possibly resolved further (absolute).
+fn same_file(a: &str, b: &str) -> bool {
+    ends_with_components(a, b) || ends_with_components(b, a)
+}
+
+/// Whether `candidate` (or a path it is a suffix of, or vice versa) is registered.
+pub fn contains(candidate: &str) -> bool {
+    let key = normalize_key(candidate);
+    let guard = vfs().lock().expect("vfs lock");
+    if guard.contains_key(&key) {
+        return true;
+    }
+    guard.keys().any(|k| same_file(k, &key))
+}
+
+/// Returns the registered bytes for `candidate`, preferring an exact key over suffix matches.
+fn read_raw(candidate: &str) -> Option<Vec<u8>> {
+    let key = normalize_key(candidate);
+    let guard = vfs().lock().expect("vfs lock");
+    if let Some(v) = guard.get(&key) {
+        return Some(v.clone());
+    }
+    // Fallback: suffix match, on whole path components, for callers that resolve to absolute
+    // paths (or vice-versa).
+    guard
+        .iter()
+        .filter(|(k, _)| same_file(k, &key))
+        .max_by_key(|(k, _)| k.len())
+        .map(|(_, v)| v.clone())
+}
+
+/// Returns the registered contents of the text file `candidate`, preferring an exact key over
+/// suffix matches.
+pub fn read(candidate: &str) -> Option<String> {
+    read_raw(candidate).map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Reads `path` from the virtual file map, falling back to the filesystem.
+pub fn read_to_string(path: &Path) -> std::io::Result<String> {
+    if let Some(bytes) = read_raw(&path.to_string_lossy()) {
+        return String::from_utf8(bytes)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e));
+    }
+    std::fs::read_to_string(path)
+}
+
+/// Reads `path` as raw bytes (fonts, images), from the virtual file map or the filesystem.
+pub fn read_bytes(path: &Path) -> std::io::Result<Vec<u8>> {
+    if let Some(bytes) = read_raw(&path.to_string_lossy()) {
+        return Ok(bytes);
+    }
+    std::fs::read(path)
+}
@@ -1,5 +1,6 @@
 use std::collections::{HashMap, HashSet};
 
+use ergogen_outline::OutlineContext;
 use ergogen_parser::{PreparedConfig, Units, Value};
 use indexmap::IndexMap;
 
@@ -27,6 +28,12 @@ pub enum JscadError {
     InvalidCase { name: String },
     #[error("invalid case part for \"{name}\"")]
     InvalidCasePart { name: String },
+    #[error("unknown pose key \"{key}\" in case \"{name}\"")]
+    UnknownPoseKey { name: String, key: String },
+    #[error("points error: {0}")]
+    Points(String),
+    #[error("outline measurements: {0}")]
+    Measurements(String),
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -78,6 +85,17 @@ struct CasePart {
     shift: [f64; 3],
     rotate: [f64; 3],
     operation: Option<CaseOp>,
+    pose: Option<Box<PartPose>>,
+}
+
+/// The 3D pose of the key named by a part's `pose`, applied on top of its own shift and rotate.
+#[derive(Debug, Clone)]
+struct PartPose {
+    key: String,
+    r: f64,
+    z: f64,
+    tilt: f64,
+    tent: f64,
 }
 
 #[derive(Debug, Clone)]
@@ -97,15 +115,31 @@ pub fn generate_cases_jscad(
     prepared: &PreparedConfig,
     case_name: &str,
 ) -> Result<String, JscadError> {
-    let data = collect_case_data(prepared, case_name)?;
-    render_cases_v1(case_name, data)
+    generate_cases_jscad_with_context(&OutlineContext::new(prepared), case_name)
 }
 
 pub fn generate_cases_jscad_v2(
     prepared: &PreparedConfig,
     case_name: &str,
 ) -> Result<String, JscadError> {
-    let data = collect_case_data(prepared, case_name)?;
+    generate_cases_jscad_v2_with_context(&OutlineContext::new(prepared), case_name)
+}
+
+/// Like [`generate_cases_jscad`], reusing the points and outlines already generated in `ctx`.
+pub fn generate_cases_jscad_with_context(
+    ctx: &OutlineContext<'_>,
+    case_name: &str,
+) -> Result<String, JscadError> {
+    let data = collect_case_data(ctx, case_name)?;
+    render_cases_v1(case_name, data)
+}
+
+/// Like [`generate_cases_jscad_v2`], reusing the points and outlines already generated in `ctx`.
+pub fn generate_cases_jscad_v2_with_context(
+    ctx: &OutlineContext<'_>,
+    case_name: &str,
+) -> Result<String, JscadError> {
+    let data = collect_case_data(ctx, case_name)?;
     render_cases_v2(case_name, data)
 }
 
@@ -116,7 +150,8 @@ struct CaseData {
     outline_shapes: HashMap<String, OutlineShape>,
 }
 
-fn collect_case_data(prepared: &PreparedConfig, case_name: &str) -> Result<CaseData, JscadError> {
+fn collect_case_data(ctx: &OutlineContext<'_>, case_name: &str) -> Result<CaseData, JscadError> {
+    let prepared = ctx.prepared();
     let cases_v = prepared
         .canonical
         .get_path("cases")
@@ -124,6 +159,8 @@ fn collect_case_data(prepared: &PreparedConfig, case_name: &str) -> Result<CaseD
     let Value::Map(cases_map) = cases_v else {
         return Err(JscadError::CasesNotMap);
     };
+    ctx.check_measurements(cases_v)
+        .map_err(|e| JscadError::Measurements(e.to_string()))?;
 
     let outlines_v = prepared
         .canonical
@@ -141,6 +178,8 @@ fn collect_case_data(prepared: &PreparedConfig, case_name: &str) -> Result<CaseD
         cases.insert(name.clone(), def);
     }
 
+    resolve_poses(ctx, &mut cases)?;
+
     if !cases.contains_key(case_name) {
         return Err(JscadError::UnknownCase {
             name: case_name.to_string(),
@@ -155,7 +194,7 @@ fn collect_case_data(prepared: &PreparedConfig, case_name: &str) -> Result<CaseD
         if outline_shapes.contains_key(outline_name) {
             continue;
         }
-        let shape = parse_outline_shape(outline_name, outlines_map, prepared)?;
+        let shape = parse_outline_shape(outline_name, outlines_map, ctx)?;
         outline_shapes.insert(outline_name.clone(), shape);
     }
 
@@ -167,6 +206,38 @@ fn collect_case_data(prepared: &PreparedConfig, case_name: &str) -> Result<CaseD
     })
 }
 
+/// Fills in `pose` parts from the placed points; points are only parsed when a part asks for one.
+fn resolve_poses(
+    ctx: &OutlineContext<'_>,
+    cases: &mut IndexMap<String, CaseDef>,
+) -> Result<(), JscadError> {
+    for (case_name, def) in cases.iter_mut() {
+        let parts: Vec<&mut CasePart> = match def {
+            CaseDef::Parts(parts) => parts.iter_mut().collect(),
+            CaseDef::Op { target, tool } => vec![target, tool],
+        };
+        for part in parts {
+            let Some(pose) = part.pose.as_mut() else {
+                continue;
+            };
+            let points = ctx
+                .points()
+                .map_err(|e| JscadError::Points(e.to_string()))?;
+            let p = points
+                .get(&pose.key)
+                .ok_or_else(|| JscadError::UnknownPoseKey {
+                    name: case_name.clone(),
+                    key: pose.key.clone(),
+                })?;
+            pose.r = p.r;
+            pose.z = p.meta.z;
+            pose.tilt = p.meta.tilt;
+            pose.tent = p.meta.tent;
+        }
+    }
+    Ok(())
+}
+
 fn parse_case_def(
     name: &str,
     v: &Value,
@@ -259,6 +330,21 @@ fn parse_case_part(
                 .get("operation")
                 .and_then(value_as_str)
                 .and_then(CaseOp::from_str);
+            let pose = match map.get("pose") {
+                None => None,
+                Some(v) => {
+                    let key = value_as_str(v).ok_or_else(|| JscadError::InvalidCasePart {
+                        name: case_name.to_string(),
+                    })?;
+                    Some(Box::new(PartPose {
+                        key: key.to_string(),
+                        r: 0.0,
+                        z: 0.0,
+                        tilt: 0.0,
+                        tent: 0.0,
+                    }))
+                }
+            };
             Ok(CasePart {
                 name: name.clone(),
                 what,
@@ -266,6 +352,7 @@ fn parse_case_part(
                 shift,
                 rotate,
                 operation,
+                pose,
             })
         }
         _ => Err(JscadError::InvalidCasePart {
@@ -307,6 +394,7 @@ fn parse_case_part_str(
         shift: [0.0, 0.0, 0.0],
         rotate: [0.0, 0.0, 0.0],
         operation,
+        pose: None,
     })
 }
 
@@ -423,13 +511,19 @@ fn render_case_fn(name: &str, def: &CaseDef) -> String {
             "                {part_var} = rotate({}, {part_var});\n",
             fmt_vec3_no_spaces(part.rotate)
         ));
+        for rotation in pose_rotations(part) {
+            out.push_str(&format!(
+                "                {part_var} = rotate({}, {part_var});\n",
+                fmt_vec3_no_spaces(rotation)
+            ));
+        }
         out.push_str(&format!(
             "                {part_var} = translate([{part_var}_x, {part_var}_y, 0], {part_var});\n"
         ));
         out.push('\n');
         out.push_str(&format!(
             "                {part_var} = translate({}, {part_var});\n",
-            fmt_vec3_no_spaces(part.shift)
+            fmt_vec3_no_spaces(part_shift(part))
         ));
         if idx == 0 {
             out.push_str(&format!("                let result = {part_var};\n"));
@@ -492,13 +586,19 @@ fn render_case_fn_v2(name: &str, def: &CaseDef) -> String {
             "  {part_var} = rotate({}, {part_var});\n",
             fmt_vec3_radians(part.rotate)
         ));
+        for rotation in pose_rotations(part) {
+            out.push_str(&format!(
+                "  {part_var} = rotate({}, {part_var});\n",
+                fmt_vec3_radians(rotation)
+            ));
+        }
         out.push_str(&format!(
             "  {part_var} = translate([{part_var}_x, {part_var}_y, 0], {part_var});\n"
         ));
         out.push('\n');
         out.push_str(&format!(
             "  {part_var} = translate({}, {part_var});\n",
-            fmt_vec3_no_spaces(part.shift)
+            fmt_vec3_no_spaces(part_shift(part))
         ));
         if idx == 0 {
             out.push_str(&format!("  let result = {part_var};\n"));
@@ -514,6 +614,24 @@ fn render_case_fn_v2(name: &str, def: &CaseDef) -> String {
     out
 }
 
+/// Tilt and tent act about the key's own axes, so they are wrapped in a rotation by the key's
+/// angle. Parts without a pose (or with a flat one) get no extra rotations.
+fn pose_rotations(part: &CasePart) -> Vec<[f64; 3]> {
+    match &part.pose {
+        Some(pose) if pose.tilt != 0.0 || pose.tent != 0.0 => vec![
+            [0.0, 0.0, -pose.r],
+            [pose.tilt, pose.tent, 0.0],
+            [0.0, 0.0, pose.r],
+        ],
+        _ => Vec::new(),
+    }
+}
+
+fn part_shift(part: &CasePart) -> [f64; 3] {
+    let z = part.pose.as_ref().map_or(0.0, |pose| pose.z);
+    [part.shift[0], part.shift[1], part.shift[2] + z]
+}
+
 fn part_fn_call(part: &CasePart) -> String {
     match part.what {
         PartWhat::Outline => {
@@ -530,8 +648,9 @@ fn part_fn_call(part: &CasePart) -> String {
 fn parse_outline_shape(
     name: &str,
     outlines_map: &IndexMap<String, Value>,
-    prepared: &PreparedConfig,
+    ctx: &OutlineContext<'_>,
 ) -> Result<OutlineShape, JscadError> {
+    let prepared = ctx.prepared();
     let def = outlines_map
         .get(name)
         .ok_or_else(|| JscadError::UnknownOutline {
@@ -579,11 +698,11 @@ fn parse_outline_shape(
         }
     }
 
-    let region = ergogen_outline::generate_outline_region(prepared, name).map_err(|_| {
-        JscadError::UnsupportedOutline {
+    let region = ctx
+        .region(name)
+        .map_err(|_| JscadError::UnsupportedOutline {
             name: name.to_string(),
-        }
-    })?;
+        })?;
     Ok(OutlineShape::Region(region))
 }
 
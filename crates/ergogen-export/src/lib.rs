@@ -1,6 +1,8 @@
 //! Output writers (SVG/DXF/JSCAD) and supporting helpers.
 
-pub mod dxf;
 pub mod dxf_geom;
 pub mod jscad;
 pub mod svg;
+
+/// DXF parsing and normalization live in `ergogen-geometry` so outlines can import DXF files.
+pub use ergogen_geometry::dxf;
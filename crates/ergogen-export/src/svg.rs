@@ -100,6 +100,82 @@ pub fn svg_from_lines(lines: &[Line]) -> Result<String, SvgError> {
     ))
 }
 
+/// A single styled path for annotated overlays (heat maps, diffs, debug views).
+#[derive(Debug, Clone)]
+pub struct SvgShape {
+    pub points: Vec<Point2>,
+    pub closed: bool,
+    pub stroke: String,
+    /// Fill colour; `None` leaves the shape unfilled.
+    pub fill: Option<String>,
+    /// Rendered as a `<title>` so viewers show it on hover.
+    pub label: Option<String>,
+}
+
+/// Renders shapes with per-shape colours, using the same mm-based viewBox and Y flip as
+/// [`svg_from_lines`].
+pub fn svg_from_shapes(shapes: &[SvgShape]) -> Result<String, SvgError> {
+    let mut bounds = Bounds::new();
+    for shape in shapes {
+        for p in &shape.points {
+            bounds.update_point(*p);
+        }
+    }
+    if !bounds.is_valid() {
+        return Err(SvgError::Empty);
+    }
+
+    let width = bounds.max_x - bounds.min_x;
+    let height = bounds.max_y - bounds.min_y;
+    let transform = |p: Point2| Point2 {
+        x: p.x - bounds.min_x,
+        y: bounds.max_y - p.y,
+    };
+
+    let mut body = String::new();
+    for shape in shapes {
+        let mut d = String::new();
+        for (idx, p) in shape.points.iter().enumerate() {
+            let p = transform(*p);
+            let cmd = if idx == 0 { "M" } else { " L" };
+            d.push_str(&format!("{cmd} {} {}", fmt_num(p.x), fmt_num(p.y)));
+        }
+        if d.is_empty() {
+            continue;
+        }
+        if shape.closed {
+            d.push_str(" Z");
+        }
+        let fill = shape.fill.as_deref().unwrap_or("none");
+        body.push_str(&format!(
+            "<path d=\"{d}\" stroke=\"{stroke}\" fill=\"{fill}\" vector-effect=\"non-scaling-stroke\">",
+            stroke = escape_attr(&shape.stroke),
+            fill = escape_attr(fill),
+        ));
+        if let Some(label) = &shape.label {
+            body.push_str(&format!("<title>{}</title>", escape_attr(label)));
+        }
+        body.push_str("</path>");
+    }
+
+    if body.is_empty() {
+        return Err(SvgError::Empty);
+    }
+
+    Ok(format!(
+        "<svg width=\"{w}mm\" height=\"{h}mm\" viewBox=\"0 0 {w} {h}\" xmlns=\"http://www.w3.org/2000/svg\"><g id=\"svgGroup\" stroke-linecap=\"round\" fill-rule=\"evenodd\" font-size=\"9pt\" stroke-width=\"0.25mm\">{body}</g></svg>",
+        w = fmt_num(width),
+        h = fmt_num(height),
+    ))
+}
+
+fn escape_attr(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 fn close(a: Point2, b: Point2) -> bool {
     (a.x - b.x).abs() <= EPS && (a.y - b.y).abs() <= EPS
 }
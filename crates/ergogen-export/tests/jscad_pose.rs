@@ -0,0 +1,73 @@
+use ergogen_export::jscad::{
+    JscadError, generate_cases_jscad, generate_cases_jscad_v2, generate_cases_jscad_with_context,
+};
+use ergogen_outline::OutlineContext;
+use ergogen_parser::PreparedConfig;
+
+const CONFIG: &str = r#"
+points:
+  zones:
+    matrix:
+      key:
+        z: 5
+        tilt: 10
+      columns:
+        index:
+          rows.home.rotate: 20
+outlines:
+  plate:
+    - what: rectangle
+      where: true
+      size: 18
+cases:
+  keywell:
+    - name: plate
+      extrude: 2
+      pose: matrix_index_home
+  flat:
+    - name: plate
+      extrude: 2
+"#;
+
+#[test]
+fn case_parts_follow_key_pose() {
+    let prepared = PreparedConfig::from_yaml_str(CONFIG).unwrap();
+
+    let v1 = generate_cases_jscad(&prepared, "keywell").unwrap();
+    assert!(v1.contains("= rotate([0,0,-20], keywell__part_0);"));
+    assert!(v1.contains("= rotate([10,0,0], keywell__part_0);"));
+    assert!(v1.contains("= rotate([0,0,20], keywell__part_0);"));
+    assert!(v1.contains("= translate([0,0,5], keywell__part_0);"));
+
+    let v2 = generate_cases_jscad_v2(&prepared, "keywell").unwrap();
+    assert!(v2.contains("= translate([0,0,5], keywell__part_0);"));
+    assert_eq!(v2.matches("= rotate(").count(), 4);
+}
+
+#[test]
+fn case_parts_without_pose_are_unchanged() {
+    let prepared = PreparedConfig::from_yaml_str(CONFIG).unwrap();
+    let v2 = generate_cases_jscad_v2(&prepared, "flat").unwrap();
+    assert!(v2.contains("= translate([0,0,0], flat__part_0);"));
+    assert_eq!(v2.matches("= rotate(").count(), 1);
+}
+
+#[test]
+fn unknown_pose_key_is_an_error() {
+    let config = CONFIG.replace("pose: matrix_index_home", "pose: nope");
+    let prepared = PreparedConfig::from_yaml_str(&config).unwrap();
+    let err = generate_cases_jscad(&prepared, "keywell").unwrap_err();
+    assert!(matches!(err, JscadError::UnknownPoseKey { ref key, .. } if key == "nope"));
+}
+
+#[test]
+fn cases_can_share_an_outline_context() {
+    let prepared = PreparedConfig::from_yaml_str(CONFIG).unwrap();
+    let ctx = OutlineContext::new(&prepared);
+    for name in ["keywell", "flat"] {
+        assert_eq!(
+            generate_cases_jscad_with_context(&ctx, name).unwrap(),
+            generate_cases_jscad(&prepared, name).unwrap()
+        );
+    }
+}
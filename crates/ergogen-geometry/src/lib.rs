@@ -1,5 +1,6 @@
 //! 2D geometry primitives and operations.
 
+pub mod dxf;
 pub mod primitives;
 pub mod region;
 
@@ -146,6 +146,28 @@ pub fn polygon(vertices: &[(f64, f64)]) -> Polyline<f64> {
     pl
 }
 
+/// A line segment from `a` to `b` thickened to `width`, with round caps (counter-clockwise).
+pub fn capsule(a: (f64, f64), b: (f64, f64), width: f64) -> Polyline<f64> {
+    let r = width / 2.0;
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let len = dx.hypot(dy);
+    if len == 0.0 {
+        return circle(a, r);
+    }
+    // Right-hand normal, so the outline runs counter-clockwise.
+    let (nx, ny) = (dy / len * r, -dx / len * r);
+    let mut pl = Polyline::new_closed();
+    pl.vertex_data
+        .push(PlineVertex::new(a.0 + nx, a.1 + ny, 0.0));
+    pl.vertex_data
+        .push(PlineVertex::new(b.0 + nx, b.1 + ny, 1.0));
+    pl.vertex_data
+        .push(PlineVertex::new(b.0 - nx, b.1 - ny, 0.0));
+    pl.vertex_data
+        .push(PlineVertex::new(a.0 - nx, a.1 - ny, 1.0));
+    pl
+}
+
 pub fn is_valid_closed_polyline(pl: &Polyline<f64>) -> bool {
     pl.is_closed && pl.vertex_count() >= 2
 }
@@ -60,6 +60,32 @@ impl Region {
             PlineOrientation::Clockwise,
         ));
     }
+
+    /// Area-weighted centroid of the filled area (holes subtracted), or `None` when it is empty.
+    pub fn centroid(&self) -> Option<[f64; 2]> {
+        let (mut area, mut cx, mut cy) = (0.0, 0.0, 0.0);
+        for (plines, sign) in [(&self.pos, 1.0), (&self.neg, -1.0)] {
+            for pl in plines {
+                // Arcs are flattened finely enough that the centroid is stable to ~1e-6.
+                let flat = pl.arcs_to_approx_lines(1e-4).unwrap_or_else(|| pl.clone());
+                let n = flat.vertex_count();
+                let (mut a, mut x, mut y) = (0.0, 0.0, 0.0);
+                for i in 0..n {
+                    let (p, q) = (flat.at(i), flat.at((i + 1) % n));
+                    let cross = p.x * q.y - q.x * p.y;
+                    a += cross;
+                    x += (p.x + q.x) * cross;
+                    y += (p.y + q.y) * cross;
+                }
+                // Winding differs between pos and neg, so use the magnitude and apply the sign.
+                let s = sign * a.signum();
+                area += s * a.abs() / 2.0;
+                cx += s * x / 6.0;
+                cy += s * y / 6.0;
+            }
+        }
+        (area.abs() > 1e-12).then(|| [cx / area, cy / area])
+    }
 }
 
 fn union_pline_set(mut plines: Vec<Polyline<f64>>) -> Vec<Polyline<f64>> {
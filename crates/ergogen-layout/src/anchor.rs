@@ -4,7 +4,12 @@ use ergogen_core::{Point, PointMeta};
 use ergogen_parser::Units;
 use ergogen_parser::Value;
 
-use crate::points::{LayoutError, eval_affect, eval_bool_opt, eval_wh, mirror_ref};
+use crate::points::{LayoutError, eval_affect, eval_bool_opt, eval_number, eval_wh, mirror_ref};
+
+/// Resolves the centroid of a named outline for `aggregate.method: centroid`.
+///
+/// Points are placed before any outline exists, so only outline and pcb anchors get one.
+pub type OutlineCentroid<'a> = dyn Fn(&str) -> Result<[f64; 2], String> + 'a;
 
 pub fn parse_anchor(
     raw: &Value,
@@ -14,186 +19,337 @@ pub fn parse_anchor(
     units: &Units,
     mirror: bool,
 ) -> Result<Point, LayoutError> {
-    match raw {
-        Value::String(s) => parse_anchor(
-            &Value::Map(IndexMap::from([(
-                "ref".to_string(),
-                Value::String(s.clone()),
-            )])),
-            name,
-            points,
-            start,
-            units,
-            mirror,
-        ),
-        Value::Seq(steps) => {
-            let mut current = start.clone();
-            for (idx, step) in steps.iter().enumerate() {
-                current = parse_anchor(
-                    step,
-                    &format!("{name}[{}]", idx + 1),
-                    points,
-                    current,
-                    units,
-                    mirror,
-                )?;
-            }
-            Ok(current)
-        }
-        Value::Map(m) => parse_anchor_map(m, name, points, start, units, mirror),
-        _ => Ok(start),
-    }
+    let env = AnchorEnv {
+        points,
+        units,
+        mirror,
+        outlines: None,
+    };
+    env.parse(raw, name, start)
 }
 
-fn parse_anchor_map(
-    m: &IndexMap<String, Value>,
+/// [`parse_anchor`], with outline centroids available to `aggregate`.
+pub fn parse_anchor_with_outlines(
+    raw: &Value,
     name: &str,
     points: &IndexMap<String, Point>,
     start: Point,
     units: &Units,
     mirror: bool,
+    outlines: &OutlineCentroid<'_>,
 ) -> Result<Point, LayoutError> {
-    let resist = eval_bool_opt(m.get("resist"), &format!("{name}.resist"))?.unwrap_or(false);
-    let ctx = AnchorCtx {
+    let env = AnchorEnv {
         points,
-        start: start.clone(),
         units,
         mirror,
-        resist,
+        outlines: Some(outlines),
     };
+    env.parse(raw, name, start)
+}
 
-    let has_ref = m.contains_key("ref");
-    let has_agg = m.contains_key("aggregate");
-    if has_ref && has_agg {
-        return Err(LayoutError::InvalidAnchor {
-            at: name.to_string(),
-            message: "Fields \"ref\" and \"aggregate\" cannot appear together!".to_string(),
-        });
-    }
+struct AnchorEnv<'a> {
+    points: &'a IndexMap<String, Point>,
+    units: &'a Units,
+    mirror: bool,
+    outlines: Option<&'a OutlineCentroid<'a>>,
+}
 
-    let mut point = start.clone();
-    if let Some(ref_v) = m.get("ref") {
-        point = resolve_ref(
-            ref_v,
-            &format!("{name}.ref"),
-            points,
-            start.clone(),
-            units,
-            mirror,
-        )?;
+impl AnchorEnv<'_> {
+    fn parse(&self, raw: &Value, name: &str, start: Point) -> Result<Point, LayoutError> {
+        match raw {
+            Value::String(s) => self.parse(
+                &Value::Map(IndexMap::from([(
+                    "ref".to_string(),
+                    Value::String(s.clone()),
+                )])),
+                name,
+                start,
+            ),
+            Value::Seq(steps) => {
+                let mut current = start.clone();
+                for (idx, step) in steps.iter().enumerate() {
+                    current = self.parse(step, &format!("{name}[{}]", idx + 1), current)?;
+                }
+                Ok(current)
+            }
+            Value::Map(m) => self.parse_map(m, name, start),
+            _ => Ok(start),
+        }
     }
 
-    if let Some(agg_v) = m.get("aggregate") {
-        point = resolve_aggregate(
-            agg_v,
-            &format!("{name}.aggregate"),
-            points,
-            start.clone(),
-            units,
-            mirror,
-        )?;
-    }
+    fn parse_map(
+        &self,
+        m: &IndexMap<String, Value>,
+        name: &str,
+        start: Point,
+    ) -> Result<Point, LayoutError> {
+        let resist = eval_bool_opt(m.get("resist"), &format!("{name}.resist"))?.unwrap_or(false);
 
-    if let Some(orient) = m.get("orient") {
-        apply_rotator(orient, &format!("{name}.orient"), &ctx, &mut point)?;
-    }
-    if let Some(shift) = m.get("shift") {
-        let xy = eval_wh(units, shift, &format!("{name}.shift"))?;
-        point.shift(xy, true, resist);
-    }
-    if let Some(rot) = m.get("rotate") {
-        apply_rotator(rot, &format!("{name}.rotate"), &ctx, &mut point)?;
+        let selectors: Vec<&str> = ["ref", "aggregate", "nearest"]
+            .into_iter()
+            .filter(|k| m.contains_key(*k))
+            .collect();
+        if selectors.len() > 1 {
+            return Err(LayoutError::InvalidAnchor {
+                at: name.to_string(),
+                message: format!(
+                    "Fields \"{}\" and \"{}\" cannot appear together!",
+                    selectors[0], selectors[1]
+                ),
+            });
+        }
+
+        let mut point = start.clone();
+        if let Some(ref_v) = m.get("ref") {
+            point = self.resolve_ref(ref_v, &format!("{name}.ref"), start.clone())?;
+        }
+
+        if let Some(agg_v) = m.get("aggregate") {
+            point = self.resolve_aggregate(agg_v, &format!("{name}.aggregate"), start.clone())?;
+        }
+
+        if let Some(near_v) = m.get("nearest") {
+            point = self.resolve_nearest(near_v, &format!("{name}.nearest"), start.clone())?;
+        }
+
+        if let Some(orient) = m.get("orient") {
+            self.apply_rotator(
+                orient,
+                &format!("{name}.orient"),
+                &start,
+                resist,
+                &mut point,
+            )?;
+        }
+        if let Some(shift) = m.get("shift") {
+            let xy = eval_wh(self.units, shift, &format!("{name}.shift"))?;
+            point.shift(xy, true, resist);
+        }
+        if let Some(rot) = m.get("rotate") {
+            self.apply_rotator(rot, &format!("{name}.rotate"), &start, resist, &mut point)?;
+        }
+
+        if let Some(affect) = m.get("affect") {
+            let candidate = point.clone();
+            let mut base = start.clone();
+            base.meta = candidate.meta.clone();
+            let affects = eval_affect(affect, &format!("{name}.affect"))?;
+            for a in affects {
+                match a {
+                    'x' => base.x = candidate.x,
+                    'y' => base.y = candidate.y,
+                    'r' => base.r = candidate.r,
+                    _ => {}
+                }
+            }
+            point = base;
+        }
+
+        Ok(point)
     }
 
-    if let Some(affect) = m.get("affect") {
-        let candidate = point.clone();
-        let mut base = start.clone();
-        base.meta = candidate.meta.clone();
-        let affects = eval_affect(affect, &format!("{name}.affect"))?;
-        for a in affects {
-            match a {
-                'x' => base.x = candidate.x,
-                'y' => base.y = candidate.y,
-                'r' => base.r = candidate.r,
-                _ => {}
+    fn resolve_ref(&self, raw: &Value, name: &str, start: Point) -> Result<Point, LayoutError> {
+        match raw {
+            Value::String(s) => {
+                let r = mirror_ref(s, self.mirror);
+                self.points
+                    .get(&r)
+                    .cloned()
+                    .ok_or(LayoutError::UnknownPointRef {
+                        name: r,
+                        at: name.to_string(),
+                    })
             }
+            other => self.parse(other, name, start),
         }
-        point = base;
     }
 
-    Ok(point)
-}
+    /// The existing point closest to the given anchor. When the anchor names a point, that point
+    /// itself is skipped, so `nearest: some_key` finds its closest neighbour.
+    fn resolve_nearest(&self, raw: &Value, name: &str, start: Point) -> Result<Point, LayoutError> {
+        let target = self.resolve_ref(raw, name, start)?;
+        let skip = match raw {
+            Value::String(s) => Some(mirror_ref(s, self.mirror)),
+            _ => None,
+        };
+        self.points
+            .iter()
+            .filter(|(n, _)| skip.as_deref() != Some(n.as_str()))
+            .map(|(_, p)| p)
+            .min_by(|a, b| {
+                let da = (a.x - target.x).hypot(a.y - target.y);
+                let db = (b.x - target.x).hypot(b.y - target.y);
+                da.total_cmp(&db)
+            })
+            .cloned()
+            .ok_or_else(|| LayoutError::InvalidAnchor {
+                at: name.to_string(),
+                message: "There are no other points to choose from!".to_string(),
+            })
+    }
 
-fn resolve_ref(
-    raw: &Value,
-    name: &str,
-    points: &IndexMap<String, Point>,
-    start: Point,
-    units: &Units,
-    mirror: bool,
-) -> Result<Point, LayoutError> {
-    match raw {
-        Value::String(s) => {
-            let r = mirror_ref(s, mirror);
-            points.get(&r).cloned().ok_or(LayoutError::UnknownPointRef {
-                name: r,
+    fn resolve_aggregate(
+        &self,
+        raw: &Value,
+        name: &str,
+        start: Point,
+    ) -> Result<Point, LayoutError> {
+        let Value::Map(m) = raw else {
+            return Err(LayoutError::InvalidAnchor {
                 at: name.to_string(),
+                message: "\"aggregate\" must be an object".to_string(),
+            });
+        };
+
+        let method = m
+            .get("method")
+            .and_then(|v| match v {
+                Value::String(s) => Some(s.as_str()),
+                _ => None,
             })
+            .unwrap_or("average");
+
+        if method == "centroid" {
+            return self.aggregate_centroid(m, name);
         }
-        other => parse_anchor(other, name, points, start, units, mirror),
-    }
-}
 
-fn resolve_aggregate(
-    raw: &Value,
-    name: &str,
-    points: &IndexMap<String, Point>,
-    start: Point,
-    units: &Units,
-    mirror: bool,
-) -> Result<Point, LayoutError> {
-    let Value::Map(m) = raw else {
-        return Err(LayoutError::InvalidAnchor {
-            at: name.to_string(),
-            message: "\"aggregate\" must be an object".to_string(),
-        });
-    };
+        let parts_v = m.get("parts").cloned().unwrap_or(Value::Seq(Vec::new()));
+        let Value::Seq(parts) = parts_v else {
+            return Err(LayoutError::InvalidAnchor {
+                at: format!("{name}.parts"),
+                message: "\"parts\" must be an array".to_string(),
+            });
+        };
 
-    let method = m
-        .get("method")
-        .and_then(|v| match v {
-            Value::String(s) => Some(s.as_str()),
-            _ => None,
-        })
-        .unwrap_or("average");
+        let mut resolved: Vec<Point> = Vec::new();
+        for (idx, part) in parts.iter().enumerate() {
+            resolved.push(self.parse(
+                part,
+                &format!("{name}.parts[{}]", idx + 1),
+                start.clone(),
+            )?);
+        }
 
-    let parts_v = m.get("parts").cloned().unwrap_or(Value::Seq(Vec::new()));
-    let Value::Seq(parts) = parts_v else {
-        return Err(LayoutError::InvalidAnchor {
-            at: format!("{name}.parts"),
-            message: "\"parts\" must be an array".to_string(),
-        });
-    };
+        match method {
+            "average" => Ok(aggregate_average(&resolved)),
+            "intersect" => aggregate_intersect(&resolved, name),
+            "farthest" => aggregate_farthest(&resolved, name),
+            "weighted" => self.aggregate_weighted(&resolved, m.get("weights"), name),
+            "bbox" => aggregate_bbox(&resolved, m.get("corner"), name),
+            other => Err(LayoutError::InvalidAnchor {
+                at: format!("{name}.method"),
+                message: format!("Unknown aggregator method \"{other}\""),
+            }),
+        }
+    }
 
-    let mut resolved: Vec<Point> = Vec::new();
-    for (idx, part) in parts.iter().enumerate() {
-        resolved.push(parse_anchor(
-            part,
-            &format!("{name}.parts[{}]", idx + 1),
-            points,
-            start.clone(),
-            units,
-            mirror,
-        )?);
+    fn aggregate_weighted(
+        &self,
+        parts: &[Point],
+        weights: Option<&Value>,
+        name: &str,
+    ) -> Result<Point, LayoutError> {
+        let at = format!("{name}.weights");
+        let Some(Value::Seq(weights)) = weights else {
+            return Err(LayoutError::InvalidAnchor {
+                at,
+                message: "\"weights\" must be an array".to_string(),
+            });
+        };
+        if weights.len() != parts.len() {
+            return Err(LayoutError::InvalidAnchor {
+                at,
+                message: format!(
+                    "Expected {} weights (one per part), but got {}!",
+                    parts.len(),
+                    weights.len()
+                ),
+            });
+        }
+        let weights = weights
+            .iter()
+            .enumerate()
+            .map(|(idx, w)| eval_number(self.units, w, &format!("{at}[{}]", idx + 1)))
+            .collect::<Result<Vec<f64>, _>>()?;
+        let total: f64 = weights.iter().sum();
+        if total.abs() < 1e-12 {
+            return Err(LayoutError::InvalidAnchor {
+                at,
+                message: "The weights must not add up to zero!".to_string(),
+            });
+        }
+        let (mut x, mut y, mut r) = (0.0, 0.0, 0.0);
+        for (p, w) in parts.iter().zip(&weights) {
+            x += p.x * w;
+            y += p.y * w;
+            r += p.r * w;
+        }
+        Ok(Point::new(
+            x / total,
+            y / total,
+            r / total,
+            PointMeta::default(),
+        ))
+    }
+
+    fn aggregate_centroid(
+        &self,
+        m: &IndexMap<String, Value>,
+        name: &str,
+    ) -> Result<Point, LayoutError> {
+        let Some(Value::String(outline)) = m.get("outline") else {
+            return Err(LayoutError::InvalidAnchor {
+                at: format!("{name}.outline"),
+                message: "\"centroid\" needs the name of an outline".to_string(),
+            });
+        };
+        let Some(outlines) = self.outlines else {
+            return Err(LayoutError::InvalidAnchor {
+                at: name.to_string(),
+                message: "Outline centroids are only available in outlines and pcbs!".to_string(),
+            });
+        };
+        let [x, y] = outlines(outline).map_err(|message| LayoutError::InvalidAnchor {
+            at: format!("{name}.outline"),
+            message,
+        })?;
+        Ok(Point::new(x, y, 0.0, PointMeta::default()))
     }
 
-    match method {
-        "average" => Ok(aggregate_average(&resolved)),
-        "intersect" => aggregate_intersect(&resolved, name),
-        other => Err(LayoutError::InvalidAnchor {
-            at: format!("{name}.method"),
-            message: format!("Unknown aggregator method \"{other}\""),
-        }),
+    fn apply_rotator(
+        &self,
+        config: &Value,
+        name: &str,
+        start: &Point,
+        resist: bool,
+        point: &mut Point,
+    ) -> Result<(), LayoutError> {
+        // Upstream behavior:
+        // - Numbers (or numeric expressions) add to rotation
+        // - Otherwise, treat config as an anchor and "turn towards" it.
+        match config {
+            Value::Number(n) => {
+                point.rotate(*n, None, resist);
+                Ok(())
+            }
+            Value::String(s) => match self.units.eval(name, s) {
+                Ok(angle) => {
+                    point.rotate(angle, None, resist);
+                    Ok(())
+                }
+                Err(_) => {
+                    // Treat as an anchor reference, e.g. orient: "ten"
+                    let target = self.parse(config, name, start.clone())?;
+                    point.r = point.angle_to(&Point::xy(target.x, target.y));
+                    Ok(())
+                }
+            },
+            _ => {
+                let target = self.parse(config, name, start.clone())?;
+                point.r = point.angle_to(&Point::xy(target.x, target.y));
+                Ok(())
+            }
+        }
     }
 }
 
@@ -243,6 +399,69 @@ fn aggregate_intersect(parts: &[Point], name: &str) -> Result<Point, LayoutError
     Ok(Point::new(x, y, 0.0, PointMeta::default()))
 }
 
+/// Midpoint of the two parts farthest apart, rotated like their average.
+fn aggregate_farthest(parts: &[Point], name: &str) -> Result<Point, LayoutError> {
+    if parts.len() < 2 {
+        return Err(LayoutError::InvalidAnchor {
+            at: format!("{name}.parts"),
+            message: format!(
+                "Farthest expects at least two parts, but it got {}!",
+                parts.len()
+            ),
+        });
+    }
+    let mut best = (0, 1, f64::NEG_INFINITY);
+    for i in 0..parts.len() {
+        for j in i + 1..parts.len() {
+            let d = (parts[i].x - parts[j].x).hypot(parts[i].y - parts[j].y);
+            if d > best.2 {
+                best = (i, j, d);
+            }
+        }
+    }
+    Ok(aggregate_average(&[
+        parts[best.0].clone(),
+        parts[best.1].clone(),
+    ]))
+}
+
+/// Centre (default) or a corner of the parts' axis-aligned bounding box.
+fn aggregate_bbox(
+    parts: &[Point],
+    corner: Option<&Value>,
+    name: &str,
+) -> Result<Point, LayoutError> {
+    if parts.is_empty() {
+        return Err(LayoutError::InvalidAnchor {
+            at: format!("{name}.parts"),
+            message: "Bbox expects at least one part!".to_string(),
+        });
+    }
+    let min_x = parts.iter().map(|p| p.x).fold(f64::INFINITY, f64::min);
+    let max_x = parts.iter().map(|p| p.x).fold(f64::NEG_INFINITY, f64::max);
+    let min_y = parts.iter().map(|p| p.y).fold(f64::INFINITY, f64::min);
+    let max_y = parts.iter().map(|p| p.y).fold(f64::NEG_INFINITY, f64::max);
+    let corner = match corner {
+        None => "center",
+        Some(Value::String(s)) => s.as_str(),
+        Some(_) => "",
+    };
+    let (x, y) = match corner {
+        "center" => ((min_x + max_x) / 2.0, (min_y + max_y) / 2.0),
+        "top_left" => (min_x, max_y),
+        "top_right" => (max_x, max_y),
+        "bottom_left" => (min_x, min_y),
+        "bottom_right" => (max_x, min_y),
+        _ => {
+            return Err(LayoutError::InvalidAnchor {
+                at: format!("{name}.corner"),
+                message: "\"corner\" must be one of center, top_left, top_right, bottom_left or bottom_right".to_string(),
+            });
+        }
+    };
+    Ok(Point::new(x, y, 0.0, PointMeta::default()))
+}
+
 fn rotate_vec(v: [f64; 2], angle_deg: f64) -> [f64; 2] {
     let a = angle_deg.to_radians();
     let (s, c) = a.sin_cos();
@@ -252,59 +471,3 @@ fn rotate_vec(v: [f64; 2], angle_deg: f64) -> [f64; 2] {
 fn cross(a: [f64; 2], b: [f64; 2]) -> f64 {
     a[0] * b[1] - a[1] * b[0]
 }
-
-struct AnchorCtx<'a> {
-    points: &'a IndexMap<String, Point>,
-    start: Point,
-    units: &'a Units,
-    mirror: bool,
-    resist: bool,
-}
-
-fn apply_rotator(
-    config: &Value,
-    name: &str,
-    ctx: &AnchorCtx<'_>,
-    point: &mut Point,
-) -> Result<(), LayoutError> {
-    // Upstream behavior:
-    // - Numbers (or numeric expressions) add to rotation
-    // - Otherwise, treat config as an anchor and "turn towards" it.
-    match config {
-        Value::Number(n) => {
-            point.rotate(*n, None, ctx.resist);
-            Ok(())
-        }
-        Value::String(s) => match ctx.units.eval(name, s) {
-            Ok(angle) => {
-                point.rotate(angle, None, ctx.resist);
-                Ok(())
-            }
-            Err(_) => {
-                // Treat as an anchor reference, e.g. orient: "ten"
-                let target = parse_anchor(
-                    config,
-                    name,
-                    ctx.points,
-                    ctx.start.clone(),
-                    ctx.units,
-                    ctx.mirror,
-                )?;
-                point.r = point.angle_to(&Point::xy(target.x, target.y));
-                Ok(())
-            }
-        },
-        _ => {
-            let target = parse_anchor(
-                config,
-                name,
-                ctx.points,
-                ctx.start.clone(),
-                ctx.units,
-                ctx.mirror,
-            )?;
-            point.r = point.angle_to(&Point::xy(target.x, target.y));
-            Ok(())
-        }
-    }
-}
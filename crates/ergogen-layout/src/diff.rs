@@ -0,0 +1,141 @@
+//! Comparison of two placed layouts: which keys were added or removed, which moved, and which had
+//! their metadata changed.
+
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use ergogen_parser::Value;
+
+use crate::points::{PlacedPoint, PointsOutput, key_to_value};
+
+/// Moves at or below these thresholds are treated as unchanged.
+#[derive(Debug, Clone, Copy)]
+pub struct DiffOptions {
+    /// Millimeters.
+    pub translation: f64,
+    /// Degrees.
+    pub rotation: f64,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            translation: 0.01,
+            rotation: 0.01,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PointsDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub moved: Vec<MovedKey>,
+    pub changed: Vec<MetaChange>,
+}
+
+impl PointsDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.moved.is_empty()
+            && self.changed.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MovedKey {
+    pub name: String,
+    pub dx: f64,
+    pub dy: f64,
+    pub distance: f64,
+    /// Rotation delta in degrees, normalized to (-180, 180].
+    pub dr: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MetaChange {
+    pub name: String,
+    pub field: String,
+    pub before: Value,
+    pub after: Value,
+}
+
+/// Compares `before` against `after`, key by key (matched by name).
+///
+/// Added keys are listed in `after` order, everything else in `before` order. Metadata covers the
+/// key attributes (`width`, `tags`, `bind`, ...) and the zone/column/row the key belongs to.
+pub fn diff_points(
+    before: &PointsOutput,
+    after: &PointsOutput,
+    options: DiffOptions,
+) -> PointsDiff {
+    let mut diff = PointsDiff {
+        added: after
+            .keys()
+            .filter(|name| !before.contains_key(*name))
+            .cloned()
+            .collect(),
+        ..PointsDiff::default()
+    };
+
+    for (name, a) in before {
+        let Some(b) = after.get(name) else {
+            diff.removed.push(name.clone());
+            continue;
+        };
+
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        let distance = dx.hypot(dy);
+        let mut dr = (b.r - a.r).rem_euclid(360.0);
+        if dr > 180.0 {
+            dr -= 360.0;
+        }
+        if distance > options.translation || dr.abs() > options.rotation {
+            diff.moved.push(MovedKey {
+                name: name.clone(),
+                dx,
+                dy,
+                distance,
+                dr,
+            });
+        }
+
+        let after_fields = meta_fields(b);
+        for (field, before_v) in meta_fields(a) {
+            let after_v = after_fields.get(&field).cloned().unwrap_or(Value::Null);
+            if before_v != after_v {
+                diff.changed.push(MetaChange {
+                    name: name.clone(),
+                    field,
+                    before: before_v,
+                    after: after_v,
+                });
+            }
+        }
+    }
+
+    diff
+}
+
+fn meta_fields(p: &PlacedPoint) -> IndexMap<String, Value> {
+    let Value::Map(mut fields) = key_to_value(&p.meta) else {
+        unreachable!("key_to_value returns a map");
+    };
+    // Names are the diff's keys; origin and shift are consumed during placement.
+    for placement_only in ["name", "colrow", "origin", "shift"] {
+        fields.shift_remove(placement_only);
+    }
+    fields.insert("zone".to_string(), Value::String(p.meta.zone.name.clone()));
+    fields.insert("col".to_string(), Value::String(p.meta.col.name.clone()));
+    fields.insert("row".to_string(), Value::String(p.meta.row.clone()));
+    fields.insert(
+        "bind".to_string(),
+        Value::Seq(p.meta.bind.iter().copied().map(Value::Number).collect()),
+    );
+    fields.insert(
+        "mirrored".to_string(),
+        Value::Bool(p.meta.mirrored.unwrap_or(false)),
+    );
+    fields
+}
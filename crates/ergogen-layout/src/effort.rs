@@ -0,0 +1,298 @@
+//! Typing-effort scoring of a placed layout against a text corpus.
+//!
+//! A [`Keymap`] maps characters to point names and columns to fingers. Replaying a corpus over it
+//! yields finger travel, same-finger bigrams, row jumps and hand balance, combined into a single
+//! score (lower is better) so that layout variants can be ranked.
+
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use ergogen_parser::{Units, Value};
+
+use crate::points::{LayoutError, PlacedPoint, PointsOutput, eval_number_opt, eval_string};
+
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    pub keys: IndexMap<char, String>,
+    /// Column name (or `zone.column`) to finger name.
+    pub fingers: IndexMap<String, String>,
+    /// Resting keys; each finger starts on the home key of its column.
+    pub home: Vec<String>,
+    pub weights: EffortWeights,
+}
+
+/// Score contributions. Travel is per millimeter, the others per occurrence, and imbalance is
+/// scaled so that a one-handed text costs the full weight.
+#[derive(Debug, Clone, Copy)]
+pub struct EffortWeights {
+    pub travel: f64,
+    pub same_finger: f64,
+    pub row_jump: f64,
+    pub imbalance: f64,
+}
+
+impl Default for EffortWeights {
+    fn default() -> Self {
+        Self {
+            travel: 1.0,
+            same_finger: 20.0,
+            row_jump: 10.0,
+            imbalance: 10.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EffortReport {
+    pub presses: usize,
+    /// Corpus characters with no key in the keymap (including whitespace unless mapped).
+    pub unmapped: usize,
+    /// Total finger travel in millimeters.
+    pub travel: f64,
+    pub bigrams: usize,
+    pub same_finger_bigrams: usize,
+    pub row_jumps: usize,
+    pub hand_balance: HandBalance,
+    /// Keyed by `<hand>_<finger>`.
+    pub fingers: IndexMap<String, FingerEffort>,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct HandBalance {
+    pub left: f64,
+    pub right: f64,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct FingerEffort {
+    pub presses: usize,
+    pub travel: f64,
+}
+
+/// Parses a keymap document.
+///
+/// ```yaml
+/// keys: { a: matrix_pinky_home, space: thumb_home }
+/// fingers: { pinky: pinky, thumb.home: thumb }
+/// home: [matrix_pinky_home]
+/// weights: { same_finger: 30 }
+/// ```
+///
+/// Key names are single characters or one of `space`, `enter` and `tab`.
+pub fn parse_keymap(config: &Value, units: &Units) -> Result<Keymap, LayoutError> {
+    let at = "keymap";
+    let Value::Map(m) = config else {
+        return Err(LayoutError::InvalidKeymap {
+            at: at.to_string(),
+            message: "keymap must be an object".to_string(),
+        });
+    };
+
+    let mut keys = IndexMap::new();
+    let Some(Value::Map(km)) = m.get("keys") else {
+        return Err(LayoutError::InvalidKeymap {
+            at: format!("{at}.keys"),
+            message: "\"keys\" must be an object".to_string(),
+        });
+    };
+    for (name, v) in km {
+        let ch = match name.as_str() {
+            "space" => ' ',
+            "enter" => '\n',
+            "tab" => '\t',
+            other => {
+                let mut chars = other.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => c,
+                    _ => {
+                        return Err(LayoutError::InvalidKeymap {
+                            at: format!("{at}.keys.{name}"),
+                            message: format!("\"{name}\" is not a single character"),
+                        });
+                    }
+                }
+            }
+        };
+        keys.insert(ch, eval_string(v, &format!("{at}.keys.{name}"))?);
+    }
+
+    let mut fingers = IndexMap::new();
+    if let Some(Value::Map(fm)) = m.get("fingers") {
+        for (col, v) in fm {
+            fingers.insert(col.clone(), eval_string(v, &format!("{at}.fingers.{col}"))?);
+        }
+    }
+
+    let home = match m.get("home") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::Seq(seq)) => seq
+            .iter()
+            .enumerate()
+            .map(|(i, v)| eval_string(v, &format!("{at}.home[{}]", i + 1)))
+            .collect::<Result<_, _>>()?,
+        Some(_) => {
+            return Err(LayoutError::InvalidKeymap {
+                at: format!("{at}.home"),
+                message: "\"home\" must be an array".to_string(),
+            });
+        }
+    };
+
+    let mut weights = EffortWeights::default();
+    if let Some(Value::Map(wm)) = m.get("weights") {
+        let at = format!("{at}.weights");
+        for (key, slot) in [
+            ("travel", &mut weights.travel),
+            ("same_finger", &mut weights.same_finger),
+            ("row_jump", &mut weights.row_jump),
+            ("imbalance", &mut weights.imbalance),
+        ] {
+            if let Some(v) = eval_number_opt(units, wm.get(key), &format!("{at}.{key}"))? {
+                *slot = v;
+            }
+        }
+    }
+
+    Ok(Keymap {
+        keys,
+        fingers,
+        home,
+        weights,
+    })
+}
+
+struct Stroke<'a> {
+    point: &'a PlacedPoint,
+    finger: String,
+    right: bool,
+}
+
+/// Replays `corpus` over the layout.
+///
+/// Fingers start on their home keys and stay on the last key they pressed. Unmapped characters
+/// are counted and break bigram chains; uppercase letters fall back to their lowercase key.
+pub fn score(
+    points: &PointsOutput,
+    keymap: &Keymap,
+    corpus: &str,
+) -> Result<EffortReport, LayoutError> {
+    let stroke_for = |name: &str, at: &str| -> Result<Stroke<'_>, LayoutError> {
+        let point = points.get(name).ok_or_else(|| LayoutError::InvalidKeymap {
+            at: at.to_string(),
+            message: format!("Unknown point \"{name}\""),
+        })?;
+        let zone = &point.meta.zone.name;
+        let col = &point.meta.col.name;
+        let finger = keymap
+            .fingers
+            .get(&format!("{zone}.{col}"))
+            .or_else(|| keymap.fingers.get(col))
+            .ok_or_else(|| LayoutError::InvalidKeymap {
+                at: at.to_string(),
+                message: format!("No finger assigned to column \"{zone}.{col}\""),
+            })?;
+        let right = point.meta.mirrored.unwrap_or(false);
+        let hand = if right { "right" } else { "left" };
+        Ok(Stroke {
+            point,
+            finger: format!("{hand}_{finger}"),
+            right,
+        })
+    };
+
+    let mut strokes: IndexMap<char, Stroke<'_>> = IndexMap::new();
+    for (ch, name) in &keymap.keys {
+        strokes.insert(*ch, stroke_for(name, &format!("keymap.keys.{ch}"))?);
+    }
+
+    let mut positions: IndexMap<String, [f64; 2]> = IndexMap::new();
+    for (i, name) in keymap.home.iter().enumerate() {
+        let s = stroke_for(name, &format!("keymap.home[{}]", i + 1))?;
+        positions.insert(s.finger, [s.point.x, s.point.y]);
+    }
+
+    let mut presses = 0usize;
+    let mut unmapped = 0usize;
+    let mut travel = 0.0;
+    let mut bigrams = 0usize;
+    let mut same_finger_bigrams = 0usize;
+    let mut row_jumps = 0usize;
+    let mut right_presses = 0usize;
+    let mut fingers: IndexMap<String, FingerEffort> = IndexMap::new();
+    let mut prev: Option<&Stroke<'_>> = None;
+
+    for c in corpus.chars() {
+        let stroke = strokes
+            .get(&c)
+            .or_else(|| c.to_lowercase().next().and_then(|l| strokes.get(&l)));
+        let Some(stroke) = stroke else {
+            unmapped += 1;
+            prev = None;
+            continue;
+        };
+        let p = stroke.point;
+
+        presses += 1;
+        if stroke.right {
+            right_presses += 1;
+        }
+        let pos = positions.entry(stroke.finger.clone()).or_insert([p.x, p.y]);
+        let dist = ((p.x - pos[0]).powi(2) + (p.y - pos[1]).powi(2)).sqrt();
+        *pos = [p.x, p.y];
+        travel += dist;
+        let entry = fingers.entry(stroke.finger.clone()).or_default();
+        entry.presses += 1;
+        entry.travel += dist;
+
+        if let Some(prev) = prev {
+            bigrams += 1;
+            let same_key = std::ptr::eq(prev.point, p);
+            if prev.finger == stroke.finger && !same_key {
+                same_finger_bigrams += 1;
+            }
+            if prev.right == stroke.right && is_row_jump(prev.point, p) {
+                row_jumps += 1;
+            }
+        }
+        prev = Some(stroke);
+    }
+
+    let right = if presses == 0 {
+        0.0
+    } else {
+        right_presses as f64 / presses as f64
+    };
+    let left = if presses == 0 { 0.0 } else { 1.0 - right };
+
+    let w = keymap.weights;
+    let score = if presses == 0 {
+        0.0
+    } else {
+        (travel * w.travel
+            + same_finger_bigrams as f64 * w.same_finger
+            + row_jumps as f64 * w.row_jump)
+            / presses as f64
+            + (left - right).abs() * w.imbalance
+    };
+
+    Ok(EffortReport {
+        presses,
+        unmapped,
+        travel,
+        bigrams,
+        same_finger_bigrams,
+        row_jumps,
+        hand_balance: HandBalance { left, right },
+        fingers,
+        score,
+    })
+}
+
+/// A row jump skips at least one row: the keys are 1.5 key heights or more apart along the first
+/// key's column axis.
+fn is_row_jump(a: &PlacedPoint, b: &PlacedPoint) -> bool {
+    let (sin, cos) = a.r.to_radians().sin_cos();
+    let along = -(b.x - a.x) * sin + (b.y - a.y) * cos;
+    along.abs() >= 1.5 * a.meta.height.max(b.meta.height)
+}
@@ -0,0 +1,425 @@
+//! Ergonomic reach analysis for placed keys.
+//!
+//! A [`HandModel`] describes a hand resting on a palm anchor: every finger has a base joint
+//! (relative to the palm), a length and a rest direction. Each key in a column assigned to a finger
+//! is measured against that finger to decide whether it falls inside a comfortable arc.
+
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use ergogen_core::{Point, PointMeta};
+use ergogen_parser::{Units, Value};
+
+use crate::anchor;
+use crate::points::{
+    LayoutError, PlacedPoint, PointsOutput, eval_bool_opt, eval_number, eval_number_opt,
+    eval_string, eval_xy, points_to_ref,
+};
+
+#[derive(Debug, Clone)]
+pub struct HandModel {
+    /// Anchor of the palm; finger bases are relative to it.
+    pub palm: Value,
+    pub fingers: IndexMap<String, Finger>,
+    /// Column name (or `zone.column`) to finger name.
+    pub columns: IndexMap<String, String>,
+    pub comfort: ComfortZone,
+    pub suggest_stagger: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Finger {
+    pub length: f64,
+    /// Base joint offset from the palm anchor, in the palm's local frame.
+    pub base: [f64; 2],
+    /// Rest direction relative to the palm rotation, in degrees.
+    pub splay: f64,
+}
+
+/// Comfortable reach, as a fraction of finger length, plus the allowed sideways deviation.
+#[derive(Debug, Clone, Copy)]
+pub struct ComfortZone {
+    pub min_reach: f64,
+    pub max_reach: f64,
+    pub max_deviation: f64,
+}
+
+impl Default for ComfortZone {
+    fn default() -> Self {
+        Self {
+            min_reach: 0.55,
+            max_reach: 0.95,
+            max_deviation: 20.0,
+        }
+    }
+}
+
+impl ComfortZone {
+    fn ideal(&self) -> f64 {
+        (self.min_reach + self.max_reach) / 2.0
+    }
+
+    fn half_range(&self) -> f64 {
+        ((self.max_reach - self.min_reach) / 2.0).max(f64::EPSILON)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ErgonomicsReport {
+    pub keys: IndexMap<String, KeyErgonomics>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stagger: Vec<StaggerSuggestion>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyErgonomics {
+    pub finger: String,
+    pub x: f64,
+    pub y: f64,
+    pub r: f64,
+    /// Distance from the finger base to the key center.
+    pub reach: f64,
+    /// `reach` divided by the finger length.
+    pub extension: f64,
+    /// Curl needed to land on the key, in degrees (0 = fully extended).
+    pub flex: f64,
+    /// Sideways angle between the finger's rest direction and the key, in degrees.
+    pub deviation: f64,
+    /// 0 at the center of the comfortable arc, 1 on its edge.
+    pub strain: f64,
+    pub comfortable: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StaggerSuggestion {
+    pub zone: String,
+    pub column: String,
+    pub finger: String,
+    pub current: f64,
+    /// Shift along the column's key axis that centers it on the comfortable arc.
+    pub delta: f64,
+    pub suggested: f64,
+}
+
+/// Parses the `ergonomics` config section.
+pub fn parse_hand_model(config: &Value, units: &Units) -> Result<HandModel, LayoutError> {
+    let at = "ergonomics";
+    let Value::Map(m) = config else {
+        return Err(LayoutError::InvalidErgonomics {
+            at: at.to_string(),
+            message: "\"ergonomics\" must be an object".to_string(),
+        });
+    };
+
+    let palm = m.get("palm").cloned().unwrap_or(Value::Null);
+
+    let mut fingers = IndexMap::new();
+    match m.get("fingers") {
+        Some(Value::Map(fm)) => {
+            for (name, fv) in fm {
+                fingers.insert(
+                    name.clone(),
+                    parse_finger(fv, units, &format!("{at}.fingers.{name}"))?,
+                );
+            }
+        }
+        _ => {
+            return Err(LayoutError::InvalidErgonomics {
+                at: format!("{at}.fingers"),
+                message: "\"fingers\" must be an object".to_string(),
+            });
+        }
+    }
+
+    let mut columns = IndexMap::new();
+    if let Some(Value::Map(cm)) = m.get("columns") {
+        for (col, fv) in cm {
+            let finger = eval_string(fv, &format!("{at}.columns.{col}"))?;
+            if !fingers.contains_key(&finger) {
+                return Err(LayoutError::InvalidErgonomics {
+                    at: format!("{at}.columns.{col}"),
+                    message: format!("Unknown finger \"{finger}\""),
+                });
+            }
+            columns.insert(col.clone(), finger);
+        }
+    }
+
+    let mut comfort = ComfortZone::default();
+    if let Some(Value::Map(cm)) = m.get("comfort") {
+        let at = format!("{at}.comfort");
+        if let Some(v) = eval_number_opt(units, cm.get("min_reach"), &format!("{at}.min_reach"))? {
+            comfort.min_reach = v;
+        }
+        if let Some(v) = eval_number_opt(units, cm.get("max_reach"), &format!("{at}.max_reach"))? {
+            comfort.max_reach = v;
+        }
+        if let Some(v) = eval_number_opt(
+            units,
+            cm.get("max_deviation"),
+            &format!("{at}.max_deviation"),
+        )? {
+            comfort.max_deviation = v;
+        }
+        if comfort.min_reach >= comfort.max_reach {
+            return Err(LayoutError::InvalidErgonomics {
+                at,
+                message: "\"min_reach\" must be smaller than \"max_reach\"".to_string(),
+            });
+        }
+    }
+
+    let suggest_stagger =
+        eval_bool_opt(m.get("suggest_stagger"), &format!("{at}.suggest_stagger"))?.unwrap_or(false);
+
+    Ok(HandModel {
+        palm,
+        fingers,
+        columns,
+        comfort,
+        suggest_stagger,
+    })
+}
+
+fn parse_finger(v: &Value, units: &Units, at: &str) -> Result<Finger, LayoutError> {
+    let Value::Map(m) = v else {
+        return Err(LayoutError::InvalidErgonomics {
+            at: at.to_string(),
+            message: "finger must be an object".to_string(),
+        });
+    };
+    let length = eval_number(
+        units,
+        m.get("length").unwrap_or(&Value::Null),
+        &format!("{at}.length"),
+    )?;
+    if length <= 0.0 {
+        return Err(LayoutError::InvalidErgonomics {
+            at: format!("{at}.length"),
+            message: "finger length must be positive".to_string(),
+        });
+    }
+    let base = match m.get("base") {
+        None | Some(Value::Null) => [0.0, 0.0],
+        Some(v) => eval_xy(units, v, &format!("{at}.base"))?,
+    };
+    let splay = eval_number_opt(units, m.get("splay"), &format!("{at}.splay"))?.unwrap_or(0.0);
+    Ok(Finger {
+        length,
+        base,
+        splay,
+    })
+}
+
+/// Runs the analysis configured under `ergonomics`, if present.
+pub fn analyze_config(
+    canonical: &Value,
+    units: &Units,
+    points: &PointsOutput,
+) -> Result<Option<ErgonomicsReport>, LayoutError> {
+    let Some(config) = canonical.get_path("ergonomics") else {
+        return Ok(None);
+    };
+    let hand = parse_hand_model(config, units)?;
+    analyze(points, &hand, units).map(Some)
+}
+
+/// Measures every key whose column is assigned to a finger.
+///
+/// Mirrored keys are measured against the hand mirrored through the palm anchor (its references
+/// resolve to their `mirror_` counterparts).
+pub fn analyze(
+    points: &PointsOutput,
+    hand: &HandModel,
+    units: &Units,
+) -> Result<ErgonomicsReport, LayoutError> {
+    let ref_points = points_to_ref(points);
+    let resolve_palm = |mirror: bool| -> Result<Point, LayoutError> {
+        let mut palm = anchor::parse_anchor(
+            &hand.palm,
+            "ergonomics.palm",
+            &ref_points,
+            Point::new(0.0, 0.0, 0.0, PointMeta::default()),
+            units,
+            mirror,
+        )?;
+        palm.meta.mirrored = mirror;
+        Ok(palm)
+    };
+    let palm = resolve_palm(false)?;
+    let mirrored_palm = if points.values().any(|p| p.meta.mirrored.unwrap_or(false)) {
+        Some(resolve_palm(true)?)
+    } else {
+        None
+    };
+
+    let mut keys = IndexMap::new();
+    for (name, p) in points {
+        let Some(finger_name) = finger_for(hand, &p.meta.zone.name, &p.meta.col.name) else {
+            continue;
+        };
+        let finger = hand.fingers[finger_name];
+        let palm = match (&mirrored_palm, p.meta.mirrored.unwrap_or(false)) {
+            (Some(m), true) => m,
+            _ => &palm,
+        };
+        keys.insert(
+            name.clone(),
+            measure_key(
+                finger_name,
+                finger,
+                finger_frame(palm, finger),
+                p,
+                hand.comfort,
+            ),
+        );
+    }
+
+    let stagger = if hand.suggest_stagger {
+        suggest_stagger(points, hand, &palm)
+    } else {
+        Vec::new()
+    };
+
+    Ok(ErgonomicsReport { keys, stagger })
+}
+
+fn finger_for<'a>(hand: &'a HandModel, zone: &str, col: &str) -> Option<&'a str> {
+    hand.columns
+        .get(&format!("{zone}.{col}"))
+        .or_else(|| hand.columns.get(col))
+        .map(String::as_str)
+}
+
+/// Returns the finger base position and its unit rest direction.
+fn finger_frame(palm: &Point, finger: Finger) -> ([f64; 2], [f64; 2]) {
+    let mut base = palm.clone();
+    base.shift(finger.base, true, false);
+    base.rotate(finger.splay, None, false);
+    ([base.x, base.y], rotate_vec([0.0, 1.0], base.r))
+}
+
+fn measure_key(
+    finger_name: &str,
+    finger: Finger,
+    (base, dir): ([f64; 2], [f64; 2]),
+    p: &PlacedPoint,
+    comfort: ComfortZone,
+) -> KeyErgonomics {
+    let v = [p.x - base[0], p.y - base[1]];
+    let reach = (v[0] * v[0] + v[1] * v[1]).sqrt();
+    let extension = reach / finger.length;
+    let flex = extension.clamp(0.0, 1.0).acos().to_degrees();
+    let mut deviation = if reach == 0.0 {
+        0.0
+    } else {
+        cross(dir, v).atan2(dot(dir, v)).to_degrees()
+    };
+    if p.meta.mirrored.unwrap_or(false) {
+        deviation = -deviation;
+    }
+    let strain = ((extension - comfort.ideal()).abs() / comfort.half_range())
+        .max(deviation.abs() / comfort.max_deviation.max(f64::EPSILON));
+
+    KeyErgonomics {
+        finger: finger_name.to_string(),
+        x: p.x,
+        y: p.y,
+        r: p.r,
+        reach,
+        extension,
+        flex,
+        deviation,
+        strain,
+        comfortable: strain <= 1.0,
+    }
+}
+
+/// Suggests, per assigned column, how far to move the column along its key axis so that its keys
+/// sit as close as possible to the middle of the comfortable arc.
+///
+/// Only unmirrored keys are considered; mirrored columns follow their source.
+pub fn suggest_stagger(
+    points: &PointsOutput,
+    hand: &HandModel,
+    palm: &Point,
+) -> Vec<StaggerSuggestion> {
+    let mut columns: IndexMap<(String, String), Vec<&PlacedPoint>> = IndexMap::new();
+    for p in points.values() {
+        if p.meta.mirrored.unwrap_or(false) {
+            continue;
+        }
+        if finger_for(hand, &p.meta.zone.name, &p.meta.col.name).is_none() {
+            continue;
+        }
+        columns
+            .entry((p.meta.zone.name.clone(), p.meta.col.name.clone()))
+            .or_default()
+            .push(p);
+    }
+
+    let ideal = hand.comfort.ideal();
+    let mut out = Vec::new();
+    for ((zone, column), keys) in columns {
+        let finger_name = finger_for(hand, &zone, &column).expect("assigned column");
+        let finger = hand.fingers[finger_name];
+        let (base, _) = finger_frame(palm, finger);
+
+        let cost = |delta: f64| -> f64 {
+            keys.iter()
+                .map(|p| {
+                    let up = rotate_vec([0.0, 1.0], p.r);
+                    let x = p.x + up[0] * delta - base[0];
+                    let y = p.y + up[1] * delta - base[1];
+                    let e = (x * x + y * y).sqrt() / finger.length - ideal;
+                    e * e
+                })
+                .sum()
+        };
+        let delta = golden_section_min(cost, -finger.length, finger.length);
+        let delta = (delta * 1000.0).round() / 1000.0;
+        let current = keys[0].meta.stagger;
+        out.push(StaggerSuggestion {
+            zone,
+            column,
+            finger: finger_name.to_string(),
+            current,
+            delta,
+            suggested: current + delta,
+        });
+    }
+    out
+}
+
+fn golden_section_min(f: impl Fn(f64) -> f64, mut lo: f64, mut hi: f64) -> f64 {
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    let mut c = hi - ratio * (hi - lo);
+    let mut d = lo + ratio * (hi - lo);
+    for _ in 0..100 {
+        if (hi - lo).abs() < 1e-6 {
+            break;
+        }
+        if f(c) < f(d) {
+            hi = d;
+        } else {
+            lo = c;
+        }
+        c = hi - ratio * (hi - lo);
+        d = lo + ratio * (hi - lo);
+    }
+    (lo + hi) / 2.0
+}
+
+fn rotate_vec(v: [f64; 2], angle_deg: f64) -> [f64; 2] {
+    let a = angle_deg.to_radians();
+    let (s, c) = a.sin_cos();
+    [v[0] * c - v[1] * s, v[0] * s + v[1] * c]
+}
+
+fn cross(a: [f64; 2], b: [f64; 2]) -> f64 {
+    a[0] * b[1] - a[1] * b[0]
+}
+
+fn dot(a: [f64; 2], b: [f64; 2]) -> f64 {
+    a[0] * b[0] + a[1] * b[1]
+}
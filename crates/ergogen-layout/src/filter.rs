@@ -0,0 +1,677 @@
+//! The `where` filter shared by outlines and PCBs.
+//!
+//! Besides the upstream forms (`true`, `false`, a `/regex/` over names and tags, a list of tags,
+//! or an anchor), a string that is not a plain point name is read as a query:
+//!
+//! ```text
+//! zone:matrix and not (row:top or tag:/thumb/) and x > 40 order by y desc, x limit 3
+//! ```
+//!
+//! - `zone:`, `col:` (or `column:`), `row:`, `tag:` and `name:` select on metadata, with a plain
+//!   value or a `/regex/`.
+//! - `field op value` compares `x`, `y`, `r`, `name`, `mirrored`, the selector names or any
+//!   `meta.<path>` (e.g. `meta.width >= 1.5u`), with `==`, `!=`, `<`, `<=`, `>` and `>=`.
+//! - A bare word matches a point name or a tag; a bare `/regex/` keeps its upstream meaning.
+//! - `and`, `or`, `not` and parentheses combine terms; `order by` and `limit` come last.
+
+use indexmap::IndexMap;
+use regex::Regex;
+
+use ergogen_core::{Point, PointMeta};
+use ergogen_parser::{Units, Value};
+
+use crate::anchor;
+use crate::points::{Asymmetry, LayoutError, PlacedPoint, PointsOutput, key_to_value};
+
+/// A point picked by a `where` filter.
+///
+/// Anchors resolve to an unnamed point without binds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selected {
+    pub name: String,
+    pub x: f64,
+    pub y: f64,
+    pub r: f64,
+    pub mirrored: bool,
+    pub bind: [f64; 4],
+}
+
+impl Selected {
+    fn from_point(name: &str, p: &PlacedPoint) -> Self {
+        Self {
+            name: name.to_string(),
+            x: p.x,
+            y: p.y,
+            r: p.r,
+            mirrored: p.meta.mirrored.unwrap_or(false),
+            bind: p.meta.bind,
+        }
+    }
+
+    fn from_anchor(p: &Point) -> Self {
+        Self {
+            name: String::new(),
+            x: p.x,
+            y: p.y,
+            r: p.r,
+            mirrored: p.meta.mirrored,
+            bind: [0.0; 4],
+        }
+    }
+}
+
+/// Resolves a `where` value to the points it selects, honoring `asym` (sources only, clones only,
+/// or both).
+pub fn select_points(
+    where_v: &Value,
+    asym: Asymmetry,
+    points: &PointsOutput,
+    ref_points: &IndexMap<String, Point>,
+    units: &Units,
+    at: &str,
+) -> Result<Vec<Selected>, LayoutError> {
+    let side_ok = |p: &PlacedPoint| {
+        let mirrored = p.meta.mirrored.unwrap_or(false);
+        !((asym == Asymmetry::Source && mirrored) || (asym == Asymmetry::Clone && !mirrored))
+    };
+    let collect = |keep: &dyn Fn(&str, &PlacedPoint) -> bool| -> Vec<Selected> {
+        points
+            .iter()
+            .filter(|(name, p)| side_ok(p) && keep(name, p))
+            .map(|(name, p)| Selected::from_point(name, p))
+            .collect()
+    };
+
+    match where_v {
+        // Keys sampled from outlines are only picked by name, tag or query.
+        Value::Bool(true) => Ok(collect(&|_, p| p.meta.from_outline.is_none())),
+        // Upstream `where` defaults to a single point at [0, 0].
+        Value::Null => Ok(vec![Selected {
+            name: String::new(),
+            x: 0.0,
+            y: 0.0,
+            r: 0.0,
+            mirrored: false,
+            bind: [0.0; 4],
+        }]),
+        Value::Bool(false) => Ok(Vec::new()),
+        Value::String(s) if looks_like_regex_literal(s) => {
+            let re = parse_regex_literal(s).ok_or_else(|| LayoutError::InvalidFilter {
+                at: at.to_string(),
+                message: format!("invalid regex {s}"),
+            })?;
+            Ok(collect(&|name, p| {
+                re.is_match(name) || p.meta.tags.iter().any(|t| re.is_match(t))
+            }))
+        }
+        Value::String(s) if is_query(s) => {
+            let query = Query::parse(s, units, at)?;
+            Ok(query
+                .select(points.iter().filter(|(_, p)| side_ok(p)))
+                .into_iter()
+                .map(|(name, p)| Selected::from_point(name, p))
+                .collect())
+        }
+        Value::Seq(seq) if seq.iter().all(|v| matches!(v, Value::String(_))) => {
+            let wanted: Vec<&str> = seq
+                .iter()
+                .filter_map(|v| match v {
+                    Value::String(s) => Some(s.as_str()),
+                    _ => None,
+                })
+                .collect();
+            if wanted.is_empty() {
+                return Ok(Vec::new());
+            }
+            Ok(collect(&|_, p| {
+                p.meta.tags.iter().any(|t| wanted.contains(&t.as_str()))
+            }))
+        }
+        other => {
+            let start = Point::new(0.0, 0.0, 0.0, PointMeta::default());
+            let resolve = |mirror: bool| {
+                anchor::parse_anchor(other, at, ref_points, start.clone(), units, mirror)
+            };
+            match asym {
+                Asymmetry::Source => Ok(vec![Selected::from_anchor(&resolve(false)?)]),
+                Asymmetry::Clone => Ok(vec![Selected::from_anchor(&resolve(true)?)]),
+                Asymmetry::Both => {
+                    let base = resolve(false)?;
+                    let m = resolve(true)?;
+                    if (base.x - m.x).abs() < 1e-9
+                        && (base.y - m.y).abs() < 1e-9
+                        && (base.r - m.r).abs() < 1e-9
+                    {
+                        Ok(vec![Selected::from_anchor(&base)])
+                    } else {
+                        Ok(vec![
+                            Selected::from_anchor(&base),
+                            Selected::from_anchor(&m),
+                        ])
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn looks_like_regex_literal(s: &str) -> bool {
+    s.starts_with('/') && s.len() >= 2 && s[1..].contains('/')
+}
+
+/// Parses a JS-style `/pattern/flags` literal; only the `i` flag is honored.
+pub fn parse_regex_literal(raw: &str) -> Option<Regex> {
+    let last_slash = raw.rfind('/')?;
+    if !raw.starts_with('/') || last_slash == 0 {
+        return None;
+    }
+
+    let pat = &raw[1..last_slash];
+    let flags = &raw[last_slash + 1..];
+    if flags.contains('i') {
+        Regex::new(&format!("(?i){pat}")).ok()
+    } else {
+        Regex::new(pat).ok()
+    }
+}
+
+/// Plain point names stay anchor references; anything with spaces, selectors or operators is a
+/// query.
+fn is_query(s: &str) -> bool {
+    s.chars()
+        .any(|c| c.is_whitespace() || matches!(c, ':' | '=' | '!' | '<' | '>' | '('))
+}
+
+/// A parsed query string.
+#[derive(Debug)]
+pub struct Query {
+    expr: Expr,
+    order: Vec<(Field, bool)>,
+    limit: Option<usize>,
+}
+
+impl Query {
+    pub fn parse(src: &str, units: &Units, at: &str) -> Result<Self, LayoutError> {
+        let tokens = tokenize(src, at)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            units,
+            at,
+        };
+        let expr = parser.expr()?;
+
+        let mut order = Vec::new();
+        if parser.eat_keyword("order") {
+            if !parser.eat_keyword("by") {
+                return Err(parser.error("expected \"by\" after \"order\""));
+            }
+            loop {
+                let field = match parser.next() {
+                    Some(Token::Word(w)) => parser.field(&w)?,
+                    _ => return Err(parser.error("expected a field after \"order by\"")),
+                };
+                let desc = if parser.eat_keyword("desc") {
+                    true
+                } else {
+                    parser.eat_keyword("asc");
+                    false
+                };
+                order.push((field, desc));
+                if !matches!(parser.peek(), Some(Token::Comma)) {
+                    break;
+                }
+                parser.pos += 1;
+            }
+        }
+
+        let mut limit = None;
+        if parser.eat_keyword("limit") {
+            limit = match parser.next() {
+                Some(Token::Word(w)) => Some(
+                    w.parse::<usize>()
+                        .map_err(|_| parser.error(&format!("invalid limit \"{w}\"")))?,
+                ),
+                _ => return Err(parser.error("expected a number after \"limit\"")),
+            };
+        }
+
+        if parser.pos < parser.tokens.len() {
+            return Err(parser.error("unexpected trailing input"));
+        }
+        Ok(Self { expr, order, limit })
+    }
+
+    pub fn matches(&self, name: &str, p: &PlacedPoint) -> bool {
+        self.expr.eval(name, p)
+    }
+
+    /// Filters, orders and limits `points`, keeping their original order among equal sort keys.
+    pub fn select<'a>(
+        &self,
+        points: impl Iterator<Item = (&'a String, &'a PlacedPoint)>,
+    ) -> Vec<(&'a String, &'a PlacedPoint)> {
+        let mut out: Vec<_> = points.filter(|(n, p)| self.matches(n, p)).collect();
+        if !self.order.is_empty() {
+            out.sort_by(|(an, ap), (bn, bp)| {
+                for (field, desc) in &self.order {
+                    let a = field.value(an, ap);
+                    let b = field.value(bn, bp);
+                    let ord = compare_values(a.first(), b.first());
+                    let ord = if *desc { ord.reverse() } else { ord };
+                    if ord.is_ne() {
+                        return ord;
+                    }
+                }
+                std::cmp::Ordering::Equal
+            });
+        }
+        if let Some(limit) = self.limit {
+            out.truncate(limit);
+        }
+        out
+    }
+}
+
+#[derive(Debug)]
+enum Expr {
+    Const(bool),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    /// Bare word: point name or tag.
+    NameOrTag(String),
+    NameOrTagRegex(Regex),
+    Compare(Field, Op, Rhs),
+}
+
+impl Expr {
+    fn eval(&self, name: &str, p: &PlacedPoint) -> bool {
+        match self {
+            Self::Const(b) => *b,
+            Self::Not(e) => !e.eval(name, p),
+            Self::And(a, b) => a.eval(name, p) && b.eval(name, p),
+            Self::Or(a, b) => a.eval(name, p) || b.eval(name, p),
+            Self::NameOrTag(w) => name == w || p.meta.tags.iter().any(|t| t == w),
+            Self::NameOrTagRegex(re) => {
+                re.is_match(name) || p.meta.tags.iter().any(|t| re.is_match(t))
+            }
+            Self::Compare(field, op, rhs) => {
+                let values = field.value(name, p);
+                // `!=` holds when no value is equal, so that `tag != thumb` excludes tagged keys.
+                match op {
+                    Op::Ne => values.iter().all(|v| rhs.test(v, *op)),
+                    _ => values.iter().any(|v| rhs.test(v, *op)),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug)]
+enum Rhs {
+    Text { text: String, number: Option<f64> },
+    Regex(Regex),
+}
+
+impl Rhs {
+    fn test(&self, v: &FieldValue, op: Op) -> bool {
+        match self {
+            Self::Regex(re) => {
+                let hit = re.is_match(&v.to_text());
+                if op == Op::Ne { !hit } else { hit }
+            }
+            Self::Text { text, number } => {
+                let num = match (v, number) {
+                    (FieldValue::Num(a), Some(b)) => Some((*a, *b)),
+                    _ => None,
+                };
+                match (op, num) {
+                    (Op::Eq, Some((a, b))) => (a - b).abs() < 1e-9,
+                    (Op::Ne, Some((a, b))) => (a - b).abs() >= 1e-9,
+                    (Op::Eq, None) => v.to_text() == *text,
+                    (Op::Ne, None) => v.to_text() != *text,
+                    (Op::Lt, Some((a, b))) => a < b,
+                    (Op::Le, Some((a, b))) => a <= b,
+                    (Op::Gt, Some((a, b))) => a > b,
+                    (Op::Ge, Some((a, b))) => a >= b,
+                    (_, None) => false,
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Field {
+    X,
+    Y,
+    R,
+    Name,
+    Mirrored,
+    Tags,
+    /// Path into the key metadata, e.g. `zone.name` or `width`.
+    Meta(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum FieldValue {
+    Num(f64),
+    Text(String),
+}
+
+impl FieldValue {
+    fn to_text(&self) -> String {
+        match self {
+            Self::Num(n) => n.to_string(),
+            Self::Text(s) => s.clone(),
+        }
+    }
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "x" => Self::X,
+            "y" => Self::Y,
+            "r" => Self::R,
+            "name" => Self::Name,
+            "mirrored" => Self::Mirrored,
+            "tag" | "tags" => Self::Tags,
+            "zone" => Self::Meta("zone.name".to_string()),
+            "col" | "column" => Self::Meta("col.name".to_string()),
+            "row" => Self::Meta("row".to_string()),
+            other => Self::Meta(other.strip_prefix("meta.")?.to_string()),
+        })
+    }
+
+    fn value(&self, name: &str, p: &PlacedPoint) -> Vec<FieldValue> {
+        match self {
+            Self::X => vec![FieldValue::Num(p.x)],
+            Self::Y => vec![FieldValue::Num(p.y)],
+            Self::R => vec![FieldValue::Num(p.r)],
+            Self::Name => vec![FieldValue::Text(name.to_string())],
+            Self::Mirrored => vec![FieldValue::Text(
+                p.meta.mirrored.unwrap_or(false).to_string(),
+            )],
+            Self::Tags => p
+                .meta
+                .tags
+                .iter()
+                .map(|t| FieldValue::Text(t.clone()))
+                .collect(),
+            Self::Meta(path) => {
+                let meta = &p.meta;
+                let text = |s: &str| vec![FieldValue::Text(s.to_string())];
+                match path.as_str() {
+                    "zone" | "zone.name" => text(&meta.zone.name),
+                    "col" | "col.name" => text(&meta.col.name),
+                    "row" => text(&meta.row),
+                    "mirrored" => text(&meta.mirrored.unwrap_or(false).to_string()),
+                    "bind" => meta.bind.iter().map(|b| FieldValue::Num(*b)).collect(),
+                    _ => match key_to_value(meta).get_path(path) {
+                        Some(Value::Seq(seq)) => seq.iter().filter_map(scalar).collect(),
+                        Some(v) => scalar(v).into_iter().collect(),
+                        None => Vec::new(),
+                    },
+                }
+            }
+        }
+    }
+}
+
+fn scalar(v: &Value) -> Option<FieldValue> {
+    match v {
+        Value::Number(n) => Some(FieldValue::Num(*n)),
+        Value::String(s) => Some(FieldValue::Text(s.clone())),
+        Value::Bool(b) => Some(FieldValue::Text(b.to_string())),
+        _ => None,
+    }
+}
+
+/// Missing values sort last; numbers before text.
+fn compare_values(a: Option<&FieldValue>, b: Option<&FieldValue>) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+    match (a, b) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(FieldValue::Num(a)), Some(FieldValue::Num(b))) => a.total_cmp(b),
+        (Some(FieldValue::Num(_)), Some(FieldValue::Text(_))) => Ordering::Less,
+        (Some(FieldValue::Text(_)), Some(FieldValue::Num(_))) => Ordering::Greater,
+        (Some(FieldValue::Text(a)), Some(FieldValue::Text(b))) => a.cmp(b),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    /// Quoted text; never a keyword or number.
+    Quoted(String),
+    Regex(String),
+    Op(Op),
+    /// A selector such as `zone:`, without the colon.
+    Selector(String),
+    LParen,
+    RParen,
+    Comma,
+}
+
+fn tokenize(src: &str, at: &str) -> Result<Vec<Token>, LayoutError> {
+    let err = |message: String| LayoutError::InvalidFilter {
+        at: at.to_string(),
+        message,
+    };
+    let chars: Vec<char> = src.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            '=' | '!' | '<' | '>' => {
+                let next = chars.get(i + 1).copied();
+                let (op, len) = match (c, next) {
+                    ('=', Some('=')) => (Op::Eq, 2),
+                    ('=', _) => (Op::Eq, 1),
+                    ('!', Some('=')) => (Op::Ne, 2),
+                    ('<', Some('=')) => (Op::Le, 2),
+                    ('<', _) => (Op::Lt, 1),
+                    ('>', Some('=')) => (Op::Ge, 2),
+                    ('>', _) => (Op::Gt, 1),
+                    _ => return Err(err(format!("unexpected \"{c}\""))),
+                };
+                tokens.push(Token::Op(op));
+                i += len;
+            }
+            '"' | '\'' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&ch| ch == c)
+                    .ok_or_else(|| err("unterminated string".to_string()))?;
+                tokens.push(Token::Quoted(chars[i + 1..i + 1 + end].iter().collect()));
+                i += end + 2;
+            }
+            '/' => {
+                let mut j = i + 1;
+                while j < chars.len() && chars[j] != '/' {
+                    if chars[j] == '\\' {
+                        j += 1;
+                    }
+                    j += 1;
+                }
+                if j >= chars.len() {
+                    return Err(err("unterminated regex".to_string()));
+                }
+                j += 1;
+                while j < chars.len() && chars[j].is_ascii_alphabetic() {
+                    j += 1;
+                }
+                tokens.push(Token::Regex(chars[i..j].iter().collect()));
+                i = j;
+            }
+            _ => {
+                let start = i;
+                while i < chars.len()
+                    && !chars[i].is_whitespace()
+                    && !matches!(
+                        chars[i],
+                        '(' | ')' | ',' | '=' | '!' | '<' | '>' | '"' | '\'' | ':'
+                    )
+                {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                if chars.get(i) == Some(&':') {
+                    tokens.push(Token::Selector(word));
+                    i += 1;
+                } else {
+                    tokens.push(Token::Word(word));
+                }
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    units: &'a Units,
+    at: &'a str,
+}
+
+const KEYWORDS: [&str; 8] = ["and", "or", "not", "order", "by", "asc", "desc", "limit"];
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> LayoutError {
+        LayoutError::InvalidFilter {
+            at: self.at.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let t = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        t
+    }
+
+    fn peek_keyword(&self, kw: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(w)) if w.eq_ignore_ascii_case(kw))
+    }
+
+    fn eat_keyword(&mut self, kw: &str) -> bool {
+        let hit = self.peek_keyword(kw);
+        if hit {
+            self.pos += 1;
+        }
+        hit
+    }
+
+    fn expr(&mut self) -> Result<Expr, LayoutError> {
+        let mut lhs = self.and()?;
+        while self.eat_keyword("or") {
+            lhs = Expr::Or(Box::new(lhs), Box::new(self.and()?));
+        }
+        Ok(lhs)
+    }
+
+    fn and(&mut self) -> Result<Expr, LayoutError> {
+        let mut lhs = self.unary()?;
+        while self.eat_keyword("and") {
+            lhs = Expr::And(Box::new(lhs), Box::new(self.unary()?));
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr, LayoutError> {
+        if self.eat_keyword("not") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, LayoutError> {
+        match self.next() {
+            Some(Token::LParen) => {
+                let e = self.expr()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(e),
+                    _ => Err(self.error("expected \")\"")),
+                }
+            }
+            Some(Token::Selector(sel)) => {
+                let field = self.field(&sel)?;
+                let rhs = self.rhs()?;
+                Ok(Expr::Compare(field, Op::Eq, rhs))
+            }
+            Some(Token::Regex(raw)) => Ok(Expr::NameOrTagRegex(self.regex(&raw)?)),
+            Some(Token::Word(w)) if matches!(self.peek(), Some(Token::Op(_))) => {
+                let field = self.field(&w)?;
+                let Some(Token::Op(op)) = self.next() else {
+                    unreachable!("peeked an operator");
+                };
+                let rhs = self.rhs()?;
+                Ok(Expr::Compare(field, op, rhs))
+            }
+            Some(Token::Word(w)) if w.eq_ignore_ascii_case("true") => Ok(Expr::Const(true)),
+            Some(Token::Word(w)) if w.eq_ignore_ascii_case("false") => Ok(Expr::Const(false)),
+            Some(Token::Word(w)) if KEYWORDS.iter().any(|k| w.eq_ignore_ascii_case(k)) => {
+                Err(self.error(&format!("unexpected \"{w}\"")))
+            }
+            Some(Token::Word(w) | Token::Quoted(w)) => Ok(Expr::NameOrTag(w)),
+            Some(other) => Err(self.error(&format!("unexpected {other:?}"))),
+            None => Err(self.error("unexpected end of query")),
+        }
+    }
+
+    fn field(&self, name: &str) -> Result<Field, LayoutError> {
+        Field::from_name(name).ok_or_else(|| self.error(&format!("unknown field \"{name}\"")))
+    }
+
+    fn rhs(&mut self) -> Result<Rhs, LayoutError> {
+        match self.next() {
+            Some(Token::Regex(raw)) => Ok(Rhs::Regex(self.regex(&raw)?)),
+            Some(Token::Quoted(text)) => Ok(Rhs::Text { text, number: None }),
+            Some(Token::Word(text)) => {
+                let number = text
+                    .parse::<f64>()
+                    .ok()
+                    .or_else(|| self.units.eval(self.at, &text).ok());
+                Ok(Rhs::Text { text, number })
+            }
+            _ => Err(self.error("expected a value")),
+        }
+    }
+
+    fn regex(&self, raw: &str) -> Result<Regex, LayoutError> {
+        parse_regex_literal(raw).ok_or_else(|| self.error(&format!("invalid regex {raw}")))
+    }
+}
@@ -0,0 +1,314 @@
+//! `points.import`: keys read from CSV or JSON coordinate lists.
+//!
+//! Each row is `name, x, y, r[, width, height, tags]`. CSV files may start with a header naming
+//! those columns (plus optional `column` and `row`), recognised by consisting of column names
+//! only; otherwise columns are positional. JSON files
+//! hold an array of objects with the same keys, or of positional arrays. Imported keys start from
+//! `points.key` and the source's own `key`, like the keys of a zone.
+
+use std::path::Path;
+
+use indexmap::IndexMap;
+
+use ergogen_core::vfs;
+use ergogen_parser::{Units, Value, extend_all};
+
+use crate::points::{
+    ColMeta, KeyMeta, LayoutError, PlacedPoint, PointsOutput, ZoneMeta, default_key, eval_number,
+    eval_string, key_to_value, value_to_keymeta,
+};
+
+const POSITIONAL: [&str; 7] = ["name", "x", "y", "r", "width", "height", "tags"];
+/// Columns a CSV header may name.
+const COLUMNS: [&str; 9] = [
+    "name", "x", "y", "r", "width", "height", "tags", "column", "row",
+];
+
+#[derive(Debug, Default)]
+struct Row {
+    name: String,
+    x: f64,
+    y: f64,
+    r: f64,
+    width: Option<f64>,
+    height: Option<f64>,
+    tags: Vec<String>,
+    column: Option<String>,
+    row: Option<String>,
+}
+
+/// Loads every source listed under `points.import`.
+///
+/// A source is a path, or `{ file, zone, key }` where `zone` names the zone the keys are grouped
+/// under (default `import`) and `key` overrides `global_key` for them. Paths are looked up in the
+/// virtual file map first, then on disk.
+pub(crate) fn parse_imports(
+    raw: &Value,
+    global_key: &Value,
+    units: &Units,
+) -> Result<PointsOutput, LayoutError> {
+    let sources: Vec<(String, &Value)> = match raw {
+        Value::Null => Vec::new(),
+        Value::Seq(seq) => seq
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (format!("points.import[{}]", i + 1), v))
+            .collect(),
+        other => vec![("points.import".to_string(), other)],
+    };
+
+    let mut out = PointsOutput::new();
+    for (at, source) in sources {
+        let (file, zone, key) = match source {
+            Value::String(s) => (s.clone(), "import".to_string(), Value::Null),
+            Value::Map(m) => {
+                let file =
+                    eval_string(m.get("file").unwrap_or(&Value::Null), &format!("{at}.file"))?;
+                let zone = match m.get("zone") {
+                    Some(v) => eval_string(v, &format!("{at}.zone"))?,
+                    None => "import".to_string(),
+                };
+                (file, zone, m.get("key").cloned().unwrap_or(Value::Null))
+            }
+            _ => {
+                return Err(LayoutError::InvalidImport {
+                    at,
+                    message: "import source must be a path or an object".to_string(),
+                });
+            }
+        };
+
+        let path = Path::new(&file);
+        let text = vfs::read_to_string(path).map_err(|e| LayoutError::InvalidImport {
+            at: at.clone(),
+            message: format!("could not read \"{file}\": {e}"),
+        })?;
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase);
+        let rows = match ext.as_deref() {
+            Some("csv") => parse_csv(&text, units, &at)?,
+            Some("json") => parse_json(&text, units, &at)?,
+            _ => {
+                return Err(LayoutError::InvalidImport {
+                    at,
+                    message: format!(
+                        "unsupported import format for \"{file}\" (expected .csv or .json)"
+                    ),
+                });
+            }
+        };
+
+        let base = value_to_keymeta(
+            &extend_all(&[key_to_value(&default_key(units)), global_key.clone(), key]),
+            units,
+            &format!("{at}.key"),
+        )?;
+        for (name, p) in rows_to_points(rows, &zone, base) {
+            if out.contains_key(&name) {
+                return Err(LayoutError::DuplicateKey { name });
+            }
+            out.insert(name, p);
+        }
+    }
+    Ok(out)
+}
+
+fn parse_csv(text: &str, units: &Units, at: &str) -> Result<Vec<Row>, LayoutError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .comment(Some(b'#'))
+        .from_reader(text.as_bytes());
+
+    let mut header: Option<Vec<String>> = None;
+    let mut rows = Vec::new();
+    for (idx, record) in reader.records().enumerate() {
+        let record = record.map_err(|e| LayoutError::InvalidImport {
+            at: at.to_string(),
+            message: e.to_string(),
+        })?;
+        let fields: Vec<&str> = record.iter().collect();
+        // Data fields may be expressions (`1u`), so a header is told apart by its column names.
+        if idx == 0
+            && fields
+                .iter()
+                .all(|f| COLUMNS.contains(&f.to_ascii_lowercase().as_str()))
+        {
+            header = Some(fields.iter().map(|f| f.to_ascii_lowercase()).collect());
+            continue;
+        }
+        let columns: Vec<&str> = match &header {
+            Some(h) => h.iter().map(String::as_str).collect(),
+            None => POSITIONAL.to_vec(),
+        };
+        let mut map = IndexMap::new();
+        for (col, field) in columns.iter().zip(&fields) {
+            if !field.is_empty() {
+                map.insert((*col).to_string(), Value::String((*field).to_string()));
+            }
+        }
+        rows.push(parse_row(&map, units, &format!("{at}[{}]", idx + 1))?);
+    }
+    Ok(rows)
+}
+
+fn parse_json(text: &str, units: &Units, at: &str) -> Result<Vec<Row>, LayoutError> {
+    let value = Value::try_from_json_str(text).map_err(|e| LayoutError::InvalidImport {
+        at: at.to_string(),
+        message: e.to_string(),
+    })?;
+    let Value::Seq(items) = value else {
+        return Err(LayoutError::InvalidImport {
+            at: at.to_string(),
+            message: "JSON import must be an array".to_string(),
+        });
+    };
+    items
+        .iter()
+        .enumerate()
+        .map(|(idx, item)| {
+            let at = format!("{at}[{}]", idx + 1);
+            match item {
+                Value::Map(m) => parse_row(m, units, &at),
+                Value::Seq(fields) => {
+                    let map = POSITIONAL
+                        .iter()
+                        .zip(fields)
+                        .map(|(k, v)| ((*k).to_string(), v.clone()))
+                        .collect();
+                    parse_row(&map, units, &at)
+                }
+                _ => Err(LayoutError::InvalidImport {
+                    at,
+                    message: "row must be an object or an array".to_string(),
+                }),
+            }
+        })
+        .collect()
+}
+
+fn parse_row(m: &IndexMap<String, Value>, units: &Units, at: &str) -> Result<Row, LayoutError> {
+    let required = |key: &str| {
+        m.get(key).ok_or_else(|| LayoutError::InvalidImport {
+            at: at.to_string(),
+            message: format!("missing \"{key}\""),
+        })
+    };
+    let number = |key: &str| -> Result<Option<f64>, LayoutError> {
+        match m.get(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(v) => eval_number(units, v, &format!("{at}.{key}")).map(Some),
+        }
+    };
+    let string = |key: &str| -> Result<Option<String>, LayoutError> {
+        match m.get(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(Value::Number(n)) => Ok(Some(n.to_string())),
+            Some(v) => eval_string(v, &format!("{at}.{key}")).map(Some),
+        }
+    };
+
+    let name = match required("name")? {
+        Value::Number(n) => n.to_string(),
+        v => eval_string(v, &format!("{at}.name"))?,
+    };
+    let tags = match m.get("tags") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::String(s)) => s
+            .split(|c: char| c == ';' || c.is_whitespace())
+            .filter(|t| !t.is_empty())
+            .map(str::to_string)
+            .collect(),
+        Some(Value::Seq(seq)) => seq
+            .iter()
+            .map(|t| eval_string(t, &format!("{at}.tags")))
+            .collect::<Result<_, _>>()?,
+        Some(_) => {
+            return Err(LayoutError::InvalidImport {
+                at: format!("{at}.tags"),
+                message: "tags must be a string or an array".to_string(),
+            });
+        }
+    };
+
+    Ok(Row {
+        x: eval_number(units, required("x")?, &format!("{at}.x"))?,
+        y: eval_number(units, required("y")?, &format!("{at}.y"))?,
+        r: number("r")?.unwrap_or(0.0),
+        width: number("width")?,
+        height: number("height")?,
+        tags,
+        column: string("column")?,
+        row: string("row")?,
+        name,
+    })
+}
+
+/// Turns rows into keys of one zone. Rows without an explicit `column` are grouped into columns
+/// of keys whose x positions lie within half a key width of each other, so that autobind sees the
+/// same column structure as for generated zones.
+fn rows_to_points(rows: Vec<Row>, zone: &str, base: KeyMeta) -> PointsOutput {
+    let mut order: Vec<usize> = (0..rows.len()).collect();
+    order.sort_by(|&a, &b| rows[a].x.total_cmp(&rows[b].x));
+    let mut inferred = vec![String::new(); rows.len()];
+    let mut column_start: Option<f64> = None;
+    let mut count = 0;
+    for &i in &order {
+        if rows[i].column.is_some() {
+            continue;
+        }
+        let half = rows[i].width.unwrap_or(base.width) / 2.0;
+        if column_start.is_none_or(|start| rows[i].x - start > half) {
+            count += 1;
+            column_start = Some(rows[i].x);
+        }
+        inferred[i] = format!("c{count}");
+    }
+
+    let mut columns: IndexMap<String, f64> = IndexMap::new();
+    for (row, inferred) in rows.iter().zip(&inferred) {
+        let col = row.column.as_ref().unwrap_or(inferred);
+        let x = columns.entry(col.clone()).or_insert(row.x);
+        *x = x.min(row.x);
+    }
+    columns.sort_by(|_, a, _, b| a.total_cmp(b));
+    let columns_order: Vec<String> = columns.into_keys().collect();
+
+    let mut out = PointsOutput::new();
+    for (row, inferred) in rows.into_iter().zip(inferred) {
+        let mut meta = base.clone();
+        meta.zone = ZoneMeta {
+            name: zone.to_string(),
+            columns_order: columns_order.clone(),
+        };
+        meta.col = ColMeta {
+            name: row.column.unwrap_or(inferred),
+        };
+        meta.row = row.row.unwrap_or_else(|| row.name.clone());
+        meta.colrow = row.name.clone();
+        meta.name = row.name.clone();
+        meta.rotate = row.r;
+        if !row.tags.is_empty() {
+            meta.tags = row.tags;
+        }
+        if let Some(w) = row.width {
+            meta.width = w;
+        }
+        if let Some(h) = row.height {
+            meta.height = h;
+        }
+        out.insert(
+            row.name,
+            PlacedPoint {
+                x: row.x,
+                y: row.y,
+                r: row.r,
+                meta,
+            },
+        );
+    }
+    out
+}
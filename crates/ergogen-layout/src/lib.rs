@@ -1,4 +1,13 @@
 pub mod anchor;
+pub mod diff;
+pub mod effort;
+pub mod ergonomics;
+pub mod filter;
+mod import;
+pub mod matrix;
 mod points;
 
-pub use points::{LayoutError, PlacedPoint, PointsOutput, parse_points};
+pub use points::{
+    Asymmetry, BindSource, BindTrace, LayoutError, PlacedPoint, PointsOutput, SideBind,
+    parse_points, parse_points_with_bind_trace, place_free_key,
+};
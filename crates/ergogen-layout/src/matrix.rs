@@ -0,0 +1,303 @@
+//! `points.matrix`: assigns every key a row and column net of the switch matrix.
+//!
+//! Keys are chained into physical columns by following each key's own "up" direction to the
+//! nearest key above it. Each chain keeps a single, uncrossed column net on consecutive rows.
+//! Chains are then packed into matrix columns: short ones (thumb clusters, outer pinky keys, a
+//! flat row of keys) share a column on rows it leaves free. Every row count from the longest chain
+//! upwards is tried, and the one needing the fewest MCU pins (`rows + columns`) wins, fewer rows
+//! on a tie.
+
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use ergogen_parser::Value;
+
+use crate::points::{LayoutError, PlacedPoint, PointsOutput, eval_bool_opt, eval_string};
+
+#[derive(Debug, Clone)]
+pub struct MatrixOptions {
+    /// Solve each half of a mirrored board on its own, reusing net names for both halves.
+    pub split: bool,
+    pub row_prefix: String,
+    pub column_prefix: String,
+}
+
+impl Default for MatrixOptions {
+    fn default() -> Self {
+        Self {
+            split: true,
+            row_prefix: "row".to_string(),
+            column_prefix: "col".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MatrixAssignment {
+    pub keys: IndexMap<String, MatrixPosition>,
+    /// Row count of the largest half (or the whole board when not split).
+    pub rows: usize,
+    /// Column count of the largest half (or the whole board when not split).
+    pub columns: usize,
+}
+
+impl MatrixAssignment {
+    /// MCU pins needed per controller.
+    pub fn pins(&self) -> usize {
+        self.rows + self.columns
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MatrixPosition {
+    /// Zero-based, counted from the bottom of the column.
+    pub row: usize,
+    /// Zero-based, left to right (right to left on the mirrored half).
+    pub column: usize,
+    pub row_net: String,
+    pub column_net: String,
+}
+
+/// Parses `points.matrix`: `true`, or an object with `split`, `row_prefix` and `column_prefix`.
+/// Returns `None` when the solver is off.
+pub fn parse_matrix_options(raw: &Value) -> Result<Option<MatrixOptions>, LayoutError> {
+    let at = "points.matrix";
+    let mut options = MatrixOptions::default();
+    match raw {
+        Value::Null | Value::Bool(false) => return Ok(None),
+        Value::Bool(true) => {}
+        Value::Map(m) => {
+            if let Some(split) = eval_bool_opt(m.get("split"), &format!("{at}.split"))? {
+                options.split = split;
+            }
+            if let Some(v) = m.get("row_prefix") {
+                options.row_prefix = eval_string(v, &format!("{at}.row_prefix"))?;
+            }
+            if let Some(v) = m.get("column_prefix") {
+                options.column_prefix = eval_string(v, &format!("{at}.column_prefix"))?;
+            }
+        }
+        _ => {
+            return Err(LayoutError::InvalidMatrix {
+                at: at.to_string(),
+                message: "matrix must be a boolean or an object".to_string(),
+            });
+        }
+    }
+    Ok(Some(options))
+}
+
+/// Assigns row and column nets to every key. Net names are the prefix plus a one-based index
+/// (`row1`, `col3`).
+pub fn solve_matrix(points: &PointsOutput, options: &MatrixOptions) -> MatrixAssignment {
+    let half = |mirrored: bool| -> Vec<&str> {
+        points
+            .iter()
+            .filter(|(_, p)| !options.split || p.meta.mirrored.unwrap_or(false) == mirrored)
+            .map(|(name, _)| name.as_str())
+            .collect()
+    };
+    let halves = if options.split {
+        vec![half(false), half(true)]
+    } else {
+        vec![half(false)]
+    };
+
+    let mut positions: IndexMap<&str, (usize, usize)> = IndexMap::new();
+    let (mut rows, mut columns) = (0, 0);
+    for half in halves.iter().filter(|h| !h.is_empty()) {
+        let (half_rows, half_columns) = solve_half(points, half, &mut positions);
+        rows = rows.max(half_rows);
+        columns = columns.max(half_columns);
+    }
+
+    let keys = points
+        .keys()
+        .map(|name| {
+            let (row, column) = positions[name.as_str()];
+            let position = MatrixPosition {
+                row,
+                column,
+                row_net: format!("{}{}", options.row_prefix, row + 1),
+                column_net: format!("{}{}", options.column_prefix, column + 1),
+            };
+            (name.clone(), position)
+        })
+        .collect();
+    MatrixAssignment {
+        keys,
+        rows,
+        columns,
+    }
+}
+
+/// Solves one controller's keys, returning its row and column counts.
+fn solve_half<'a>(
+    points: &PointsOutput,
+    names: &[&'a str],
+    positions: &mut IndexMap<&'a str, (usize, usize)>,
+) -> (usize, usize) {
+    let keys: Vec<&PlacedPoint> = names.iter().map(|n| &points[*n]).collect();
+
+    // Each key links to the closest key above it; a key claimed by several keys below keeps the
+    // closest one, so that links form disjoint chains.
+    let mut up: Vec<Option<(usize, f64)>> = (0..keys.len())
+        .map(|i| {
+            (0..keys.len())
+                .filter(|&j| j != i)
+                .filter_map(|j| above(keys[i], keys[j]).map(|cost| (j, cost)))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+        })
+        .collect();
+    let mut claimed: IndexMap<usize, (usize, f64)> = IndexMap::new();
+    for (i, link) in up.iter().enumerate() {
+        if let Some((j, cost)) = *link
+            && claimed.get(&j).is_none_or(|(_, best)| cost < *best)
+        {
+            claimed.insert(j, (i, cost));
+        }
+    }
+    for (i, link) in up.iter_mut().enumerate() {
+        if let Some((j, _)) = *link
+            && claimed.get(&j).map(|(below, _)| *below) != Some(i)
+        {
+            *link = None;
+        }
+    }
+
+    let has_below: Vec<bool> = (0..keys.len())
+        .map(|j| claimed.get(&j).is_some_and(|(i, _)| up[*i].is_some()))
+        .collect();
+    let mut chains: Vec<Vec<usize>> = Vec::new();
+    let mut visited = vec![false; keys.len()];
+    for start in (0..keys.len()).filter(|&i| !has_below[i]) {
+        let mut chain = Vec::new();
+        let mut current = Some(start);
+        while let Some(i) = current.filter(|&i| !visited[i]) {
+            visited[i] = true;
+            chain.push(i);
+            current = up[i].map(|(j, _)| j);
+        }
+        chains.push(chain);
+    }
+    // Keys only reachable through a cycle of links (possible with odd rotations) start their own.
+    for (i, seen) in visited.iter_mut().enumerate() {
+        if !*seen {
+            *seen = true;
+            chains.push(vec![i]);
+        }
+    }
+
+    let centroids: Vec<(f64, f64)> = chains
+        .iter()
+        .map(|chain| {
+            let n = chain.len() as f64;
+            chain.iter().fold((0.0, 0.0), |(x, y), &i| {
+                (x + keys[i].x / n, y + keys[i].y / n)
+            })
+        })
+        .collect();
+
+    let longest = chains.iter().map(Vec::len).max().unwrap_or(0);
+    let mut best: Option<(usize, Vec<Column>)> = None;
+    for rows in longest..=keys.len() {
+        let best_pins = best.as_ref().map(|(r, cols)| r + cols.len());
+        if let Some(pins) = best_pins {
+            // Every layout needs a column, and enough columns to fit every key.
+            if rows + 1 >= pins {
+                break;
+            }
+            if rows + keys.len().div_ceil(rows) >= pins {
+                continue;
+            }
+        }
+        let cols = pack(&chains, &centroids, rows);
+        if best_pins.is_none_or(|pins| rows + cols.len() < pins) {
+            best = Some((rows, cols));
+        }
+    }
+    let (rows, mut cols) = best.unwrap_or_default();
+
+    // Columns are numbered left to right by the position of their main chain.
+    cols.sort_by(|a, b| a.x.total_cmp(&b.x));
+    let mirrored = keys
+        .first()
+        .is_some_and(|p| p.meta.mirrored.unwrap_or(false));
+    if mirrored {
+        cols.reverse();
+    }
+    for (column, col) in cols.iter().enumerate() {
+        for &(key, row) in &col.members {
+            positions.insert(names[key], (row, column));
+        }
+    }
+    (rows, cols.len())
+}
+
+struct Column {
+    used: Vec<bool>,
+    x: f64,
+    y: f64,
+    /// (key, row)
+    members: Vec<(usize, usize)>,
+}
+
+/// Packs `chains` into columns of `rows` rows. Longest chains claim columns first; each shorter
+/// chain joins the nearest column that has a free, contiguous run of rows for it.
+fn pack(chains: &[Vec<usize>], centroids: &[(f64, f64)], rows: usize) -> Vec<Column> {
+    let mut order: Vec<usize> = (0..chains.len()).collect();
+    order.sort_by(|&a, &b| {
+        chains[b]
+            .len()
+            .cmp(&chains[a].len())
+            .then(centroids[a].0.total_cmp(&centroids[b].0))
+    });
+    let mut cols: Vec<Column> = Vec::new();
+    for c in order {
+        let chain = &chains[c];
+        let (cx, cy) = centroids[c];
+        let mut best: Option<(usize, usize, f64)> = None;
+        for (idx, col) in cols.iter().enumerate() {
+            let Some(offset) =
+                (0..=rows - chain.len()).find(|&o| (o..o + chain.len()).all(|r| !col.used[r]))
+            else {
+                continue;
+            };
+            let dist = (col.x - cx).hypot(col.y - cy);
+            if best.is_none_or(|(_, _, d)| dist < d) {
+                best = Some((idx, offset, dist));
+            }
+        }
+        let (idx, offset) = match best {
+            Some((idx, offset, _)) => (idx, offset),
+            None => {
+                cols.push(Column {
+                    used: vec![false; rows],
+                    x: cx,
+                    y: cy,
+                    members: Vec::new(),
+                });
+                (cols.len() - 1, 0)
+            }
+        };
+        let col = &mut cols[idx];
+        for (r, &key) in chain.iter().enumerate() {
+            col.used[offset + r] = true;
+            col.members.push((key, offset + r));
+        }
+    }
+    cols
+}
+
+/// How well `other` fits as the next key up the column of `key`: the distance along the key's own
+/// column axis plus the sideways offset, or `None` when it is not directly above.
+fn above(key: &PlacedPoint, other: &PlacedPoint) -> Option<f64> {
+    let (sin, cos) = key.r.to_radians().sin_cos();
+    let (dx, dy) = (other.x - key.x, other.y - key.y);
+    let along = -dx * sin + dy * cos;
+    let across = dx * cos + dy * sin;
+    let height = key.meta.height.max(other.meta.height);
+    let width = key.meta.width.min(other.meta.width);
+    (along > 0.25 * height && along < 1.75 * height && across.abs() < 0.5 * width)
+        .then_some(along + across.abs())
+}
@@ -7,6 +7,8 @@ use ergogen_parser::Units;
 use ergogen_parser::{Value, extend_all};
 
 use crate::anchor;
+use crate::import;
+use crate::matrix;
 
 #[derive(Debug, Error)]
 pub enum LayoutError {
@@ -42,6 +44,21 @@ pub enum LayoutError {
 
     #[error("{message}")]
     InvalidAnchor { at: String, message: String },
+
+    #[error("invalid ergonomics config at \"{at}\": {message}")]
+    InvalidErgonomics { at: String, message: String },
+
+    #[error("invalid keymap at \"{at}\": {message}")]
+    InvalidKeymap { at: String, message: String },
+
+    #[error("invalid points import at \"{at}\": {message}")]
+    InvalidImport { at: String, message: String },
+
+    #[error("invalid filter at \"{at}\": {message}")]
+    InvalidFilter { at: String, message: String },
+
+    #[error("invalid matrix config at \"{at}\": {message}")]
+    InvalidMatrix { at: String, message: String },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -67,6 +84,16 @@ pub struct KeyMeta {
     pub height: f64,
     pub padding: f64,
     pub autobind: f64,
+    /// Height of the key's switch plate above the base, for cases and 3D previews.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub z: f64,
+    /// Pitch in degrees about the key's own x axis (positive raises the top edge).
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub tilt: f64,
+    /// Roll in degrees about the key's own y axis (positive raises the inner, right edge); negated
+    /// on mirrored keys.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub tent: f64,
     pub skip: bool,
     pub asym: Asymmetry,
     pub colrow: String,
@@ -77,6 +104,14 @@ pub struct KeyMeta {
     pub bind: [f64; 4],
     pub mirrored: Option<bool>,
     pub mirror: Option<Value>,
+    /// Matrix nets assigned by `points.matrix`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub row_net: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column_net: Option<String>,
+    /// The outline a `from_outline` key was sampled from. `where: true` leaves these keys out.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from_outline: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -100,13 +135,28 @@ pub enum Asymmetry {
 
 pub type PointsOutput = IndexMap<String, PlacedPoint>;
 
+fn is_zero(v: &f64) -> bool {
+    *v == 0.0
+}
+
+/// Places the points of every zone except `from_outline` ones, which need generated outlines:
+/// `ergogen_outline::sample::parse_points_with_outline_samples` returns all of them.
 pub fn parse_points(canonical: &Value, units: &Units) -> Result<PointsOutput, LayoutError> {
+    let mut points = place_points(canonical, units)?;
+    perform_autobind(&mut points);
+    Ok(points)
+}
+
+/// Everything up to (but excluding) autobind, including matrix nets.
+fn place_points(canonical: &Value, units: &Units) -> Result<PointsOutput, LayoutError> {
     let points_v = canonical
         .get_path("points")
         .ok_or(LayoutError::MissingPoints)?;
-    let zones_v = points_v.get_path("zones").ok_or(LayoutError::ZonesNotMap)?;
-    let Value::Map(zones) = zones_v else {
-        return Err(LayoutError::ZonesNotMap);
+    let import_v = points_v.get_path("import");
+    let zones = match (points_v.get_path("zones"), import_v) {
+        (Some(Value::Map(zones)), _) => zones.clone(),
+        (None, Some(_)) => IndexMap::new(),
+        _ => return Err(LayoutError::ZonesNotMap),
     };
 
     let global_key = points_v
@@ -116,16 +166,24 @@ pub fn parse_points(canonical: &Value, units: &Units) -> Result<PointsOutput, La
     let global_rotate = eval_number_opt(units, points_v.get_path("rotate"), "points.rotate")?;
     let global_mirror = points_v.get_path("mirror").cloned();
 
-    let mut points: PointsOutput = IndexMap::new();
-    let mut ref_points: IndexMap<String, Point> = IndexMap::new();
+    // Imported keys come first so that zones can anchor to them.
+    let mut points: PointsOutput = match import_v {
+        Some(raw) => import::parse_imports(raw, &global_key, units)?,
+        None => IndexMap::new(),
+    };
+    let mut ref_points: IndexMap<String, Point> = points_to_ref(&points);
 
-    for (zone_name, zone_v) in zones {
+    for (zone_name, zone_v) in &zones {
         let zone_v = zone_v.clone();
         let mut zone = match zone_v {
             Value::Map(m) => m,
             Value::Null => IndexMap::new(),
             _ => continue,
         };
+        // Sampled from a generated outline, which needs these points first (see ergogen-outline).
+        if zone.contains_key("from_outline") {
+            continue;
+        }
 
         let anchor_raw = zone
             .shift_remove("anchor")
@@ -266,12 +324,57 @@ pub fn parse_points(canonical: &Value, units: &Units) -> Result<PointsOutput, La
     }
 
     points.retain(|_, p| !p.meta.skip);
-    perform_autobind(&mut points);
+
+    if let Some(options) =
+        matrix::parse_matrix_options(points_v.get_path("matrix").unwrap_or(&Value::Null))?
+    {
+        let assignment = matrix::solve_matrix(&points, &options);
+        for (name, position) in assignment.keys {
+            let meta = &mut points[&name].meta;
+            meta.row_net = Some(position.row_net);
+            meta.column_net = Some(position.column_net);
+        }
+    }
 
     Ok(points)
 }
 
-fn default_key(units: &Units) -> KeyMeta {
+/// A single key outside any column grid (e.g. sampled along an outline), at `name` in zone
+/// `zone_name`. It picks up the global and zone `key` settings like any other key, and is left
+/// out of autobind.
+pub fn place_free_key(
+    canonical: &Value,
+    units: &Units,
+    zone_name: &str,
+    name: &str,
+    [x, y, r]: [f64; 3],
+) -> Result<PlacedPoint, LayoutError> {
+    let raw_key = extend_all(&[
+        key_to_value(&default_key(units)),
+        canonical
+            .get_path("points.key")
+            .cloned()
+            .unwrap_or(Value::Null),
+        canonical
+            .get_path(&format!("points.zones.{zone_name}.key"))
+            .cloned()
+            .unwrap_or(Value::Null),
+    ]);
+    let mut meta = value_to_keymeta(&raw_key, units, &format!("{zone_name}.{name}"))?;
+    meta.zone = ZoneMeta {
+        name: zone_name.to_string(),
+        columns_order: Vec::new(),
+    };
+    meta.row = name.to_string();
+    meta.colrow = name.to_string();
+    meta.name = name.to_string();
+    meta.rotate = r;
+    meta.autobind = 0.0;
+    meta.bind = [0.0; 4];
+    Ok(PlacedPoint { x, y, r, meta })
+}
+
+pub(crate) fn default_key(units: &Units) -> KeyMeta {
     KeyMeta {
         stagger: units.get("$default_stagger").unwrap_or(0.0),
         spread: units.get("$default_spread").unwrap_or(19.0),
@@ -286,6 +389,9 @@ fn default_key(units: &Units) -> KeyMeta {
         height: units.get("$default_height").unwrap_or(18.0),
         padding: units.get("$default_padding").unwrap_or(19.0),
         autobind: units.get("$default_autobind").unwrap_or(10.0),
+        z: 0.0,
+        tilt: 0.0,
+        tent: 0.0,
         skip: false,
         asym: Asymmetry::Both,
         colrow: "{{col.name}}_{{row}}".to_string(),
@@ -301,6 +407,9 @@ fn default_key(units: &Units) -> KeyMeta {
         bind: [-1.0, -1.0, -1.0, -1.0],
         mirrored: None,
         mirror: None,
+        row_net: None,
+        column_net: None,
+        from_outline: None,
     }
 }
 
@@ -562,7 +671,7 @@ fn parse_axis(
     }
 }
 
-fn points_to_ref(points: &PointsOutput) -> IndexMap<String, Point> {
+pub(crate) fn points_to_ref(points: &PointsOutput) -> IndexMap<String, Point> {
     points
         .iter()
         .map(|(k, v)| {
@@ -588,6 +697,7 @@ fn perform_mirror(p: &PlacedPoint, axis_x: f64) -> Option<(String, PlacedPoint)>
     let mut mp = p.clone();
     mp.x = 2.0 * axis_x - mp.x;
     mp.r = -mp.r;
+    mp.meta.tent = -mp.meta.tent;
     // Mirror flips the local X axis, so left/right bind values must swap.
     mp.meta.bind.swap(1, 3);
 
@@ -600,7 +710,40 @@ fn perform_mirror(p: &PlacedPoint, axis_x: f64) -> Option<(String, PlacedPoint)>
     Some((mirrored_name, mp))
 }
 
-fn perform_autobind(points: &mut PointsOutput) {
+/// Why one side of a key ended up with its bind.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "source", rename_all = "lowercase")]
+pub enum BindSource {
+    /// Set in the config (`bind`), left untouched by autobind.
+    Explicit,
+    /// Filled by autobind because of `neighbour`.
+    Auto { neighbour: String },
+    /// Autobind found nothing to bind to on this side (or autobind is off).
+    None,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SideBind {
+    /// The bind stored on the key: `-1` for an unset side while autobind is off.
+    pub amount: f64,
+    #[serde(flatten)]
+    pub source: BindSource,
+}
+
+/// Per-key bind explanation, sides in `bind` order (top, right, bottom, left).
+pub type BindTrace = IndexMap<String, [SideBind; 4]>;
+
+/// Like [`parse_points`], but also explains how every key's `bind` came about.
+pub fn parse_points_with_bind_trace(
+    canonical: &Value,
+    units: &Units,
+) -> Result<(PointsOutput, BindTrace), LayoutError> {
+    let mut points = place_points(canonical, units)?;
+    let trace = perform_autobind(&mut points);
+    Ok((points, trace))
+}
+
+fn perform_autobind(points: &mut PointsOutput) -> BindTrace {
     #[derive(Default)]
     struct Bounds {
         min: f64,
@@ -640,9 +783,35 @@ fn perform_autobind(points: &mut PointsOutput) {
         b.max = b.max.max(p.y);
     }
 
-    for p in points.values_mut() {
+    // (zone, column, y, name) of every key, to name the neighbour behind each automatic bind.
+    let keys: Vec<(String, String, f64, String)> = points
+        .iter()
+        .map(|(name, p)| (mirrorzone(p), p.meta.col.name.clone(), p.y, name.clone()))
+        .collect();
+    let nearest = |zone: &str, col: &str, key: &dyn Fn(f64) -> Option<f64>| -> String {
+        keys.iter()
+            .filter(|(z, c, _, _)| z == zone && c == col)
+            .filter_map(|(_, _, y, name)| key(*y).map(|d| (d, name)))
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, name)| name.clone())
+            .unwrap_or_default()
+    };
+
+    let mut trace = BindTrace::new();
+    for (name, p) in points.iter_mut() {
+        let configured = p.meta.bind;
+        let mut sides = configured.map(|amount| SideBind {
+            amount,
+            source: if amount == -1.0 {
+                BindSource::None
+            } else {
+                BindSource::Explicit
+            },
+        });
+
         let autobind = p.meta.autobind;
         if autobind == 0.0 {
+            trace.insert(name.clone(), sides);
             continue;
         }
 
@@ -650,16 +819,31 @@ fn perform_autobind(points: &mut PointsOutput) {
         let col = p.meta.col.name.clone();
         let col_list = col_lists.get(&zone).cloned().unwrap_or_default();
         let col_bounds = bounds.get(&zone).and_then(|m| m.get(&col)).unwrap();
+        let y = p.y;
 
         let mut bind = p.meta.bind;
 
         // up
         if bind[0] == -1.0 {
-            bind[0] = if p.y < col_bounds.max { autobind } else { 0.0 };
+            bind[0] = if y < col_bounds.max { autobind } else { 0.0 };
+            if bind[0] > 0.0 {
+                let neighbour = nearest(&zone, &col, &|other| (other > y).then_some(other - y));
+                sides[0] = SideBind {
+                    amount: bind[0],
+                    source: BindSource::Auto { neighbour },
+                };
+            }
         }
         // down
         if bind[2] == -1.0 {
-            bind[2] = if p.y > col_bounds.min { autobind } else { 0.0 };
+            bind[2] = if y > col_bounds.min { autobind } else { 0.0 };
+            if bind[2] > 0.0 {
+                let neighbour = nearest(&zone, &col, &|other| (other < y).then_some(y - other));
+                sides[2] = SideBind {
+                    amount: bind[2],
+                    source: BindSource::Auto { neighbour },
+                };
+            }
         }
         // left
         if bind[3] == -1.0 {
@@ -669,10 +853,15 @@ fn perform_autobind(points: &mut PointsOutput) {
             {
                 let left_col = &col_list[col_index - 1];
                 if let Some(left) = bounds.get(&zone).and_then(|m| m.get(left_col))
-                    && p.y >= left.min
-                    && p.y <= left.max
+                    && y >= left.min
+                    && y <= left.max
                 {
                     bind[3] = autobind;
+                    let neighbour = nearest(&zone, left_col, &|other| Some((other - y).abs()));
+                    sides[3] = SideBind {
+                        amount: autobind,
+                        source: BindSource::Auto { neighbour },
+                    };
                 }
             }
         }
@@ -684,16 +873,26 @@ fn perform_autobind(points: &mut PointsOutput) {
             {
                 let right_col = &col_list[col_index + 1];
                 if let Some(right) = bounds.get(&zone).and_then(|m| m.get(right_col))
-                    && p.y >= right.min
-                    && p.y <= right.max
+                    && y >= right.min
+                    && y <= right.max
                 {
                     bind[1] = autobind;
+                    let neighbour = nearest(&zone, right_col, &|other| Some((other - y).abs()));
+                    sides[1] = SideBind {
+                        amount: autobind,
+                        source: BindSource::Auto { neighbour },
+                    };
                 }
             }
         }
 
         p.meta.bind = bind;
+        for (side, amount) in sides.iter_mut().zip(bind) {
+            side.amount = amount;
+        }
+        trace.insert(name.clone(), sides);
     }
+    trace
 }
 
 fn parse_anchor(
@@ -718,7 +917,11 @@ pub(crate) fn mirror_ref(ref_name: &str, mirror: bool) -> String {
     }
 }
 
-fn eval_number_opt(units: &Units, v: Option<&Value>, at: &str) -> Result<Option<f64>, LayoutError> {
+pub(crate) fn eval_number_opt(
+    units: &Units,
+    v: Option<&Value>,
+    at: &str,
+) -> Result<Option<f64>, LayoutError> {
     let Some(v) = v else { return Ok(None) };
     match v {
         Value::Number(n) => Ok(Some(*n)),
@@ -731,7 +934,7 @@ fn eval_number_opt(units: &Units, v: Option<&Value>, at: &str) -> Result<Option<
     }
 }
 
-fn eval_number(units: &Units, v: &Value, at: &str) -> Result<f64, LayoutError> {
+pub(crate) fn eval_number(units: &Units, v: &Value, at: &str) -> Result<f64, LayoutError> {
     eval_number_opt(units, Some(v), at)?.ok_or(LayoutError::InvalidNumber { at: at.to_string() })
 }
 
@@ -744,7 +947,7 @@ pub(crate) fn eval_bool_opt(v: Option<&Value>, at: &str) -> Result<Option<bool>,
     }
 }
 
-fn eval_string(v: &Value, at: &str) -> Result<String, LayoutError> {
+pub(crate) fn eval_string(v: &Value, at: &str) -> Result<String, LayoutError> {
     match v {
         Value::String(s) => Ok(s.clone()),
         _ => Err(LayoutError::InvalidString { at: at.to_string() }),
@@ -780,7 +983,7 @@ fn eval_tags(v: Option<&Value>, at: &str) -> Result<Vec<String>, LayoutError> {
     }
 }
 
-fn eval_xy(units: &Units, v: &Value, at: &str) -> Result<[f64; 2], LayoutError> {
+pub(crate) fn eval_xy(units: &Units, v: &Value, at: &str) -> Result<[f64; 2], LayoutError> {
     match v {
         Value::Seq(seq) if seq.len() == 2 => Ok([
             eval_number(units, &seq[0], at)?,
@@ -875,7 +1078,7 @@ pub(crate) fn eval_affect(v: &Value, at: &str) -> Result<Vec<char>, LayoutError>
     }
 }
 
-fn key_to_value(k: &KeyMeta) -> Value {
+pub(crate) fn key_to_value(k: &KeyMeta) -> Value {
     Value::Map(IndexMap::from([
         ("stagger".to_string(), Value::Number(k.stagger)),
         ("spread".to_string(), Value::Number(k.spread)),
@@ -899,6 +1102,9 @@ fn key_to_value(k: &KeyMeta) -> Value {
         ("height".to_string(), Value::Number(k.height)),
         ("padding".to_string(), Value::Number(k.padding)),
         ("autobind".to_string(), Value::Number(k.autobind)),
+        ("z".to_string(), Value::Number(k.z)),
+        ("tilt".to_string(), Value::Number(k.tilt)),
+        ("tent".to_string(), Value::Number(k.tent)),
         ("skip".to_string(), Value::Bool(k.skip)),
         (
             "asym".to_string(),
@@ -916,7 +1122,7 @@ fn key_to_value(k: &KeyMeta) -> Value {
     ]))
 }
 
-fn value_to_keymeta(v: &Value, units: &Units, at: &str) -> Result<KeyMeta, LayoutError> {
+pub(crate) fn value_to_keymeta(v: &Value, units: &Units, at: &str) -> Result<KeyMeta, LayoutError> {
     let Value::Map(m) = v else {
         return Err(LayoutError::InvalidString { at: at.to_string() });
     };
@@ -984,6 +1190,9 @@ fn value_to_keymeta(v: &Value, units: &Units, at: &str) -> Result<KeyMeta, Layou
             m.get("autobind").unwrap_or(&Value::Number(0.0)),
             &format!("{at}.autobind"),
         )?,
+        z: eval_number_opt(units, m.get("z"), &format!("{at}.z"))?.unwrap_or(0.0),
+        tilt: eval_number_opt(units, m.get("tilt"), &format!("{at}.tilt"))?.unwrap_or(0.0),
+        tent: eval_number_opt(units, m.get("tent"), &format!("{at}.tent"))?.unwrap_or(0.0),
         skip: eval_bool_opt(m.get("skip"), &format!("{at}.skip"))?.unwrap_or(false),
         asym: eval_asym(
             m.get("asym").unwrap_or(&Value::String("both".to_string())),
@@ -1008,6 +1217,9 @@ fn value_to_keymeta(v: &Value, units: &Units, at: &str) -> Result<KeyMeta, Layou
         bind: eval_trbl(units, m.get("bind"), &format!("{at}.bind"), -1.0)?,
         mirrored: None,
         mirror: m.get("mirror").cloned(),
+        row_net: None,
+        column_net: None,
+        from_outline: None,
     })
 }
 
@@ -1,7 +1,7 @@
 use indexmap::IndexMap;
 
 use ergogen_core::{Point, PointMeta};
-use ergogen_layout::anchor::parse_anchor;
+use ergogen_layout::anchor::{parse_anchor, parse_anchor_with_outlines};
 use ergogen_parser::{Units, Value};
 
 fn units() -> Units {
@@ -158,6 +158,93 @@ fn anchor_aggregate_intersect() {
     assert!(err.to_string().contains("do not intersect"));
 }
 
+#[test]
+fn anchor_aggregate_farthest_weighted_and_bbox() {
+    let units = units();
+    let points = sample_points();
+    let parse = |j: serde_json::Value| {
+        parse_anchor(&v(j), "name", &points, Point::xy(0.0, 0.0), &units, false)
+    };
+
+    let p = parse(serde_json::json!({
+        "aggregate": { "parts": ["o", "five_o", "ten"], "method": "farthest" }
+    }))
+    .unwrap();
+    assert_point(&p, 5.0, 5.0, -45.0);
+
+    let p = parse(serde_json::json!({
+        "aggregate": { "parts": ["o", "ten"], "method": "weighted", "weights": [3, 1] }
+    }))
+    .unwrap();
+    assert_point(&p, 2.5, 2.5, -22.5);
+
+    let err = parse(serde_json::json!({
+        "aggregate": { "parts": ["o", "ten"], "method": "weighted", "weights": [1] }
+    }))
+    .unwrap_err();
+    assert!(err.to_string().contains("Expected 2 weights"), "{err}");
+
+    let p = parse(serde_json::json!({
+        "aggregate": { "parts": ["o_five", "five_o", "ten"], "method": "bbox" }
+    }))
+    .unwrap();
+    assert_point(&p, 5.0, 5.0, 0.0);
+
+    let p = parse(serde_json::json!({
+        "aggregate": { "parts": ["o_five", "five_o", "ten"], "method": "bbox", "corner": "top_left" }
+    }))
+    .unwrap();
+    assert_point(&p, 0.0, 10.0, 0.0);
+}
+
+#[test]
+fn anchor_aggregate_centroid_needs_outlines() {
+    let units = units();
+    let points = sample_points();
+    let raw = v(serde_json::json!({
+        "aggregate": { "method": "centroid", "outline": "board" },
+        "shift": [1, 1]
+    }));
+
+    let err = parse_anchor(&raw, "name", &points, Point::xy(0.0, 0.0), &units, false).unwrap_err();
+    assert!(err.to_string().contains("only available"), "{err}");
+
+    let outlines = |name: &str| match name {
+        "board" => Ok([3.0, 4.0]),
+        other => Err(format!("unknown outline {other}")),
+    };
+    let p = parse_anchor_with_outlines(
+        &raw,
+        "name",
+        &points,
+        Point::xy(0.0, 0.0),
+        &units,
+        false,
+        &outlines,
+    )
+    .unwrap();
+    assert_point(&p, 4.0, 5.0, 0.0);
+}
+
+#[test]
+fn anchor_nearest() {
+    let units = units();
+    let points = sample_points();
+    let parse = |j: serde_json::Value| {
+        parse_anchor(&v(j), "name", &points, Point::xy(0.0, 0.0), &units, false)
+    };
+
+    // A named point never picks itself.
+    let p = parse(serde_json::json!({ "nearest": "ten" })).unwrap();
+    assert_point(&p, 5.0, 5.0, 90.0);
+
+    let p = parse(serde_json::json!({ "nearest": { "ref": "o", "shift": [4, 1] } })).unwrap();
+    assert_point(&p, 5.0, 0.0, 0.0);
+
+    let err = parse(serde_json::json!({ "ref": "o", "nearest": "ten" })).unwrap_err();
+    assert!(err.to_string().contains("cannot appear together"), "{err}");
+}
+
 #[test]
 fn anchor_orient_rotate_and_affect() {
     let units = units();
@@ -0,0 +1,69 @@
+use ergogen_layout::{BindSource, parse_points, parse_points_with_bind_trace};
+use ergogen_parser::PreparedConfig;
+
+const LAYOUT: &str = r#"
+points:
+  zones:
+    matrix:
+      columns:
+        pinky:
+        ring:
+          key.bind: [3, -1, -1, -1]
+      rows:
+        bottom:
+        top:
+"#;
+
+fn auto(neighbour: &str) -> BindSource {
+    BindSource::Auto {
+        neighbour: neighbour.to_string(),
+    }
+}
+
+#[test]
+fn bind_trace_names_the_neighbour_behind_each_autobind() {
+    let prepared = PreparedConfig::from_yaml_str(LAYOUT).unwrap();
+    let (points, trace) =
+        parse_points_with_bind_trace(&prepared.canonical, &prepared.units).unwrap();
+
+    let pinky_bottom = &trace["matrix_pinky_bottom"];
+    assert_eq!(pinky_bottom[0].source, auto("matrix_pinky_top"));
+    assert_eq!(pinky_bottom[0].amount, 10.0);
+    assert_eq!(pinky_bottom[1].source, auto("matrix_ring_bottom"));
+    assert_eq!(pinky_bottom[2].source, BindSource::None);
+    assert_eq!(pinky_bottom[2].amount, 0.0);
+    assert_eq!(pinky_bottom[3].source, BindSource::None);
+
+    let ring_top = &trace["matrix_ring_top"];
+    assert_eq!(ring_top[0].source, BindSource::Explicit);
+    assert_eq!(ring_top[0].amount, 3.0);
+    assert_eq!(ring_top[2].source, auto("matrix_ring_bottom"));
+    assert_eq!(ring_top[3].source, auto("matrix_pinky_top"));
+
+    // The trace matches the binds that end up on the points.
+    for (name, sides) in &trace {
+        let bind = points[name].meta.bind;
+        for (side, amount) in sides.iter().zip(bind) {
+            assert_eq!(side.amount, amount, "{name}");
+        }
+    }
+    let plain = parse_points(&prepared.canonical, &prepared.units).unwrap();
+    for (name, p) in &plain {
+        assert_eq!(p.meta.bind, points[name].meta.bind, "{name}");
+    }
+}
+
+#[test]
+fn bind_trace_keeps_unset_sides_when_autobind_is_off() {
+    let yaml = LAYOUT.replace("key.bind: [3, -1, -1, -1]", "key.autobind: 0");
+    let prepared = PreparedConfig::from_yaml_str(&yaml).unwrap();
+    let (points, trace) =
+        parse_points_with_bind_trace(&prepared.canonical, &prepared.units).unwrap();
+
+    let ring_top = &trace["matrix_ring_top"];
+    assert_eq!(ring_top[0].source, BindSource::None);
+    assert_eq!(ring_top[0].amount, -1.0);
+    assert_eq!(points["matrix_ring_top"].meta.bind, [-1.0; 4]);
+    // The other column still autobinds, towards the ring column too.
+    assert_eq!(trace["matrix_pinky_top"][1].source, auto("matrix_ring_top"));
+}
@@ -0,0 +1,93 @@
+use ergogen_layout::diff::{DiffOptions, diff_points};
+use ergogen_layout::{PointsOutput, parse_points};
+use ergogen_parser::{PreparedConfig, Value};
+
+fn points(yaml: &str) -> PointsOutput {
+    let prepared = PreparedConfig::from_yaml_str(yaml).unwrap();
+    parse_points(&prepared.canonical, &prepared.units).unwrap()
+}
+
+const BEFORE: &str = r#"
+points:
+  zones:
+    matrix:
+      columns:
+        pinky:
+        ring:
+        middle:
+      rows:
+        bottom:
+        top:
+"#;
+
+const AFTER: &str = r#"
+points:
+  zones:
+    matrix:
+      columns:
+        pinky:
+        ring:
+          key.stagger: 5
+        index:
+          key.width: 20
+      rows:
+        bottom:
+        top:
+          tags: [top]
+"#;
+
+#[test]
+fn diff_lists_added_removed_moved_and_changed_keys() {
+    let diff = diff_points(&points(BEFORE), &points(AFTER), DiffOptions::default());
+
+    assert_eq!(diff.added, ["matrix_index_bottom", "matrix_index_top"]);
+    assert_eq!(diff.removed, ["matrix_middle_bottom", "matrix_middle_top"]);
+
+    let moved: Vec<&str> = diff.moved.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(moved, ["matrix_ring_bottom", "matrix_ring_top"]);
+    assert!((diff.moved[0].dy - 5.0).abs() < 1e-9);
+    assert!(diff.moved[0].dx.abs() < 1e-9);
+    assert_eq!(diff.moved[0].dr, 0.0);
+
+    let stagger = diff
+        .changed
+        .iter()
+        .find(|c| c.name == "matrix_ring_bottom" && c.field == "stagger")
+        .unwrap();
+    assert_eq!(stagger.before, Value::Number(0.0));
+    assert_eq!(stagger.after, Value::Number(5.0));
+    let tags = diff
+        .changed
+        .iter()
+        .find(|c| c.name == "matrix_pinky_top" && c.field == "tags")
+        .unwrap();
+    assert_eq!(
+        tags.after,
+        Value::Seq(vec![Value::String("top".to_string())])
+    );
+}
+
+#[test]
+fn moves_within_threshold_are_ignored() {
+    let nudged = BEFORE.replace(
+        "        ring:\n",
+        "        ring:\n          key.stagger: 0.5\n",
+    );
+    let loose = DiffOptions {
+        translation: 1.0,
+        rotation: 1.0,
+    };
+    assert!(
+        diff_points(&points(BEFORE), &points(&nudged), loose)
+            .moved
+            .is_empty()
+    );
+    assert_eq!(
+        diff_points(&points(BEFORE), &points(&nudged), DiffOptions::default())
+            .moved
+            .len(),
+        4,
+        "stagger carries over to the following columns"
+    );
+    assert!(diff_points(&points(BEFORE), &points(BEFORE), DiffOptions::default()).is_empty());
+}
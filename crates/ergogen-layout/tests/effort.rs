@@ -0,0 +1,93 @@
+use ergogen_layout::effort::{parse_keymap, score};
+use ergogen_layout::{LayoutError, parse_points};
+use ergogen_parser::{PreparedConfig, Value};
+
+const LAYOUT: &str = r#"
+points:
+  zones:
+    matrix:
+      columns:
+        pinky:
+        ring:
+      rows:
+        bottom:
+        home:
+        top:
+  mirror:
+    ref: matrix_ring_home
+    distance: 40
+"#;
+
+const KEYMAP: &str = r#"
+keys:
+  a: matrix_pinky_home
+  q: matrix_pinky_top
+  z: matrix_pinky_bottom
+  s: matrix_ring_home
+  j: mirror_matrix_pinky_home
+fingers:
+  pinky: pinky
+  ring: ring
+home: [matrix_pinky_home, matrix_ring_home, mirror_matrix_pinky_home]
+"#;
+
+fn run(corpus: &str) -> ergogen_layout::effort::EffortReport {
+    let prepared = PreparedConfig::from_yaml_str(LAYOUT).unwrap();
+    let points = parse_points(&prepared.canonical, &prepared.units).unwrap();
+    let keymap = parse_keymap(&Value::from_yaml_str(KEYMAP).unwrap(), &prepared.units).unwrap();
+    score(&points, &keymap, corpus).unwrap()
+}
+
+fn assert_close(got: f64, expected: f64) {
+    assert!(
+        (got - expected).abs() <= 1e-9,
+        "got={got} expected={expected}"
+    );
+}
+
+#[test]
+fn effort_counts_travel_bigrams_and_row_jumps() {
+    let r = run("Qz as");
+
+    assert_eq!(r.presses, 4);
+    assert_eq!(r.unmapped, 1);
+    // q: home -> top (19), z: top -> bottom (38), a: bottom -> home (19), s: already home.
+    assert_close(r.travel, 76.0);
+    assert_close(r.fingers["left_pinky"].travel, 76.0);
+    assert_eq!(r.fingers["left_ring"].presses, 1);
+    // The space breaks the chain, leaving "qz" and "as".
+    assert_eq!(r.bigrams, 2);
+    assert_eq!(r.same_finger_bigrams, 1);
+    assert_eq!(r.row_jumps, 1);
+    assert_close(r.hand_balance.left, 1.0);
+    assert_close(r.score, (76.0 + 20.0 + 10.0) / 4.0 + 10.0);
+}
+
+#[test]
+fn effort_splits_hands_by_mirroring() {
+    let r = run("ajaj");
+
+    assert_close(r.travel, 0.0);
+    assert_close(r.hand_balance.left, 0.5);
+    assert_close(r.hand_balance.right, 0.5);
+    assert_eq!(r.fingers["right_pinky"].presses, 2);
+    assert_eq!(r.same_finger_bigrams, 0);
+    assert_close(r.score, 0.0);
+}
+
+#[test]
+fn effort_rejects_unknown_points_and_unassigned_columns() {
+    let prepared = PreparedConfig::from_yaml_str(LAYOUT).unwrap();
+    let points = parse_points(&prepared.canonical, &prepared.units).unwrap();
+
+    let yaml = KEYMAP.replace("matrix_ring_home\n", "matrix_middle_home\n");
+    let keymap = parse_keymap(&Value::from_yaml_str(&yaml).unwrap(), &prepared.units).unwrap();
+    let err = score(&points, &keymap, "s").unwrap_err();
+    assert!(matches!(err, LayoutError::InvalidKeymap { .. }), "{err}");
+    assert!(err.to_string().contains("matrix_middle_home"), "{err}");
+
+    let yaml = KEYMAP.replace("  ring: ring\n", "");
+    let keymap = parse_keymap(&Value::from_yaml_str(&yaml).unwrap(), &prepared.units).unwrap();
+    let err = score(&points, &keymap, "s").unwrap_err();
+    assert!(err.to_string().contains("matrix.ring"), "{err}");
+}
@@ -0,0 +1,96 @@
+use ergogen_layout::ergonomics::analyze_config;
+use ergogen_layout::{LayoutError, parse_points};
+use ergogen_parser::PreparedConfig;
+
+const SINGLE_COLUMN: &str = r#"
+points:
+  zones:
+    matrix:
+      columns:
+        index:
+      rows:
+        bottom:
+        home:
+        top:
+ergonomics:
+  palm:
+    ref: matrix_index_bottom
+    shift: [0, -50]
+  fingers:
+    index:
+      length: 80
+  columns:
+    index: index
+  suggest_stagger: true
+"#;
+
+fn assert_close(got: f64, expected: f64) {
+    assert!(
+        (got - expected).abs() <= 1e-6,
+        "got={got} expected={expected}"
+    );
+}
+
+#[test]
+fn ergonomics_measures_reach_and_comfort_per_key() {
+    let prepared = PreparedConfig::from_yaml_str(SINGLE_COLUMN).unwrap();
+    let points = parse_points(&prepared.canonical, &prepared.units).unwrap();
+    let report = analyze_config(&prepared.canonical, &prepared.units, &points)
+        .unwrap()
+        .expect("ergonomics section present");
+
+    let bottom = &report.keys["matrix_index_bottom"];
+    assert_eq!(bottom.finger, "index");
+    assert_close(bottom.reach, 50.0);
+    assert_close(bottom.extension, 0.625);
+    assert_close(bottom.flex, 0.625f64.acos().to_degrees());
+    assert_close(bottom.deviation, 0.0);
+    assert!(bottom.comfortable);
+
+    let home = &report.keys["matrix_index_home"];
+    assert_close(home.reach, 69.0);
+    assert!(home.comfortable);
+
+    let top = &report.keys["matrix_index_top"];
+    assert_close(top.reach, 88.0);
+    assert_close(top.flex, 0.0);
+    assert!(!top.comfortable);
+    assert!(top.strain > 1.0);
+}
+
+#[test]
+fn ergonomics_suggests_column_stagger() {
+    let prepared = PreparedConfig::from_yaml_str(SINGLE_COLUMN).unwrap();
+    let points = parse_points(&prepared.canonical, &prepared.units).unwrap();
+    let report = analyze_config(&prepared.canonical, &prepared.units, &points)
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(report.stagger.len(), 1);
+    let s = &report.stagger[0];
+    assert_eq!((s.zone.as_str(), s.column.as_str()), ("matrix", "index"));
+    // Average reach is 69; the middle of the default arc is 0.75 * 80 = 60.
+    assert_close(s.delta, -9.0);
+    assert_close(s.suggested, s.current - 9.0);
+}
+
+#[test]
+fn ergonomics_is_optional_and_validates_fingers() {
+    let yaml = SINGLE_COLUMN.split("ergonomics:").next().unwrap();
+    let prepared = PreparedConfig::from_yaml_str(yaml).unwrap();
+    let points = parse_points(&prepared.canonical, &prepared.units).unwrap();
+    assert!(
+        analyze_config(&prepared.canonical, &prepared.units, &points)
+            .unwrap()
+            .is_none()
+    );
+
+    let yaml = SINGLE_COLUMN.replace("index: index", "index: thumb");
+    let prepared = PreparedConfig::from_yaml_str(&yaml).unwrap();
+    let points = parse_points(&prepared.canonical, &prepared.units).unwrap();
+    let err = analyze_config(&prepared.canonical, &prepared.units, &points).unwrap_err();
+    assert!(
+        matches!(err, LayoutError::InvalidErgonomics { .. }),
+        "{err}"
+    );
+}
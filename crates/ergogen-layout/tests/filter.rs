@@ -0,0 +1,176 @@
+use indexmap::IndexMap;
+
+use ergogen_core::{Point, PointMeta};
+use ergogen_layout::filter::select_points;
+use ergogen_layout::{Asymmetry, LayoutError, PointsOutput, parse_points};
+use ergogen_parser::{PreparedConfig, Units, Value};
+
+const LAYOUT: &str = r#"
+points:
+  zones:
+    matrix:
+      columns:
+        pinky:
+        ring:
+          key.tags: [ring]
+        middle:
+      rows:
+        bottom:
+        top:
+    thumb:
+      anchor:
+        shift: [0, -30]
+      key.tags: [thumb]
+      columns:
+        near:
+        far:
+  mirror:
+    ref: matrix_middle_top
+    distance: 30
+"#;
+
+fn setup() -> (PointsOutput, IndexMap<String, Point>, Units) {
+    let prepared = PreparedConfig::from_yaml_str(LAYOUT).unwrap();
+    let points = parse_points(&prepared.canonical, &prepared.units).unwrap();
+    let refs = points
+        .iter()
+        .map(|(k, p)| {
+            let meta = PointMeta {
+                mirrored: p.meta.mirrored.unwrap_or(false),
+            };
+            (k.clone(), Point::new(p.x, p.y, p.r, meta))
+        })
+        .collect();
+    (points, refs, prepared.units)
+}
+
+fn select(where_v: Value, asym: Asymmetry) -> Result<Vec<String>, LayoutError> {
+    let (points, refs, units) = setup();
+    Ok(
+        select_points(&where_v, asym, &points, &refs, &units, "test.where")?
+            .into_iter()
+            .map(|s| s.name)
+            .collect(),
+    )
+}
+
+fn query(q: &str) -> Vec<String> {
+    select(Value::String(q.to_string()), Asymmetry::Source).unwrap()
+}
+
+#[test]
+fn filter_keeps_upstream_forms() {
+    assert_eq!(
+        select(Value::Bool(true), Asymmetry::Source).unwrap().len(),
+        8
+    );
+    assert_eq!(
+        select(Value::Bool(true), Asymmetry::Both).unwrap().len(),
+        16
+    );
+    assert!(
+        select(Value::Bool(false), Asymmetry::Both)
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(select(Value::Null, Asymmetry::Both).unwrap(), [""]);
+
+    let regex = Value::String("/^thumb_/".to_string());
+    assert_eq!(
+        select(regex, Asymmetry::Source).unwrap(),
+        ["thumb_near", "thumb_far"]
+    );
+    let tags = Value::Seq(vec![Value::String("ring".to_string())]);
+    assert_eq!(
+        select(tags, Asymmetry::Clone).unwrap(),
+        ["mirror_matrix_ring_bottom", "mirror_matrix_ring_top"]
+    );
+
+    // Plain names are still anchors, resolved on both sides.
+    let anchor = Value::String("matrix_pinky_top".to_string());
+    let (points, refs, units) = setup();
+    let picked = select_points(
+        &anchor,
+        Asymmetry::Both,
+        &points,
+        &refs,
+        &units,
+        "test.where",
+    )
+    .unwrap();
+    assert_eq!(picked.len(), 2);
+    assert_eq!(picked[0].name, "");
+    assert_eq!((picked[0].x, picked[0].y), (0.0, 19.0));
+    assert!(picked[1].mirrored);
+}
+
+#[test]
+fn filter_queries_select_on_metadata() {
+    assert_eq!(
+        query("zone:matrix and row:top"),
+        ["matrix_pinky_top", "matrix_ring_top", "matrix_middle_top"]
+    );
+    assert_eq!(
+        query("col:/^(pinky|far)$/"),
+        ["matrix_pinky_bottom", "matrix_pinky_top", "thumb_far"]
+    );
+    assert_eq!(
+        query("tag:thumb or name:matrix_ring_top"),
+        ["matrix_ring_top", "thumb_near", "thumb_far"]
+    );
+    assert_eq!(
+        query("x > 20 and y >= 0 and not ring"),
+        ["matrix_middle_bottom", "matrix_middle_top"]
+    );
+    assert_eq!(
+        query("meta.row == bottom and x < 1u"),
+        ["matrix_pinky_bottom"]
+    );
+    assert_eq!(
+        query("zone == thumb and tag != ring and meta.width >= 18"),
+        ["thumb_near", "thumb_far"]
+    );
+    assert_eq!(
+        query("(ring or thumb) and not (row:top or col:far)"),
+        ["matrix_ring_bottom", "thumb_near"]
+    );
+}
+
+#[test]
+fn filter_queries_order_and_limit() {
+    assert_eq!(
+        query("row:bottom order by x desc limit 2"),
+        ["matrix_middle_bottom", "matrix_ring_bottom"]
+    );
+    assert_eq!(
+        query("true order by y, x desc limit 3"),
+        ["thumb_far", "thumb_near", "matrix_middle_bottom"]
+    );
+    assert_eq!(
+        select(
+            Value::String("zone:matrix and row:top order by x desc limit 1".to_string()),
+            Asymmetry::Clone
+        )
+        .unwrap(),
+        ["mirror_matrix_pinky_top"]
+    );
+}
+
+#[test]
+fn filter_reports_malformed_queries() {
+    for bad in [
+        "zone:matrix and",
+        "x > ",
+        "(row:top",
+        "foo.bar == 1",
+        "row:top order x",
+        "row:top limit many",
+        "name:/[/",
+    ] {
+        let err = select(Value::String(bad.to_string()), Asymmetry::Both).unwrap_err();
+        assert!(
+            matches!(err, LayoutError::InvalidFilter { .. }),
+            "{bad}: {err}"
+        );
+    }
+}
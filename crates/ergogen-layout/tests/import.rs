@@ -0,0 +1,175 @@
+use indexmap::IndexMap;
+
+use ergogen_layout::{LayoutError, PointsOutput, parse_points};
+use ergogen_parser::PreparedConfig;
+
+/// Every test installs the same map, so parallel tests never observe each other's files.
+fn install_files() {
+    let files = IndexMap::from([
+        (
+            "imports/header.csv".to_string(),
+            "name,x,y,r,tags,column,row\n\
+             a_bottom,0,0,0,home;alpha,a,bottom\n\
+             a_top,0,19,0,,a,top\n\
+             b_bottom,19,0,-5,alpha,b,bottom\n"
+                .to_string(),
+        ),
+        (
+            "imports/plain.csv".to_string(),
+            "# name, x, y, r, width, height\n\
+             k1, 0, 0, 0\n\
+             k2, 0.5, 19, 0\n\
+             k3, 19, 0, 0, 1.5u, 18\n"
+                .to_string(),
+        ),
+        (
+            "imports/keys.json".to_string(),
+            r#"[
+                {"name": "thumb", "x": 30, "y": -20, "r": 15, "tags": ["thumb"]},
+                ["extra", 50, -20, 0]
+            ]"#
+            .to_string(),
+        ),
+        (
+            "imports/units.csv".to_string(),
+            "k1,1u,0,0\nk2,2u,0,0\n".to_string(),
+        ),
+        ("imports/keys.txt".to_string(), "k1,0,0,0\n".to_string()),
+    ]);
+    ergogen_core::vfs::set(files);
+}
+
+fn points(yaml: &str) -> Result<PointsOutput, LayoutError> {
+    install_files();
+    let prepared = PreparedConfig::from_yaml_str(yaml).unwrap();
+    parse_points(&prepared.canonical, &prepared.units)
+}
+
+#[test]
+fn import_csv_with_header_keeps_columns_tags_and_anchors() {
+    let points = points(
+        r#"
+points:
+  import: imports/header.csv
+  zones:
+    thumbs:
+      anchor:
+        ref: a_bottom
+        shift: [0, -25]
+"#,
+    )
+    .unwrap();
+
+    let names: Vec<&str> = points.keys().map(String::as_str).collect();
+    assert_eq!(names, ["a_bottom", "a_top", "b_bottom", "thumbs"]);
+
+    let a = &points["a_bottom"];
+    assert_eq!(a.meta.zone.name, "import");
+    assert_eq!(a.meta.col.name, "a");
+    assert_eq!(a.meta.row, "bottom");
+    assert_eq!(a.meta.tags, ["home", "alpha"]);
+    assert_eq!(a.meta.zone.columns_order, ["a", "b"]);
+    assert_eq!(points["b_bottom"].r, -5.0);
+
+    // Autobind treats the imported columns like generated ones.
+    assert_eq!(a.meta.bind, [10.0, 10.0, 0.0, 0.0]);
+    assert_eq!(points["a_top"].meta.bind, [0.0, 0.0, 10.0, 0.0]);
+
+    assert_eq!((points["thumbs"].x, points["thumbs"].y), (0.0, -25.0));
+}
+
+#[test]
+fn import_positional_csv_infers_columns_and_sizes() {
+    let points = points("points.import: imports/plain.csv").unwrap();
+
+    assert_eq!(points["k1"].meta.col.name, "c1");
+    assert_eq!(points["k2"].meta.col.name, "c1");
+    assert_eq!(points["k3"].meta.col.name, "c2");
+    assert_eq!(points["k3"].meta.width, 1.5 * 19.0);
+    assert_eq!(points["k1"].meta.width, 18.0);
+    assert_eq!(points["k1"].meta.bind[1], 10.0);
+}
+
+#[test]
+fn import_csv_without_header_may_start_with_expressions() {
+    let points = points("points.import: imports/units.csv").unwrap();
+
+    assert_eq!(points.len(), 2);
+    assert_eq!(points["k1"].x, 19.0);
+    assert_eq!(points["k2"].x, 38.0);
+}
+
+#[test]
+fn imported_keys_start_from_the_global_and_source_keys() {
+    let points = points(
+        r#"
+points:
+  key:
+    tags: [pin]
+    autobind: 5
+  import:
+    - imports/plain.csv
+    - file: imports/header.csv
+      key.height: 10
+"#,
+    )
+    .unwrap();
+
+    let k1 = &points["k1"];
+    assert_eq!(k1.meta.tags, ["pin"]);
+    assert_eq!(k1.meta.autobind, 5.0);
+    assert_eq!(k1.meta.bind[1], 5.0);
+    assert_eq!(k1.meta.height, 18.0);
+    // Tags in the file win; the source's key only reaches its own rows.
+    assert_eq!(points["a_bottom"].meta.tags, ["home", "alpha"]);
+    assert_eq!(points["a_top"].meta.tags, ["pin"]);
+    assert_eq!(points["a_top"].meta.height, 10.0);
+}
+
+#[test]
+fn import_json_and_mirror() {
+    let points = points(
+        r#"
+points:
+  import:
+    - file: imports/keys.json
+      zone: thumbs
+  mirror:
+    ref: thumb
+    distance: 20
+"#,
+    )
+    .unwrap();
+
+    let thumb = &points["thumb"];
+    assert_eq!(thumb.meta.zone.name, "thumbs");
+    assert_eq!(thumb.meta.tags, ["thumb"]);
+    let mirrored = &points["mirror_thumb"];
+    assert_eq!(mirrored.meta.mirrored, Some(true));
+    assert_eq!(mirrored.r, -15.0);
+    assert!(points.contains_key("mirror_extra"));
+}
+
+#[test]
+fn import_rejects_unknown_formats_missing_files_and_duplicates() {
+    let err = points("points.import: imports/keys.txt").unwrap_err();
+    assert!(matches!(err, LayoutError::InvalidImport { .. }), "{err}");
+
+    let err = points("points.import: imports/missing.csv").unwrap_err();
+    assert!(err.to_string().contains("missing.csv"), "{err}");
+
+    let err = points("points.import: [imports/plain.csv, imports/plain.csv]").unwrap_err();
+    assert!(matches!(err, LayoutError::DuplicateKey { .. }), "{err}");
+
+    let err = points(
+        r#"
+points:
+  import: imports/plain.csv
+  zones:
+    k1:
+      key.name: k1
+"#,
+    )
+    .unwrap_err();
+    assert!(matches!(err, LayoutError::DuplicateKey { .. }), "{err}");
+}
@@ -0,0 +1,58 @@
+use ergogen_layout::parse_points;
+use ergogen_parser::PreparedConfig;
+
+const LAYOUT: &str = r#"
+points:
+  key:
+    tent: 15
+  zones:
+    matrix:
+      key.z: 4
+      columns:
+        pinky:
+          key.tilt: -10
+        ring:
+      rows:
+        bottom:
+        top:
+          z: 6
+  mirror:
+    ref: matrix_ring_top
+    distance: 30
+"#;
+
+#[test]
+fn pose_fields_inherit_like_other_key_attributes() {
+    let prepared = PreparedConfig::from_yaml_str(LAYOUT).unwrap();
+    let points = parse_points(&prepared.canonical, &prepared.units).unwrap();
+
+    let pinky_bottom = &points["matrix_pinky_bottom"].meta;
+    assert_eq!(
+        (pinky_bottom.z, pinky_bottom.tilt, pinky_bottom.tent),
+        (4.0, -10.0, 15.0)
+    );
+    let ring_top = &points["matrix_ring_top"].meta;
+    assert_eq!((ring_top.z, ring_top.tilt, ring_top.tent), (6.0, 0.0, 15.0));
+
+    let mirrored = &points["mirror_matrix_ring_top"].meta;
+    assert_eq!(
+        (mirrored.z, mirrored.tilt, mirrored.tent),
+        (6.0, 0.0, -15.0)
+    );
+}
+
+#[test]
+fn flat_keys_serialize_without_pose_fields() {
+    let flat = "points.zones.matrix.columns.pinky.rows.home:\n";
+    let prepared = PreparedConfig::from_yaml_str(flat).unwrap();
+    let points = parse_points(&prepared.canonical, &prepared.units).unwrap();
+    let json = serde_json::to_value(&points["matrix_pinky_home"]).unwrap();
+    for field in ["z", "tilt", "tent"] {
+        assert!(json["meta"].get(field).is_none(), "{field}");
+    }
+
+    let prepared = PreparedConfig::from_yaml_str(LAYOUT).unwrap();
+    let points = parse_points(&prepared.canonical, &prepared.units).unwrap();
+    let json = serde_json::to_value(&points["matrix_pinky_bottom"]).unwrap();
+    assert_eq!(json["meta"]["tilt"], -10.0);
+}
@@ -0,0 +1,152 @@
+use ergogen_layout::matrix::{MatrixAssignment, MatrixOptions, solve_matrix};
+use ergogen_layout::{LayoutError, parse_points};
+use ergogen_parser::PreparedConfig;
+
+const SPLIT: &str = r#"
+points:
+  zones:
+    matrix:
+      columns:
+        pinky:
+        ring:
+          key.stagger: 5
+        middle:
+          key.stagger: 3
+      rows:
+        bottom:
+        home:
+        top:
+    thumb:
+      anchor:
+        ref: matrix_middle_bottom
+        shift: [10, -25]
+      columns:
+        near:
+          key.splay: -10
+        mid:
+          key.splay: -10
+        far:
+          key.splay: -10
+  mirror:
+    ref: matrix_middle_home
+    distance: 60
+"#;
+
+fn nets(yaml: &str, name: &str) -> (String, String) {
+    let prepared = PreparedConfig::from_yaml_str(yaml).unwrap();
+    let points = parse_points(&prepared.canonical, &prepared.units).unwrap();
+    let meta = &points[name].meta;
+    (
+        meta.row_net.clone().unwrap(),
+        meta.column_net.clone().unwrap(),
+    )
+}
+
+#[test]
+fn columns_follow_key_chains_and_thumbs_fold_into_one_column() {
+    let prepared = PreparedConfig::from_yaml_str(SPLIT).unwrap();
+    let points = parse_points(&prepared.canonical, &prepared.units).unwrap();
+    let assignment = solve_matrix(&points, &MatrixOptions::default());
+
+    // 3 rows, 3 finger columns and the thumb cluster sharing a fourth column.
+    assert_eq!((assignment.rows, assignment.columns), (3, 4));
+    assert_eq!(assignment.pins(), 7);
+
+    for (col, net) in [("pinky", "col1"), ("ring", "col2"), ("middle", "col3")] {
+        for (row, row_net) in [("bottom", "row1"), ("home", "row2"), ("top", "row3")] {
+            let key = &assignment.keys[&format!("matrix_{col}_{row}")];
+            assert_eq!(
+                (key.row_net.as_str(), key.column_net.as_str()),
+                (row_net, net)
+            );
+        }
+    }
+    let mut thumb_rows: Vec<&str> = ["near", "mid", "far"]
+        .iter()
+        .map(|c| assignment.keys[&format!("thumb_{c}")].row_net.as_str())
+        .collect();
+    thumb_rows.sort();
+    assert_eq!(thumb_rows, ["row1", "row2", "row3"]);
+    assert!(
+        ["near", "mid", "far"]
+            .iter()
+            .all(|c| assignment.keys[&format!("thumb_{c}")].column_net == "col4")
+    );
+
+    // The mirrored half reuses the same nets, numbered from its own pinky column.
+    assert_eq!(
+        assignment.keys["mirror_matrix_pinky_top"].column_net,
+        "col1"
+    );
+    assert_eq!(assignment.keys["mirror_matrix_pinky_top"].row_net, "row3");
+}
+
+#[test]
+fn whole_board_matrix_when_not_split() {
+    let prepared = PreparedConfig::from_yaml_str(SPLIT).unwrap();
+    let points = parse_points(&prepared.canonical, &prepared.units).unwrap();
+    let options = MatrixOptions {
+        split: false,
+        ..MatrixOptions::default()
+    };
+    let assignment = solve_matrix(&points, &options);
+    // Stacking two finger columns (and the thumb chains) per column net needs 10 pins, where
+    // 3 rows would need 3 + 8.
+    assert_eq!((assignment.rows, assignment.columns), (6, 4));
+    let mut pairs: Vec<(usize, usize)> = assignment
+        .keys
+        .values()
+        .map(|k| (k.row, k.column))
+        .collect();
+    pairs.sort();
+    pairs.dedup();
+    assert_eq!(
+        pairs.len(),
+        points.len(),
+        "every key needs its own crossing"
+    );
+}
+
+/// A single row of `n` keys, none above another.
+fn flat_row(n: usize) -> MatrixAssignment {
+    let columns: String = (1..=n).map(|c| format!("        c{c}:\n")).collect();
+    let yaml = format!("points.zones.row:\n  columns:\n{columns}");
+    let prepared = PreparedConfig::from_yaml_str(&yaml).unwrap();
+    let points = parse_points(&prepared.canonical, &prepared.units).unwrap();
+    solve_matrix(&points, &MatrixOptions::default())
+}
+
+#[test]
+fn flat_rows_fold_into_a_square_matrix() {
+    let twelve = flat_row(12);
+    assert_eq!((twelve.rows, twelve.columns), (3, 4));
+    assert_eq!(twelve.pins(), 7);
+    // Neighbouring keys share a column.
+    assert_eq!(twelve.keys["row_c1"].column, twelve.keys["row_c3"].column);
+    assert_ne!(twelve.keys["row_c3"].column, twelve.keys["row_c4"].column);
+
+    let six = flat_row(6);
+    assert_eq!((six.rows, six.columns), (2, 3));
+
+    assert_eq!((flat_row(1).rows, flat_row(1).columns), (1, 1));
+}
+
+#[test]
+fn points_matrix_sets_net_metadata() {
+    let config = format!("{SPLIT}  matrix:\n    row_prefix: R\n    column_prefix: C\n");
+    assert_eq!(
+        nets(&config, "matrix_ring_home"),
+        ("R2".to_string(), "C2".to_string())
+    );
+
+    let prepared = PreparedConfig::from_yaml_str(SPLIT).unwrap();
+    let points = parse_points(&prepared.canonical, &prepared.units).unwrap();
+    assert!(points.values().all(|p| p.meta.row_net.is_none()));
+
+    let bad = format!("{SPLIT}  matrix: 3\n");
+    let prepared = PreparedConfig::from_yaml_str(&bad).unwrap();
+    assert!(matches!(
+        parse_points(&prepared.canonical, &prepared.units),
+        Err(LayoutError::InvalidMatrix { .. })
+    ));
+}
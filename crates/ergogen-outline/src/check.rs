@@ -0,0 +1,307 @@
+//! Manufacturability checks for generated outlines.
+//!
+//! Laser and CNC shops reject parts they cannot cut cleanly. [`check_region`] looks for:
+//!
+//! - open contours and self-intersecting boundaries (always),
+//! - material thinner than `min_feature`, measured straight across from the boundary,
+//! - inside corners tighter than `min_inside_radius` (sharp inside corners have radius 0),
+//! - islands and holes smaller than `min_area`.
+//!
+//! A limit of 0 turns its check off. Every violation carries a location, so it can be found in
+//! the drawing.
+
+use std::fmt;
+
+use cavalier_contours::core::math::Vector2;
+use cavalier_contours::polyline::internal::pline_intersects::all_self_intersects_as_basic;
+use cavalier_contours::polyline::{
+    PlineSegIntr, PlineSource, PlineVertex, pline_seg_intr, seg_arc_radius_and_center, seg_length,
+    seg_tangent_vector,
+};
+use ergogen_geometry::Polyline;
+use ergogen_geometry::region::Region;
+
+use crate::sample::point_on_seg;
+
+/// Turns smaller than this (in degrees) are not corners.
+const CORNER_TOLERANCE: f64 = 1.0;
+/// Thin features are probed this many times per `min_feature` of boundary.
+const SAMPLES_PER_FEATURE: f64 = 4.0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CheckOptions {
+    /// Thinnest material the tool can leave, in mm.
+    pub min_feature: f64,
+    /// Tightest inside corner the tool can cut (the bit radius), in mm.
+    pub min_inside_radius: f64,
+    /// Smallest island or hole worth cutting, in mm².
+    pub min_area: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ViolationKind {
+    OpenContour,
+    SelfIntersection,
+    SmallIsland {
+        area: f64,
+    },
+    SmallHole {
+        area: f64,
+    },
+    TightCorner {
+        radius: f64,
+    },
+    /// `width` is the thinnest measurement across the feature.
+    ThinFeature {
+        width: f64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Violation {
+    pub kind: ViolationKind,
+    /// Where to look: the start of an open contour, the crossing, the middle of an island or hole,
+    /// the corner, or the middle of a thin feature.
+    pub at: [f64; 2],
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ViolationKind::OpenContour => write!(f, "open contour")?,
+            ViolationKind::SelfIntersection => write!(f, "self-intersection")?,
+            ViolationKind::SmallIsland { area } => write!(f, "island of {area:.3} mm²")?,
+            ViolationKind::SmallHole { area } => write!(f, "hole of {area:.3} mm²")?,
+            ViolationKind::TightCorner { radius: 0.0 } => write!(f, "sharp inside corner")?,
+            ViolationKind::TightCorner { radius } => {
+                write!(f, "inside corner with radius {radius:.3}")?
+            }
+            ViolationKind::ThinFeature { width } => write!(f, "feature {width:.3} wide")?,
+        }
+        write!(f, " at ({:.3}, {:.3})", self.at[0], self.at[1])
+    }
+}
+
+/// Every violation of `options` in `region`, grouped by check.
+pub fn check_region(region: &Region, options: &CheckOptions) -> Vec<Violation> {
+    let mut out = Vec::new();
+    let boundaries: Vec<(&Polyline<f64>, bool)> = region
+        .pos
+        .iter()
+        .map(|pl| (pl, true))
+        .chain(region.neg.iter().map(|pl| (pl, false)))
+        .filter(|(pl, _)| pl.vertex_count() >= 2)
+        .collect();
+
+    for (pl, _) in &boundaries {
+        if !pl.is_closed() {
+            out.push(violation(ViolationKind::OpenContour, pl.at(0).pos()));
+        }
+    }
+    let closed: Vec<(&Polyline<f64>, bool)> = boundaries
+        .into_iter()
+        .filter(|(pl, _)| pl.is_closed())
+        .collect();
+
+    for (pl, _) in &closed {
+        let mut seen: Vec<Vector2<f64>> = Vec::new();
+        let index = pl.create_approx_aabb_index();
+        for intr in all_self_intersects_as_basic(*pl, &index, true, 1e-5) {
+            if seen.iter().all(|p| (*p - intr.point).length() > 1e-6) {
+                seen.push(intr.point);
+                out.push(violation(ViolationKind::SelfIntersection, intr.point));
+            }
+        }
+    }
+
+    if options.min_area > 0.0 {
+        for (pl, is_pos) in &closed {
+            let area = pl.area().abs();
+            if area < options.min_area {
+                let kind = if *is_pos {
+                    ViolationKind::SmallIsland { area }
+                } else {
+                    ViolationKind::SmallHole { area }
+                };
+                let e = pl.extents().expect("closed polyline has extents");
+                let center = Vector2::new((e.min_x + e.max_x) / 2.0, (e.min_y + e.max_y) / 2.0);
+                out.push(violation(kind, center));
+            }
+        }
+    }
+
+    if options.min_inside_radius > 0.0 {
+        for (pl, is_pos) in &closed {
+            tight_corners(pl, *is_pos, options.min_inside_radius, &mut out);
+        }
+    }
+
+    if options.min_feature > 0.0 {
+        for (pl, is_pos) in &closed {
+            thin_features(pl, *is_pos, &closed, options.min_feature, &mut out);
+        }
+    }
+    out
+}
+
+fn violation(kind: ViolationKind, at: Vector2<f64>) -> Violation {
+    Violation {
+        kind,
+        at: [at.x, at.y],
+    }
+}
+
+/// `1.0` when the material is on the left of `pl` as it is walked, `-1.0` otherwise.
+fn material_side(pl: &Polyline<f64>, is_pos: bool) -> f64 {
+    if (pl.area() > 0.0) == is_pos {
+        1.0
+    } else {
+        -1.0
+    }
+}
+
+fn segment(pl: &Polyline<f64>, i: usize) -> (PlineVertex<f64>, PlineVertex<f64>) {
+    (pl.at(i), pl.at((i + 1) % pl.vertex_count()))
+}
+
+/// Concave vertices, and concave arcs tighter than `min_radius`.
+fn tight_corners(pl: &Polyline<f64>, is_pos: bool, min_radius: f64, out: &mut Vec<Violation>) {
+    let side = material_side(pl, is_pos);
+    let n = pl.vertex_count();
+    for i in 0..n {
+        let (p1, p2) = segment(pl, (i + n - 1) % n);
+        let (v1, v2) = segment(pl, i);
+        let incoming = seg_tangent_vector(p1, p2, p2.pos()).normalize();
+        let outgoing = seg_tangent_vector(v1, v2, v1.pos()).normalize();
+        let turn = incoming.perp_dot(outgoing).atan2(incoming.dot(outgoing)) * side;
+        if turn.to_degrees() < -CORNER_TOLERANCE {
+            out.push(violation(
+                ViolationKind::TightCorner { radius: 0.0 },
+                v1.pos(),
+            ));
+        }
+
+        if !v1.bulge_is_zero() && v1.bulge * side < 0.0 {
+            let (radius, _) = seg_arc_radius_and_center(v1, v2);
+            if radius < min_radius - 1e-9 {
+                let mid = point_on_seg(v1, v2, seg_length(v1, v2) / 2.0);
+                out.push(violation(ViolationKind::TightCorner { radius }, mid));
+            }
+        }
+    }
+}
+
+/// Probes the material along `pl` by measuring from the boundary straight inwards to the next
+/// boundary. Consecutive thin probes are one feature, reported in the middle of the run with its
+/// thinnest width.
+fn thin_features(
+    pl: &Polyline<f64>,
+    is_pos: bool,
+    boundaries: &[(&Polyline<f64>, bool)],
+    min_feature: f64,
+    out: &mut Vec<Violation>,
+) {
+    let side = material_side(pl, is_pos);
+    let inward = |t: Vector2<f64>| Vector2::new(-t.y, t.x).scale(side);
+    let step = min_feature / SAMPLES_PER_FEATURE;
+    let n = pl.vertex_count();
+
+    // (width, middle of the material) for every thin probe, `None` for the others.
+    let mut probes: Vec<Option<(f64, Vector2<f64>)>> = Vec::new();
+    for i in 0..n {
+        let (p1, p2) = segment(pl, (i + n - 1) % n);
+        let (v1, v2) = segment(pl, i);
+        let length = seg_length(v1, v2);
+        let count = (length / step).ceil().max(1.0) as usize;
+        for j in 0..count {
+            let pos = point_on_seg(v1, v2, length * j as f64 / count as f64);
+            let outgoing = seg_tangent_vector(v1, v2, pos).normalize();
+            let mut normal = inward(outgoing);
+            if j == 0 {
+                // Split the difference at vertices.
+                let incoming = seg_tangent_vector(p1, p2, p2.pos()).normalize();
+                let bisector = normal + inward(incoming);
+                if bisector.length() > 1e-9 {
+                    normal = bisector.normalize();
+                }
+            }
+            probes.push(
+                width_across(pos, normal, min_feature, boundaries)
+                    .map(|w| (w, pos + normal.scale(w / 2.0))),
+            );
+        }
+    }
+
+    // Rotate so that no run of thin probes wraps around the end.
+    if let Some(start) = probes.iter().position(Option::is_none) {
+        probes.rotate_left(start);
+    }
+    let mut run: Vec<(f64, Vector2<f64>)> = Vec::new();
+    for probe in probes.into_iter().chain([None]) {
+        if let Some(p) = probe {
+            run.push(p);
+            continue;
+        }
+        if run.is_empty() {
+            continue;
+        }
+        let width = run.iter().map(|p| p.0).fold(f64::INFINITY, f64::min);
+        let at = run[run.len() / 2].1;
+        // The other side of the feature measures it again.
+        let seen = out.iter().any(|v| {
+            matches!(v.kind, ViolationKind::ThinFeature { .. })
+                && (Vector2::new(v.at[0], v.at[1]) - at).length() < min_feature
+        });
+        if !seen {
+            out.push(violation(ViolationKind::ThinFeature { width }, at));
+        }
+        run.clear();
+    }
+}
+
+/// Distance from `pos` along `normal` to the nearest boundary, if it is under `limit`.
+fn width_across(
+    pos: Vector2<f64>,
+    normal: Vector2<f64>,
+    limit: f64,
+    boundaries: &[(&Polyline<f64>, bool)],
+) -> Option<f64> {
+    let end = pos + normal.scale(limit);
+    let (a, b) = (
+        PlineVertex::new(pos.x, pos.y, 0.0),
+        PlineVertex::new(end.x, end.y, 0.0),
+    );
+    let (min_x, max_x) = (pos.x.min(end.x), pos.x.max(end.x));
+    let (min_y, max_y) = (pos.y.min(end.y), pos.y.max(end.y));
+
+    let mut nearest = f64::INFINITY;
+    let mut hit = |p: Vector2<f64>| {
+        let d = (p - pos).length();
+        // Skip the boundary the probe starts on.
+        if d > 1e-6 {
+            nearest = nearest.min(d);
+        }
+    };
+    for (pl, _) in boundaries {
+        let Some(e) = pl.extents() else { continue };
+        if e.max_x < min_x || e.min_x > max_x || e.max_y < min_y || e.min_y > max_y {
+            continue;
+        }
+        for i in 0..pl.vertex_count() {
+            let (u1, u2) = segment(pl, i);
+            match pline_seg_intr(a, b, u1, u2, 1e-9) {
+                PlineSegIntr::NoIntersect => {}
+                PlineSegIntr::TangentIntersect { point } | PlineSegIntr::OneIntersect { point } => {
+                    hit(point)
+                }
+                PlineSegIntr::TwoIntersects { point1, point2 }
+                | PlineSegIntr::OverlappingLines { point1, point2 }
+                | PlineSegIntr::OverlappingArcs { point1, point2 } => {
+                    hit(point1);
+                    hit(point2);
+                }
+            }
+        }
+    }
+    (nearest < limit - 1e-9).then_some(nearest)
+}
@@ -0,0 +1,186 @@
+//! Kerf and tool-radius compensation, applied to outlines only when they are exported.
+//!
+//! ```yaml
+//! export:
+//!   plate:                           # outline name
+//!     kerf: 0.15                     # width of the laser cut
+//!     tool_diameter: 3.175           # router bit
+//!     inside_corner_relief: dogbone  # dogbone | tbone | none (default)
+//! ```
+//!
+//! The cut is `kerf + tool_diameter` wide and the exported contours run along its middle: outer
+//! contours move outwards and holes inwards by half of it, so the finished part has the designed
+//! size. A bit cannot cut sharp inside corners, so reliefs notch them with a circle the size of the
+//! cut: `dogbone` reaches diagonally into the corner, `tbone` straight across the shorter side.
+
+use cavalier_contours::core::math::Vector2;
+use cavalier_contours::polyline::{
+    PlineSource, PlineSourceMut, PlineVertex, seg_length, seg_tangent_vector,
+};
+use ergogen_geometry::primitives;
+use ergogen_geometry::region::Region;
+use ergogen_parser::{PreparedConfig, Value};
+
+use crate::{ExpandJoints, OutlineError, eval_number, expand_region};
+
+/// Turns sharper than this (in degrees) are corners that get a relief.
+const CORNER_TURN: f64 = 1.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CornerRelief {
+    #[default]
+    None,
+    Dogbone,
+    Tbone,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ExportOptions {
+    pub kerf: f64,
+    pub tool_diameter: f64,
+    pub inside_corner_relief: CornerRelief,
+}
+
+impl ExportOptions {
+    /// Half the width of the cut, which is how far contours move.
+    pub fn offset(&self) -> f64 {
+        (self.kerf + self.tool_diameter) / 2.0
+    }
+}
+
+/// The `export.<name>` options of an outline, or the defaults (no compensation).
+pub fn export_options(
+    prepared: &PreparedConfig,
+    name: &str,
+) -> Result<ExportOptions, OutlineError> {
+    let at = format!("export.{name}");
+    let err = |message: &str| OutlineError::InvalidExport {
+        at: at.clone(),
+        message: message.to_string(),
+    };
+    let Some(spec) = prepared
+        .canonical
+        .get_path("export")
+        .and_then(Value::as_map)
+        .and_then(|e| e.get(name))
+    else {
+        return Ok(ExportOptions::default());
+    };
+    let Value::Map(m) = spec else {
+        return Err(err("export options must be an object"));
+    };
+    for key in m.keys() {
+        if !matches!(
+            key.as_str(),
+            "kerf" | "tool_diameter" | "inside_corner_relief"
+        ) {
+            return Err(err(&format!("unknown option \"{key}\"")));
+        }
+    }
+
+    let number = |key: &str| match m.get(key) {
+        None | Some(Value::Null) => Ok(0.0),
+        Some(v) => eval_number(&prepared.units, v, &format!("{at}.{key}")),
+    };
+    let kerf = number("kerf")?;
+    let tool_diameter = number("tool_diameter")?;
+    if kerf < 0.0 || tool_diameter < 0.0 {
+        return Err(err("kerf and tool_diameter must not be negative"));
+    }
+    let inside_corner_relief = match m.get("inside_corner_relief") {
+        None | Some(Value::Null) => CornerRelief::None,
+        Some(Value::String(s)) => match s.as_str() {
+            "none" => CornerRelief::None,
+            "dogbone" => CornerRelief::Dogbone,
+            "tbone" => CornerRelief::Tbone,
+            other => return Err(err(&format!("unknown inside_corner_relief \"{other}\""))),
+        },
+        Some(_) => return Err(err("inside_corner_relief must be dogbone, tbone or none")),
+    };
+    if inside_corner_relief != CornerRelief::None && tool_diameter == 0.0 {
+        return Err(err("inside_corner_relief needs a tool_diameter"));
+    }
+    Ok(ExportOptions {
+        kerf,
+        tool_diameter,
+        inside_corner_relief,
+    })
+}
+
+/// The contours to cut for `region`. The region itself is left as designed.
+pub fn compensate(region: &Region, options: &ExportOptions) -> Result<Region, OutlineError> {
+    let offset = options.offset();
+    if offset == 0.0 {
+        return Ok(region.clone());
+    }
+    let mut relieved = region.clone();
+    if options.inside_corner_relief != CornerRelief::None {
+        let notches: Vec<_> = relief_centers(region, options.inside_corner_relief, offset)
+            .into_iter()
+            .map(|c| primitives::circle((c.x, c.y), offset))
+            .collect();
+        if !notches.is_empty() {
+            // Cut the holes again together with the notches, so notches merge into the holes
+            // they touch.
+            let mut cutters = region.neg.clone();
+            for hole in &mut cutters {
+                if hole.area() < 0.0 {
+                    hole.invert_direction_mut();
+                }
+            }
+            cutters.extend(notches);
+            relieved = Region::from_pos(region.pos.clone());
+            relieved.subtract_all(&cutters);
+        }
+    }
+    // Growing the material by the notch radius shrinks each notch to its center, where the
+    // tool has to reach.
+    expand_region(&relieved, offset, ExpandJoints::Round)
+}
+
+/// Centers of the relief circles (of radius `radius`) for every sharp inside corner of `region`.
+fn relief_centers(region: &Region, relief: CornerRelief, radius: f64) -> Vec<Vector2<f64>> {
+    let mut out = Vec::new();
+    let boundaries = region
+        .pos
+        .iter()
+        .map(|pl| (pl, true))
+        .chain(region.neg.iter().map(|pl| (pl, false)));
+    for (pl, is_pos) in boundaries {
+        let n = pl.vertex_count();
+        if !pl.is_closed() || n < 3 {
+            continue;
+        }
+        // `1.0` when the material is on the left.
+        let side = if (pl.area() > 0.0) == is_pos {
+            1.0
+        } else {
+            -1.0
+        };
+        let seg = |i: usize| -> (PlineVertex<f64>, PlineVertex<f64>) {
+            (pl.at(i % n), pl.at((i + 1) % n))
+        };
+        for i in 0..n {
+            let (p1, p2) = seg(i + n - 1);
+            let (v1, v2) = seg(i);
+            let incoming = seg_tangent_vector(p1, p2, p2.pos()).normalize();
+            let outgoing = seg_tangent_vector(v1, v2, v1.pos()).normalize();
+            let turn = incoming.perp_dot(outgoing).atan2(incoming.dot(outgoing)) * side;
+            if turn.to_degrees() >= -CORNER_TURN {
+                continue;
+            }
+            // Normals pointing away from the material, into the cut.
+            let cut_in = Vector2::new(incoming.y, -incoming.x).scale(side);
+            let cut_out = Vector2::new(outgoing.y, -outgoing.x).scale(side);
+            let direction = match relief {
+                CornerRelief::None => continue,
+                CornerRelief::Dogbone => (cut_in + cut_out).normalize(),
+                // Stay against the longer side and notch across the shorter one.
+                CornerRelief::Tbone if seg_length(p1, p2) >= seg_length(v1, v2) => cut_in,
+                CornerRelief::Tbone => cut_out,
+            };
+            out.push(v1.pos() + direction.scale(radius));
+        }
+    }
+    out
+}
@@ -0,0 +1,111 @@
+//! Shared evaluation state for everything generated from one config.
+//!
+//! Outlines reference each other, and PCBs and cases reference outlines, so the same regions are
+//! asked for many times per render. An [`OutlineContext`] resolves the outline measurements and
+//! places the points once, and generates every outline once, on first use.
+//!
+//! A measurement that cannot be resolved does not fail the context: it fails the outlines (and,
+//! through [`OutlineContext::check_measurements`], the PCBs and cases) whose expressions use it.
+
+use std::borrow::Cow;
+use std::cell::{OnceCell, RefCell};
+use std::collections::{HashMap, HashSet};
+
+use ergogen_geometry::region::Region;
+use ergogen_layout::{BindTrace, PointsOutput};
+use ergogen_parser::{PreparedConfig, Value};
+
+use crate::{OutlineError, generate_outline_region_inner, measure, sample};
+
+/// Generated outlines by name.
+pub(crate) type RegionCache = RefCell<HashMap<String, Region>>;
+
+pub struct OutlineContext<'a> {
+    prepared: Cow<'a, PreparedConfig>,
+    points: OnceCell<(PointsOutput, BindTrace)>,
+    regions: RegionCache,
+    /// Why a measurement is missing, by `outlines.<name>` or `outlines.<name>.<field>`.
+    failed_measurements: HashMap<String, String>,
+}
+
+impl<'a> OutlineContext<'a> {
+    /// Resolves the `outlines.<name>.<field>` measurements used by `prepared`. Points are placed
+    /// when first needed.
+    pub fn new(prepared: &'a PreparedConfig) -> Self {
+        let mut ctx = Self {
+            prepared: Cow::Borrowed(prepared),
+            points: OnceCell::new(),
+            regions: RegionCache::default(),
+            failed_measurements: HashMap::new(),
+        };
+        measure::resolve_measurements(&mut ctx);
+        ctx
+    }
+
+    /// The config, with the units extended by the outline measurements it uses.
+    pub fn prepared(&self) -> &PreparedConfig {
+        &self.prepared
+    }
+
+    /// The placed points, including the ones sampled from outlines.
+    pub fn points(&self) -> Result<&PointsOutput, OutlineError> {
+        Ok(&self.placed()?.0)
+    }
+
+    /// How autobind set the binds of [`Self::points`].
+    pub fn bind_trace(&self) -> Result<&BindTrace, OutlineError> {
+        Ok(&self.placed()?.1)
+    }
+
+    fn placed(&self) -> Result<&(PointsOutput, BindTrace), OutlineError> {
+        if let Some(placed) = self.points.get() {
+            return Ok(placed);
+        }
+        let placed = sample::place_points(&self.prepared, &self.regions)?;
+        Ok(self.points.get_or_init(|| placed))
+    }
+
+    /// The outline `name`, generated on the first call.
+    pub fn region(&self, name: &str) -> Result<Region, OutlineError> {
+        if let Some(outline) = self
+            .prepared
+            .canonical
+            .get_path(&format!("outlines.{name}"))
+        {
+            self.check_measurements(outline)?;
+        }
+        generate_outline_region_inner(
+            &self.prepared,
+            name,
+            self.points()?,
+            &mut HashSet::new(),
+            &self.regions,
+        )
+    }
+
+    /// Fails if `config`, or an outline it refers to by name, uses a measurement that could not
+    /// be resolved. Outlines are checked by [`Self::region`].
+    pub fn check_measurements(&self, config: &Value) -> Result<(), OutlineError> {
+        measure::check_measurements(self, config)
+    }
+
+    pub(crate) fn fail_measurement(&mut self, name: String, message: String) {
+        self.failed_measurements.insert(name, message);
+    }
+
+    pub(crate) fn failed_measurement(&self, name: &str) -> Option<OutlineError> {
+        self.failed_measurements
+            .get(name)
+            .map(|message| OutlineError::FailedMeasurement {
+                name: name.to_string(),
+                message: message.clone(),
+            })
+    }
+
+    /// Adds `vars` to the units. Outlines generated so far stay cached: they cannot depend on
+    /// variables that did not exist yet.
+    pub(crate) fn extend_units(&mut self, vars: impl IntoIterator<Item = (String, f64)>) {
+        let prepared = self.prepared.to_mut();
+        prepared.units = prepared.units.with_extra_vars(vars);
+    }
+}
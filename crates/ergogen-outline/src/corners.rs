@@ -0,0 +1,304 @@
+//! Fillets and chamfers on selected corners of a referenced outline.
+//!
+//! ```yaml
+//! outlines.case:
+//!   - what: outline
+//!     name: board
+//!     fillet:
+//!       radius: 3
+//!       corners: convex    # convex | concave | all (default)
+//!       max_angle: 120     # only corners whose inside angle is below this
+//!       indices: [0, 3]    # vertex indices, counted over all boundaries (islands, then holes)
+//!       near: [matrix_pinky_top]
+//!       within: 12         # with `near`: every corner this close; otherwise the closest one
+//!     chamfer:
+//!       size: 2            # distance cut off along both edges
+//! ```
+//!
+//! Every given selector must match. Only corners between two straight edges are touched; the
+//! fillet or chamfer is shortened when an edge is too short to hold it (on either end).
+
+use indexmap::IndexMap;
+
+use cavalier_contours::core::math::Vector2;
+use cavalier_contours::polyline::{PlineSource, PlineSourceMut, PlineVertex};
+use ergogen_core::Point;
+use ergogen_geometry::Polyline;
+use ergogen_geometry::region::Region;
+use ergogen_parser::{Units, Value};
+
+use crate::{OutlineError, eval_number};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CornerOp {
+    Fillet,
+    Chamfer,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Convexity {
+    All,
+    Convex,
+    Concave,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct CornerSpec {
+    op: CornerOp,
+    /// Fillet radius or chamfer size.
+    amount: f64,
+    convexity: Convexity,
+    max_angle: Option<f64>,
+    indices: Option<Vec<usize>>,
+    near: Vec<[f64; 2]>,
+    within: Option<f64>,
+}
+
+/// Parses the object form of `fillet` / `chamfer`. A plain `chamfer: <size>` cuts every corner.
+pub(crate) fn parse_corner_spec(
+    op: CornerOp,
+    raw: &Value,
+    at: &str,
+    units: &Units,
+    ref_points: &IndexMap<String, Point>,
+) -> Result<CornerSpec, OutlineError> {
+    let err = |at: String, message: &str| OutlineError::InvalidCorners {
+        at,
+        message: message.to_string(),
+    };
+    let amount_key = match op {
+        CornerOp::Fillet => "radius",
+        CornerOp::Chamfer => "size",
+    };
+    let mut spec = CornerSpec {
+        op,
+        amount: 0.0,
+        convexity: Convexity::All,
+        max_angle: None,
+        indices: None,
+        near: Vec::new(),
+        within: None,
+    };
+    let m = match raw {
+        Value::Map(m) => m,
+        other => {
+            spec.amount = eval_number(units, other, at)?;
+            return Ok(spec);
+        }
+    };
+
+    let Some(amount) = m.get(amount_key) else {
+        return Err(err(
+            format!("{at}.{amount_key}"),
+            &format!("\"{amount_key}\" is required"),
+        ));
+    };
+    spec.amount = eval_number(units, amount, &format!("{at}.{amount_key}"))?;
+    if spec.amount < 0.0 {
+        return Err(err(format!("{at}.{amount_key}"), "must not be negative"));
+    }
+
+    spec.convexity = match m.get("corners") {
+        None | Some(Value::Null) => Convexity::All,
+        Some(Value::String(s)) if s == "all" => Convexity::All,
+        Some(Value::String(s)) if s == "convex" => Convexity::Convex,
+        Some(Value::String(s)) if s == "concave" => Convexity::Concave,
+        Some(_) => {
+            return Err(err(
+                format!("{at}.corners"),
+                "must be one of all, convex or concave",
+            ));
+        }
+    };
+    if let Some(v) = m.get("max_angle") {
+        spec.max_angle = Some(eval_number(units, v, &format!("{at}.max_angle"))?);
+    }
+    if let Some(v) = m.get("indices") {
+        let Value::Seq(seq) = v else {
+            return Err(err(format!("{at}.indices"), "must be an array"));
+        };
+        let mut indices = Vec::with_capacity(seq.len());
+        for (i, v) in seq.iter().enumerate() {
+            let n = eval_number(units, v, &format!("{at}.indices[{i}]"))?;
+            if n < 0.0 || n.fract() != 0.0 {
+                return Err(err(
+                    format!("{at}.indices[{i}]"),
+                    "must be a non-negative whole number",
+                ));
+            }
+            indices.push(n as usize);
+        }
+        spec.indices = Some(indices);
+    }
+    if let Some(v) = m.get("near") {
+        let names = match v {
+            Value::String(s) => vec![s.clone()],
+            Value::Seq(seq) => seq
+                .iter()
+                .map(|v| match v {
+                    Value::String(s) => Ok(s.clone()),
+                    _ => Err(err(format!("{at}.near"), "must name points")),
+                })
+                .collect::<Result<_, _>>()?,
+            _ => return Err(err(format!("{at}.near"), "must name points")),
+        };
+        for name in names {
+            let Some(p) = ref_points.get(&name) else {
+                return Err(err(
+                    format!("{at}.near"),
+                    &format!("unknown point \"{name}\""),
+                ));
+            };
+            spec.near.push([p.x, p.y]);
+        }
+    }
+    if let Some(v) = m.get("within") {
+        spec.within = Some(eval_number(units, v, &format!("{at}.within"))?);
+    }
+    Ok(spec)
+}
+
+/// A corner between two straight edges.
+struct Corner {
+    /// Boundary (islands first, then holes) and vertex index within it.
+    pline: usize,
+    vertex: usize,
+    /// Running index over all boundaries, as used by `indices`.
+    index: usize,
+    pos: Vector2<f64>,
+    /// Counter-clockwise turn in degrees; positive turns are convex (material is on the left for
+    /// both islands and holes).
+    turn: f64,
+}
+
+pub(crate) fn apply_corner_spec(region: &Region, spec: &CornerSpec) -> Region {
+    if spec.amount == 0.0 {
+        return region.clone();
+    }
+    let plines: Vec<&Polyline<f64>> = region.pos.iter().chain(&region.neg).collect();
+
+    let mut corners = Vec::new();
+    let mut index = 0;
+    for (p, pl) in plines.iter().enumerate() {
+        let n = pl.vertex_count();
+        for i in 0..n {
+            let prev = pl.at((i + n - 1) % n);
+            let v = pl.at(i);
+            let next = pl.at((i + 1) % n);
+            index += 1;
+            if !prev.bulge_is_zero() || !v.bulge_is_zero() {
+                continue;
+            }
+            let (u, w) = (v.pos() - prev.pos(), next.pos() - v.pos());
+            let turn = u.perp_dot(w).atan2(u.dot(w)).to_degrees();
+            if turn.abs() < 1e-6 {
+                continue;
+            }
+            corners.push(Corner {
+                pline: p,
+                vertex: i,
+                index: index - 1,
+                pos: v.pos(),
+                turn,
+            });
+        }
+    }
+
+    let selected: Vec<&Corner> = corners.iter().filter(|c| selects(spec, c)).collect();
+    let selected: Vec<&Corner> = if !spec.near.is_empty() && spec.within.is_none() {
+        // The closest corner to each named point.
+        let mut picked: Vec<&Corner> = Vec::new();
+        for near in &spec.near {
+            let target = Vector2::new(near[0], near[1]);
+            if let Some(c) = selected.iter().min_by(|a, b| {
+                (a.pos - target)
+                    .length()
+                    .total_cmp(&(b.pos - target).length())
+            }) && !picked.iter().any(|p| p.index == c.index)
+            {
+                picked.push(c);
+            }
+        }
+        picked
+    } else {
+        selected
+    };
+
+    let mut out = region.clone();
+    for (p, pl) in plines.iter().enumerate() {
+        let vertices: Vec<usize> = selected
+            .iter()
+            .filter(|c| c.pline == p)
+            .map(|c| c.vertex)
+            .collect();
+        if vertices.is_empty() {
+            continue;
+        }
+        let cut = cut_corners(pl, &vertices, spec);
+        if p < region.pos.len() {
+            out.pos[p] = cut;
+        } else {
+            out.neg[p - region.pos.len()] = cut;
+        }
+    }
+    out
+}
+
+fn selects(spec: &CornerSpec, c: &Corner) -> bool {
+    let convex = c.turn > 0.0;
+    match spec.convexity {
+        Convexity::Convex if !convex => return false,
+        Convexity::Concave if convex => return false,
+        _ => {}
+    }
+    if let Some(max) = spec.max_angle
+        && 180.0 - c.turn >= max
+    {
+        return false;
+    }
+    if let Some(indices) = &spec.indices
+        && !indices.contains(&c.index)
+    {
+        return false;
+    }
+    if let Some(within) = spec.within
+        && !spec
+            .near
+            .iter()
+            .any(|n| (c.pos - Vector2::new(n[0], n[1])).length() <= within)
+    {
+        return false;
+    }
+    true
+}
+
+fn cut_corners(pl: &Polyline<f64>, vertices: &[usize], spec: &CornerSpec) -> Polyline<f64> {
+    let n = pl.vertex_count();
+    let cut = |i: usize| vertices.contains(&i);
+    let mut out = Polyline::new_closed();
+    for i in 0..n {
+        let v = pl.at(i);
+        if !cut(i) {
+            out.add_vertex(v);
+            continue;
+        }
+        let (prev, next) = (pl.at((i + n - 1) % n), pl.at((i + 1) % n));
+        let (u, w) = (v.pos() - prev.pos(), next.pos() - v.pos());
+        // Edges shared with another cut corner only offer half their length.
+        let room_in = u.length() / if cut((i + n - 1) % n) { 2.0 } else { 1.0 };
+        let room_out = w.length() / if cut((i + 1) % n) { 2.0 } else { 1.0 };
+        let (u, w) = (u.normalize(), w.normalize());
+        let turn = u.perp_dot(w).atan2(u.dot(w));
+
+        let (setback, bulge) = match spec.op {
+            CornerOp::Fillet => (spec.amount * (turn.abs() / 2.0).tan(), (turn / 4.0).tan()),
+            CornerOp::Chamfer => (spec.amount, 0.0),
+        };
+        let setback = setback.min(room_in).min(room_out);
+        let p1 = v.pos() - u.scale(setback);
+        let p2 = v.pos() + w.scale(setback);
+        out.add_vertex(PlineVertex::new(p1.x, p1.y, bulge));
+        out.add_vertex(PlineVertex::new(p2.x, p2.y, v.bulge));
+    }
+    out.remove_redundant(1e-6).unwrap_or(out)
+}
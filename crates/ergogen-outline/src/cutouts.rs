@@ -0,0 +1,259 @@
+//! Plate cutouts for switches and stabilizers.
+//!
+//! ```yaml
+//! outlines.plate:
+//!   - what: rectangle
+//!     size: [120, 60]
+//!   - what: switch_cutout
+//!     where: true
+//!     profile: mx_notched  # mx | mx_notched | mx_opening | alps | choc | choc_v2 | choc_mini
+//!     kerf: 0.1            # width of the cut; every edge moves in by half of it
+//!     operation: subtract
+//!   - what: stabilizer_cutout
+//!     where: true          # keys narrower than 2u are skipped
+//!     size: 6.25           # optional: stabilizer size in u instead of the key's width
+//!     kerf: 0.1
+//!     operation: subtract
+//! ```
+//!
+//! Switch cutouts are centered on the key and turn with it. `mx_notched` adds 0.8mm side notches
+//! for plates that clip the switch housing, `mx_opening` puts them on the top and bottom edges so
+//! switches can be opened while mounted.
+//!
+//! Stabilizer cutouts fit both Cherry and Costar plate-mount stabilizers (a 6.75 x 12.3 housing
+//! with a 3.3 x 14 Costar slot) and sit on either side of the key along its x axis, 23.8mm apart for
+//! 2u to 2.75u keys, 38.1mm for 3u, 100mm for 6.25u and 114.3mm for 7u. The key's size in u comes
+//! from its `width` (`width + (u - $default_width)` divided by `u`).
+
+use ergogen_geometry::primitives;
+use ergogen_geometry::region::Region;
+use ergogen_parser::{Units, Value};
+
+use crate::{OutlineError, eval_number};
+
+/// Stabilizer spacing (center to center, mm) by key size in u.
+const STABILIZER_SPACINGS: &[(f64, f64)] = &[
+    (2.0, 23.8),
+    (2.25, 23.8),
+    (2.75, 23.8),
+    (3.0, 38.1),
+    (6.25, 100.0),
+    (7.0, 114.3),
+];
+/// Vertical offset of the stabilizer housing center from the key center.
+const STABILIZER_OFFSET: f64 = -0.62;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Profile {
+    Mx,
+    MxNotched,
+    MxOpening,
+    Alps,
+    Choc,
+    ChocV2,
+    ChocMini,
+}
+
+pub(crate) struct SwitchSpec {
+    profile: Profile,
+    kerf: f64,
+}
+
+pub(crate) struct StabilizerSpec {
+    /// Explicit size in u; otherwise taken from each key's width.
+    size: Option<f64>,
+    kerf: f64,
+    /// Unit size and gap between neighbouring keys, to turn key widths into u.
+    u: f64,
+    gap: f64,
+}
+
+fn err(key: &str, message: impl Into<String>) -> OutlineError {
+    OutlineError::InvalidCutout {
+        at: format!("outlines.{key}"),
+        message: message.into(),
+    }
+}
+
+fn parse_kerf(obj: &indexmap::IndexMap<String, Value>, units: &Units) -> Result<f64, OutlineError> {
+    let kerf = match obj.get("kerf") {
+        None | Some(Value::Null) => 0.0,
+        Some(v) => eval_number(units, v, "outlines.kerf")?,
+    };
+    if !(0.0..1.0).contains(&kerf) {
+        return Err(err("kerf", "must be at least 0 and below 1"));
+    }
+    Ok(kerf)
+}
+
+pub(crate) fn parse_switch_spec(
+    obj: &indexmap::IndexMap<String, Value>,
+    units: &Units,
+) -> Result<SwitchSpec, OutlineError> {
+    let profile = match obj.get("profile") {
+        None | Some(Value::Null) => Profile::Mx,
+        Some(Value::String(s)) => match s.as_str() {
+            "mx" => Profile::Mx,
+            "mx_notched" => Profile::MxNotched,
+            "mx_opening" => Profile::MxOpening,
+            "alps" => Profile::Alps,
+            "choc" => Profile::Choc,
+            "choc_v2" => Profile::ChocV2,
+            "choc_mini" => Profile::ChocMini,
+            other => {
+                return Err(err(
+                    "profile",
+                    format!("unknown switch profile \"{other}\""),
+                ));
+            }
+        },
+        Some(_) => return Err(err("profile", "must be a string")),
+    };
+    Ok(SwitchSpec {
+        profile,
+        kerf: parse_kerf(obj, units)?,
+    })
+}
+
+pub(crate) fn parse_stabilizer_spec(
+    obj: &indexmap::IndexMap<String, Value>,
+    units: &Units,
+) -> Result<StabilizerSpec, OutlineError> {
+    let size = match obj.get("size") {
+        None | Some(Value::Null) => None,
+        Some(v) => {
+            let size = eval_number(units, v, "outlines.size")?;
+            spacing_for(size).ok_or_else(|| err("size", unsupported_size(size)))?;
+            Some(size)
+        }
+    };
+    let u = units.get("u").unwrap_or(19.0);
+    Ok(StabilizerSpec {
+        size,
+        kerf: parse_kerf(obj, units)?,
+        u,
+        gap: u - units.get("$default_width").unwrap_or(18.0),
+    })
+}
+
+fn spacing_for(size: f64) -> Option<f64> {
+    STABILIZER_SPACINGS
+        .iter()
+        .find(|(u, _)| (u - size).abs() < 0.05)
+        .map(|(_, spacing)| *spacing)
+}
+
+fn unsupported_size(size: f64) -> String {
+    let known: Vec<String> = STABILIZER_SPACINGS
+        .iter()
+        .map(|(u, _)| format!("{u}u"))
+        .collect();
+    format!(
+        "no stabilizer spacing for {size}u (known: {})",
+        known.join(", ")
+    )
+}
+
+/// The switch cutout centered on `[x, y]`, turned by `r` degrees.
+pub(crate) fn switch_region(spec: &SwitchSpec, x: f64, y: f64, r: f64) -> Region {
+    let k = spec.kerf;
+    let local = match spec.profile {
+        Profile::Mx => rect(0.0, 0.0, 14.0 - k, 14.0 - k),
+        Profile::MxNotched => notched(k),
+        Profile::MxOpening => notched(k).iter().map(|&(x, y)| (-y, x)).collect(),
+        Profile::Alps => rect(0.0, 0.0, 15.5 - k, 12.8 - k),
+        Profile::Choc => rect(0.0, 0.0, 13.8 - k, 13.8 - k),
+        Profile::ChocV2 => rect(0.0, 0.0, 14.0 - k, 14.0 - k),
+        Profile::ChocMini => rect(0.0, 0.0, 13.5 - k, 12.5 - k),
+    };
+    Region::from_pos(vec![primitives::polygon(&place(&local, x, y, r))])
+}
+
+/// Stabilizer cutouts for a key `key_width` wide, or `None` when the key needs none (under 2u).
+pub(crate) fn stabilizer_region(
+    spec: &StabilizerSpec,
+    key_width: f64,
+    x: f64,
+    y: f64,
+    r: f64,
+) -> Result<Option<Region>, OutlineError> {
+    let size = spec.size.unwrap_or((key_width + spec.gap) / spec.u);
+    if spec.size.is_none() && size < 2.0 - 0.05 {
+        return Ok(None);
+    }
+    let spacing = spacing_for(size).ok_or_else(|| err("where", unsupported_size(size)))?;
+    let k = spec.kerf;
+    let (cw, ch) = (6.75 - k, 12.3 - k);
+    let (sw, sh) = (3.3 - k, 14.0 - k);
+    let plines = [-spacing / 2.0, spacing / 2.0]
+        .into_iter()
+        .map(|cx| {
+            let cy = STABILIZER_OFFSET;
+            // Cherry housing with the taller, narrower Costar slot poking out top and bottom.
+            let outline = [
+                (cx - cw / 2.0, cy - ch / 2.0),
+                (cx - sw / 2.0, cy - ch / 2.0),
+                (cx - sw / 2.0, cy - sh / 2.0),
+                (cx + sw / 2.0, cy - sh / 2.0),
+                (cx + sw / 2.0, cy - ch / 2.0),
+                (cx + cw / 2.0, cy - ch / 2.0),
+                (cx + cw / 2.0, cy + ch / 2.0),
+                (cx + sw / 2.0, cy + ch / 2.0),
+                (cx + sw / 2.0, cy + sh / 2.0),
+                (cx - sw / 2.0, cy + sh / 2.0),
+                (cx - sw / 2.0, cy + ch / 2.0),
+                (cx - cw / 2.0, cy + ch / 2.0),
+            ];
+            primitives::polygon(&place(&outline, x, y, r))
+        })
+        .collect();
+    Ok(Some(Region::from_pos(plines)))
+}
+
+/// Counter-clockwise rectangle corners.
+fn rect(cx: f64, cy: f64, w: f64, h: f64) -> Vec<(f64, f64)> {
+    let (hw, hh) = (w / 2.0, h / 2.0);
+    vec![
+        (cx - hw, cy - hh),
+        (cx + hw, cy - hh),
+        (cx + hw, cy + hh),
+        (cx - hw, cy + hh),
+    ]
+}
+
+/// MX square with 0.8mm deep, 3.1mm tall notches on the left and right edges, 1mm from the
+/// corners.
+fn notched(kerf: f64) -> Vec<(f64, f64)> {
+    let (h, d) = ((14.0 - kerf) / 2.0, 0.8);
+    let (a, b) = (2.9 + kerf / 2.0, 6.0 - kerf / 2.0);
+    vec![
+        (-h, -h),
+        (h, -h),
+        (h, -b),
+        (h + d, -b),
+        (h + d, -a),
+        (h, -a),
+        (h, a),
+        (h + d, a),
+        (h + d, b),
+        (h, b),
+        (h, h),
+        (-h, h),
+        (-h, b),
+        (-h - d, b),
+        (-h - d, a),
+        (-h, a),
+        (-h, -a),
+        (-h - d, -a),
+        (-h - d, -b),
+        (-h, -b),
+    ]
+}
+
+fn place(local: &[(f64, f64)], x: f64, y: f64, r: f64) -> Vec<(f64, f64)> {
+    let (sin, cos) = r.to_radians().sin_cos();
+    local
+        .iter()
+        .map(|&(lx, ly)| (x + lx * cos - ly * sin, y + lx * sin + ly * cos))
+        .collect()
+}
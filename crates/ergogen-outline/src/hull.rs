@@ -0,0 +1,292 @@
+//! Hull algorithms for `what: hull` beyond the upstream concave hull.
+//!
+//! ```yaml
+//! outlines.board:
+//!   - what: hull
+//!     points: [matrix_pinky_bottom, matrix_pinky_top, thumb_far]
+//!     algorithm: alpha     # concave (default) | convex | alpha | rounded
+//!     alpha: 30            # alpha only: largest circumradius of a triangle in the shape
+//!   - what: hull
+//!     points: [...]
+//!     algorithm: rounded
+//!     radius: 3            # rounded only: offset, and radius of every corner
+//! ```
+//!
+//! - `concave` is the hull.js port, steered by `concavity`.
+//! - `convex` never has notches.
+//! - `alpha` keeps the Delaunay triangles of the samples whose circumcircle is at most `alpha` in
+//!   radius, so gaps wider than about `2 * alpha` stay open and the shape may have several islands
+//!   and holes. A large `alpha` approaches the convex hull.
+//! - `rounded` grows the concave hull by `radius` and rounds all of its corners, inside ones
+//!   included, to at least `radius`. Notches narrower than `4 * radius` close up.
+//!
+//! All of them are deterministic: the same samples always give the same outline.
+
+use std::collections::{BTreeMap, HashSet};
+
+use cavalier_contours::polyline::{PlineSource, PlineSourceMut};
+use ergogen_geometry::primitives;
+use ergogen_geometry::region::Region;
+use ergogen_parser::{Units, Value};
+
+use crate::{OutlineError, eval_number, expand_region_round, simplify_closed_ring_points};
+
+/// Rings of sample points.
+pub(crate) type Rings = Vec<Vec<[f64; 2]>>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum HullAlgorithm {
+    Concave,
+    Convex,
+    Alpha { alpha: f64 },
+    Rounded { radius: f64 },
+}
+
+fn err(key: &str, message: impl Into<String>) -> OutlineError {
+    OutlineError::InvalidHull {
+        at: format!("outlines.{key}"),
+        message: message.into(),
+    }
+}
+
+pub(crate) fn parse_algorithm(
+    obj: &indexmap::IndexMap<String, Value>,
+    units: &Units,
+) -> Result<HullAlgorithm, OutlineError> {
+    let number = |key: &str| match obj.get(key) {
+        None | Some(Value::Null) => Err(err(key, format!("\"{key}\" is required"))),
+        Some(v) => eval_number(units, v, &format!("outlines.{key}")),
+    };
+    match obj.get("algorithm") {
+        None | Some(Value::Null) => Ok(HullAlgorithm::Concave),
+        Some(Value::String(s)) => match s.as_str() {
+            "concave" => Ok(HullAlgorithm::Concave),
+            "convex" => Ok(HullAlgorithm::Convex),
+            "alpha" => {
+                let alpha = number("alpha")?;
+                if alpha <= 0.0 {
+                    return Err(err("alpha", "must be positive"));
+                }
+                Ok(HullAlgorithm::Alpha { alpha })
+            }
+            "rounded" => {
+                let radius = number("radius")?;
+                if radius < 0.0 {
+                    return Err(err("radius", "must not be negative"));
+                }
+                Ok(HullAlgorithm::Rounded { radius })
+            }
+            other => Err(err(
+                "algorithm",
+                format!("unknown hull algorithm \"{other}\""),
+            )),
+        },
+        Some(_) => Err(err("algorithm", "must be a string")),
+    }
+}
+
+/// The region enclosed by `islands` minus `holes`, each a ring of sample points mapped through
+/// `place`.
+pub(crate) fn rings_region(
+    islands: Rings,
+    holes: Rings,
+    place: impl Fn([f64; 2]) -> (f64, f64),
+) -> Region {
+    let polygon = |ring: Vec<[f64; 2]>| {
+        let vertices: Vec<(f64, f64)> = simplify_closed_ring_points(ring)
+            .into_iter()
+            .map(&place)
+            .collect();
+        primitives::polygon(&vertices)
+    };
+    let islands: Vec<_> = islands.into_iter().map(polygon).collect();
+    if holes.is_empty() {
+        return Region::from_pos(islands);
+    }
+    let holes: Vec<_> = holes
+        .into_iter()
+        .map(|ring| {
+            let mut pl = polygon(ring);
+            if pl.area() < 0.0 {
+                pl.invert_direction_mut();
+            }
+            pl
+        })
+        .collect();
+    let mut region = Region::union_all(islands);
+    region.subtract_all(&holes);
+    region
+}
+
+/// Grows `region` by `radius` and rounds every corner to at least `radius`: a closing by
+/// `radius` on top of the offset.
+pub(crate) fn round(region: &Region, radius: f64) -> Result<Region, OutlineError> {
+    if radius == 0.0 {
+        return Ok(region.clone());
+    }
+    let grown = expand_region_round(region, 2.0 * radius)?;
+    expand_region_round(&grown, -radius)
+}
+
+/// Alpha shape of `points`: `(islands, holes)`, counter-clockwise and clockwise rings.
+pub(crate) fn alpha_shape(points: &[[f64; 2]], alpha: f64) -> (Rings, Rings) {
+    let mut unique = points.to_vec();
+    unique.sort_by(|a, b| a[0].total_cmp(&b[0]).then(a[1].total_cmp(&b[1])));
+    unique.dedup();
+
+    let kept: Vec<Triangle> = delaunay(&unique)
+        .into_iter()
+        .filter(|t| t.radius_sq <= alpha * alpha)
+        .collect();
+
+    // Edges of exactly one kept triangle, in its counter-clockwise direction.
+    let edges: HashSet<(usize, usize)> = kept.iter().flat_map(|t| t.edges()).collect();
+    let mut outgoing: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for &(a, b) in &edges {
+        if !edges.contains(&(b, a)) {
+            outgoing.entry(a).or_default().push(b);
+        }
+    }
+    for targets in outgoing.values_mut() {
+        targets.sort_unstable();
+    }
+
+    let mut islands = Vec::new();
+    let mut holes = Vec::new();
+    while let Some((&start, _)) = outgoing.iter().find(|(_, targets)| !targets.is_empty()) {
+        let first = outgoing.get_mut(&start).unwrap().remove(0);
+        let mut ring = vec![start];
+        let (mut prev, mut cur) = (start, first);
+        loop {
+            let targets = outgoing
+                .get_mut(&cur)
+                .expect("boundary edges form closed rings");
+            // Where the shape touches itself at a vertex, stay in the same wedge of material:
+            // take the edge turning most sharply to the right.
+            let back = sub(unique[prev], unique[cur]);
+            let turn = |t: usize| clockwise_angle(back, sub(unique[t], unique[cur]));
+            let next =
+                (0..targets.len()).min_by(|&i, &j| turn(targets[i]).total_cmp(&turn(targets[j])));
+            if cur == start && next.is_none_or(|k| turn(first) < turn(targets[k])) {
+                break;
+            }
+            let next = next.expect("boundary edges form closed rings");
+            ring.push(cur);
+            (prev, cur) = (cur, targets.remove(next));
+        }
+        let ring: Vec<[f64; 2]> = ring.into_iter().map(|i| unique[i]).collect();
+        if ring_area(&ring) > 0.0 {
+            islands.push(ring);
+        } else {
+            holes.push(ring);
+        }
+    }
+    (islands, holes)
+}
+
+fn sub(a: [f64; 2], b: [f64; 2]) -> [f64; 2] {
+    [a[0] - b[0], a[1] - b[1]]
+}
+
+/// Angle from `from` clockwise to `to`, in `(0, 2π]`.
+fn clockwise_angle(from: [f64; 2], to: [f64; 2]) -> f64 {
+    let angle = (from[1].atan2(from[0]) - to[1].atan2(to[0])).rem_euclid(std::f64::consts::TAU);
+    if angle == 0.0 {
+        std::f64::consts::TAU
+    } else {
+        angle
+    }
+}
+
+fn ring_area(ring: &[[f64; 2]]) -> f64 {
+    let n = ring.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = (ring[i], ring[(i + 1) % n]);
+            a[0] * b[1] - b[0] * a[1]
+        })
+        .sum::<f64>()
+        / 2.0
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Triangle {
+    /// Counter-clockwise.
+    v: [usize; 3],
+    center: [f64; 2],
+    radius_sq: f64,
+}
+
+impl Triangle {
+    fn new(points: &[[f64; 2]], v: [usize; 3]) -> Self {
+        let [a, b, c] = v.map(|i| points[i]);
+        let (bx, by) = (b[0] - a[0], b[1] - a[1]);
+        let (cx, cy) = (c[0] - a[0], c[1] - a[1]);
+        let d = 2.0 * (bx * cy - by * cx);
+        let (b2, c2) = (bx * bx + by * by, cx * cx + cy * cy);
+        let (ux, uy) = ((cy * b2 - by * c2) / d, (bx * c2 - cx * b2) / d);
+        Self {
+            v,
+            center: [a[0] + ux, a[1] + uy],
+            // Collinear triangles have no circumcircle; they never take part in the shape.
+            radius_sq: if d == 0.0 {
+                f64::INFINITY
+            } else {
+                ux * ux + uy * uy
+            },
+        }
+    }
+
+    fn edges(&self) -> [(usize, usize); 3] {
+        let [a, b, c] = self.v;
+        [(a, b), (b, c), (c, a)]
+    }
+
+    /// Strictly inside the circumcircle; points on it (common with key corners on a grid) are not.
+    fn encloses(&self, p: [f64; 2]) -> bool {
+        let (dx, dy) = (p[0] - self.center[0], p[1] - self.center[1]);
+        dx * dx + dy * dy < self.radius_sq * (1.0 - 1e-9)
+    }
+}
+
+/// Delaunay triangulation (Bowyer-Watson), inserting `points` in order.
+fn delaunay(points: &[[f64; 2]]) -> Vec<Triangle> {
+    let n = points.len();
+    if n < 3 {
+        return Vec::new();
+    }
+    let (mut min, mut max) = ([f64::INFINITY; 2], [f64::NEG_INFINITY; 2]);
+    for p in points {
+        min = [min[0].min(p[0]), min[1].min(p[1])];
+        max = [max[0].max(p[0]), max[1].max(p[1])];
+    }
+    let size = (max[0] - min[0]).max(max[1] - min[1]).max(1.0);
+    let mid = [(min[0] + max[0]) / 2.0, (min[1] + max[1]) / 2.0];
+
+    // A triangle far around all points, removed again at the end.
+    let mut all = points.to_vec();
+    all.push([mid[0] - 20.0 * size, mid[1] - size]);
+    all.push([mid[0] + 20.0 * size, mid[1] - size]);
+    all.push([mid[0], mid[1] + 20.0 * size]);
+    let mut triangles = vec![Triangle::new(&all, [n, n + 1, n + 2])];
+
+    for (i, &p) in points.iter().enumerate() {
+        let (bad, good): (Vec<Triangle>, Vec<Triangle>) =
+            triangles.into_iter().partition(|t| t.encloses(p));
+        let edges: HashSet<(usize, usize)> = bad.iter().flat_map(|t| t.edges()).collect();
+        triangles = good;
+        let mut cavity: Vec<(usize, usize)> = edges
+            .iter()
+            .copied()
+            .filter(|&(a, b)| !edges.contains(&(b, a)))
+            .collect();
+        cavity.sort_unstable();
+        triangles.extend(
+            cavity
+                .into_iter()
+                .map(|(a, b)| Triangle::new(&all, [a, b, i])),
+        );
+    }
+    triangles.retain(|t| t.v.iter().all(|&v| v < n));
+    triangles
+}
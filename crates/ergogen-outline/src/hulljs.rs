@@ -377,3 +377,20 @@ pub fn hull(pointset: Vec<[f64; 2]>, concavity: f64) -> Vec<[f64; 2]> {
 
     convex.into_iter().map(|p| p.to_xy()).collect()
 }
+
+/// The convex hull around `pointset` (upstream `convex.js`), as a closed point list like [`hull`].
+pub fn convex(pointset: Vec<[f64; 2]>) -> Vec<[f64; 2]> {
+    let points = sort_by_x(pointset.into_iter().map(Pt::from_xy).collect());
+    let mut points = filter_duplicates(points);
+    if points.len() < 3 {
+        let mut out = points.into_iter().map(|p| p.to_xy()).collect::<Vec<_>>();
+        if let Some(first) = out.first().copied() {
+            out.push(first);
+        }
+        return out;
+    }
+    convex_in_place(points.as_mut_slice())
+        .into_iter()
+        .map(|p| p.to_xy())
+        .collect()
+}
@@ -0,0 +1,586 @@
+//! `what: import` outlines: closed shapes loaded from DXF or SVG files.
+//!
+//! ```yaml
+//! outlines.plate:
+//!   - what: import
+//!     file: parts/logo.svg   # .dxf, otherwise an SVG document or bare path data
+//!     where: matrix_pinky_bottom
+//!     adjust.shift: [0, -10]
+//!     scale: 0.5
+//! ```
+//!
+//! File coordinates are millimetres relative to the placement point (SVG's downward y axis is
+//! flipped). DXF `LINE`, `ARC`, `CIRCLE` and `LWPOLYLINE` entities and SVG `<path>` elements are
+//! chained into closed contours: circular arcs stay arcs, Béziers and elliptical arcs are
+//! flattened, and SVG `transform`s are not applied. A contour inside an odd number of others is a
+//! hole. On mirrored points the shape is mirrored too.
+
+use std::f64::consts::PI;
+use std::path::Path;
+
+use cavalier_contours::core::math::Vector2;
+use cavalier_contours::polyline::{PlineSource, PlineSourceMut};
+use ergogen_geometry::dxf::{Dxf, Entity};
+use ergogen_geometry::region::Region;
+use ergogen_geometry::{PlineVertex, Polyline, primitives};
+use ergogen_parser::{Units, Value};
+
+use crate::{OutlineError, eval_number};
+
+/// Endpoints closer than this are joined when chaining loose segments.
+const JOIN_TOLERANCE: f64 = 1e-3;
+/// Segments per Bézier curve or elliptical arc when flattening SVG paths.
+const CURVE_STEPS: usize = 16;
+
+pub(crate) struct ImportSpec {
+    /// The file's shape in its own coordinates.
+    shape: Region,
+    scale: f64,
+}
+
+pub(crate) fn parse_import_spec(
+    obj: &indexmap::IndexMap<String, Value>,
+    units: &Units,
+) -> Result<ImportSpec, OutlineError> {
+    let err = |key: &str, message: String| OutlineError::InvalidImport {
+        at: format!("outlines.{key}"),
+        message,
+    };
+    let Some(Value::String(file)) = obj.get("file") else {
+        return Err(err("file", "\"file\" must be a path".to_string()));
+    };
+    let raw = ergogen_core::vfs::read_to_string(Path::new(file))
+        .map_err(|e| err("file", format!("cannot read \"{file}\": {e}")))?;
+
+    let contours = if file.to_ascii_lowercase().ends_with(".dxf") {
+        let dxf = Dxf::parse_str(&raw).map_err(|e| err("file", format!("\"{file}\": {e}")))?;
+        dxf_contours(&dxf)
+    } else {
+        svg_contours(&raw)
+    }
+    .map_err(|message| err("file", format!("\"{file}\": {message}")))?;
+    if contours.is_empty() {
+        return Err(err("file", format!("\"{file}\" contains no shapes")));
+    }
+
+    let scale = match obj.get("scale") {
+        None | Some(Value::Null) => 1.0,
+        Some(v) => eval_number(units, v, "outlines.scale")?,
+    };
+    if scale <= 0.0 {
+        return Err(err("scale", "must be positive".to_string()));
+    }
+
+    Ok(ImportSpec {
+        shape: nest(contours),
+        scale,
+    })
+}
+
+/// The imported shape scaled, mirrored when `mirrored`, rotated by `r` degrees and moved to
+/// `[x, y]`.
+pub(crate) fn import_region(spec: &ImportSpec, x: f64, y: f64, r: f64, mirrored: bool) -> Region {
+    let (sin, cos) = r.to_radians().sin_cos();
+    let place = |pl: &Polyline<f64>| {
+        let mut pl = pl.clone();
+        for v in &mut pl.vertex_data {
+            let (mut lx, ly) = (v.x * spec.scale, v.y * spec.scale);
+            if mirrored {
+                lx = -lx;
+                v.bulge = -v.bulge;
+            }
+            v.x = x + lx * cos - ly * sin;
+            v.y = y + lx * sin + ly * cos;
+        }
+        if mirrored {
+            pl.invert_direction_mut();
+        }
+        pl
+    };
+    Region {
+        pos: spec.shape.pos.iter().map(place).collect(),
+        neg: spec.shape.neg.iter().map(place).collect(),
+    }
+}
+
+/// Sorts closed contours into islands and holes by nesting depth.
+fn nest(contours: Vec<Polyline<f64>>) -> Region {
+    let (mut outer, mut holes) = (Vec::new(), Vec::new());
+    for (i, pl) in contours.iter().enumerate() {
+        let probe = pl.at(0).pos();
+        let depth = contours
+            .iter()
+            .enumerate()
+            .filter(|(j, other)| *j != i && other.winding_number(probe) != 0)
+            .count();
+        if depth % 2 == 0 {
+            outer.push(pl.clone());
+        } else {
+            holes.push(pl.clone());
+        }
+    }
+    let mut region = Region::union_all(outer);
+    if !holes.is_empty() {
+        region.subtract_all(&holes);
+    }
+    region
+}
+
+/// A straight (`bulge == 0`) or circular piece of a contour.
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    a: Vector2<f64>,
+    b: Vector2<f64>,
+    bulge: f64,
+}
+
+impl Segment {
+    fn reversed(self) -> Self {
+        Self {
+            a: self.b,
+            b: self.a,
+            bulge: -self.bulge,
+        }
+    }
+}
+
+/// Joins loose segments end to end into closed polylines.
+fn chain(segments: Vec<Segment>) -> Result<Vec<Polyline<f64>>, String> {
+    let near = |p: Vector2<f64>, q: Vector2<f64>| (p - q).length() < JOIN_TOLERANCE;
+    let mut left: Vec<Option<Segment>> = segments.into_iter().map(Some).collect();
+    let mut out = Vec::new();
+    while let Some(first) = left.iter_mut().find_map(Option::take) {
+        let mut pl = Polyline::new_closed();
+        pl.add_vertex(PlineVertex::new(first.a.x, first.a.y, first.bulge));
+        let mut end = first.b;
+        while !near(end, first.a) {
+            let next = left.iter_mut().find_map(|slot| match *slot {
+                Some(s) if near(s.a, end) => slot.take(),
+                Some(s) if near(s.b, end) => slot.take().map(Segment::reversed),
+                _ => None,
+            });
+            let Some(next) = next else {
+                return Err(format!("open contour at ({:.3}, {:.3})", end.x, end.y));
+            };
+            pl.add_vertex(PlineVertex::new(next.a.x, next.a.y, next.bulge));
+            end = next.b;
+        }
+        if pl.vertex_count() >= 2 {
+            out.push(pl);
+        }
+    }
+    Ok(out)
+}
+
+fn dxf_contours(dxf: &Dxf) -> Result<Vec<Polyline<f64>>, String> {
+    let mut closed = Vec::new();
+    let mut loose = Vec::new();
+    let mut unsupported: Vec<&str> = Vec::new();
+    let v = |p: &ergogen_geometry::dxf::Point2| Vector2::new(p.x, p.y);
+    for e in &dxf.entities {
+        match e {
+            Entity::Line(l) => loose.push(Segment {
+                a: v(&l.start),
+                b: v(&l.end),
+                bulge: 0.0,
+            }),
+            Entity::Circle(c) => {
+                closed.push(primitives::circle((c.center.x, c.center.y), c.radius))
+            }
+            Entity::Arc(a) => {
+                let sweep = (a.end_angle_deg - a.start_angle_deg).rem_euclid(360.0);
+                if sweep < 1e-9 {
+                    closed.push(primitives::circle((a.center.x, a.center.y), a.radius));
+                    continue;
+                }
+                let at = |deg: f64| {
+                    let (s, c) = deg.to_radians().sin_cos();
+                    Vector2::new(a.center.x + a.radius * c, a.center.y + a.radius * s)
+                };
+                loose.push(Segment {
+                    a: at(a.start_angle_deg),
+                    b: at(a.end_angle_deg),
+                    bulge: (sweep.to_radians() / 4.0).tan(),
+                });
+            }
+            Entity::LwPolyline(p) if p.closed => {
+                let mut pl = Polyline::new_closed();
+                for (q, bulge) in p.vertices.iter().zip(&p.bulges) {
+                    pl.add_vertex(PlineVertex::new(q.x, q.y, *bulge));
+                }
+                closed.push(pl);
+            }
+            Entity::LwPolyline(p) => {
+                for (i, pair) in p.vertices.windows(2).enumerate() {
+                    loose.push(Segment {
+                        a: v(&pair[0]),
+                        b: v(&pair[1]),
+                        bulge: p.bulges[i],
+                    });
+                }
+            }
+            Entity::Unsupported(u) => unsupported.push(&u.kind),
+        }
+    }
+    if !unsupported.is_empty() {
+        unsupported.sort_unstable();
+        unsupported.dedup();
+        return Err(format!("unsupported entities {}", unsupported.join(", ")));
+    }
+    loose.retain(|s| (s.b - s.a).length() >= JOIN_TOLERANCE);
+    closed.extend(chain(loose)?);
+    Ok(closed)
+}
+
+/// Contours of every `<path>` in an SVG document, or of `raw` itself when it is bare path data.
+fn svg_contours(raw: &str) -> Result<Vec<Polyline<f64>>, String> {
+    let mut builder = PathBuilder::default();
+    if raw.contains('<') {
+        let mut rest = raw;
+        while let Some(start) = rest.find("<path") {
+            rest = &rest[start + 5..];
+            let tag = &rest[..rest.find('>').ok_or("unterminated <path> element")?];
+            let Some(d) = attribute(tag, "d") else {
+                continue;
+            };
+            builder.path(d)?;
+        }
+    } else {
+        builder.path(raw)?;
+    }
+    let mut contours = builder.closed;
+    contours.extend(chain(builder.loose)?);
+    Ok(contours)
+}
+
+/// The value of `name="..."` (or single-quoted) inside a tag.
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = tag;
+    while let Some(i) = rest.find(name) {
+        let before = rest[..i].chars().next_back();
+        let after = rest[i + name.len()..].trim_start();
+        rest = &rest[i + name.len()..];
+        if before.is_some_and(|c| !c.is_whitespace()) {
+            continue;
+        }
+        let Some(value) = after.strip_prefix('=') else {
+            continue;
+        };
+        let value = value.trim_start();
+        let quote = value.chars().next()?;
+        if quote != '"' && quote != '\'' {
+            return None;
+        }
+        let value = &value[1..];
+        return value.find(quote).map(|end| &value[..end]);
+    }
+    None
+}
+
+/// Turns SVG path data into segments, flipping y so the drawing keeps its orientation.
+#[derive(Default)]
+struct PathBuilder {
+    closed: Vec<Polyline<f64>>,
+    loose: Vec<Segment>,
+    /// Segments of the subpath being drawn.
+    current: Vec<Segment>,
+}
+
+impl PathBuilder {
+    fn path(&mut self, d: &str) -> Result<(), String> {
+        let mut data = PathData {
+            s: d.as_bytes(),
+            i: 0,
+        };
+        let (mut cur, mut start) = ([0.0, 0.0], [0.0, 0.0]);
+        // Second control point of the previous cubic (`C`/`S`) or quadratic (`Q`/`T`) curve.
+        let mut last_cubic: Option<[f64; 2]> = None;
+        let mut last_quad: Option<[f64; 2]> = None;
+
+        while let Some(mut cmd) = data.command()? {
+            loop {
+                let rel = cmd.is_ascii_lowercase();
+                let base = if rel { cur } else { [0.0, 0.0] };
+                let point = |data: &mut PathData| -> Result<[f64; 2], String> {
+                    Ok([base[0] + data.number()?, base[1] + data.number()?])
+                };
+                let (mut cubic, mut quad) = (None, None);
+                match cmd.to_ascii_uppercase() {
+                    b'M' => {
+                        self.finish_open();
+                        cur = point(&mut data)?;
+                        start = cur;
+                        // Further pairs are implicit line-tos.
+                        cmd = if rel { b'l' } else { b'L' };
+                    }
+                    b'L' => {
+                        let p = point(&mut data)?;
+                        self.line(cur, p);
+                        cur = p;
+                    }
+                    b'H' => {
+                        let p = [base[0] + data.number()?, cur[1]];
+                        self.line(cur, p);
+                        cur = p;
+                    }
+                    b'V' => {
+                        let p = [cur[0], base[1] + data.number()?];
+                        self.line(cur, p);
+                        cur = p;
+                    }
+                    b'C' | b'S' => {
+                        let c1 = if cmd.eq_ignore_ascii_case(&b'C') {
+                            point(&mut data)?
+                        } else {
+                            reflect(last_cubic, cur)
+                        };
+                        let c2 = point(&mut data)?;
+                        let p = point(&mut data)?;
+                        self.curve(|t| cubic_at(cur, c1, c2, p, t));
+                        cubic = Some(c2);
+                        cur = p;
+                    }
+                    b'Q' | b'T' => {
+                        let c = if cmd.eq_ignore_ascii_case(&b'Q') {
+                            point(&mut data)?
+                        } else {
+                            reflect(last_quad, cur)
+                        };
+                        let p = point(&mut data)?;
+                        self.curve(|t| quad_at(cur, c, p, t));
+                        quad = Some(c);
+                        cur = p;
+                    }
+                    b'A' => {
+                        let (rx, ry) = (data.number()?.abs(), data.number()?.abs());
+                        let phi = data.number()?.to_radians();
+                        let (large, sweep) = (data.flag()?, data.flag()?);
+                        let p = point(&mut data)?;
+                        self.arc(cur, p, rx, ry, phi, large, sweep);
+                        cur = p;
+                    }
+                    b'Z' => {
+                        self.line(cur, start);
+                        self.finish_closed();
+                        cur = start;
+                    }
+                    other => return Err(format!("unknown path command '{}'", other as char)),
+                }
+                (last_cubic, last_quad) = (cubic, quad);
+                if cmd.eq_ignore_ascii_case(&b'Z') || !data.at_number() {
+                    break;
+                }
+            }
+        }
+        self.finish_open();
+        Ok(())
+    }
+
+    fn push(&mut self, a: [f64; 2], b: [f64; 2], bulge: f64) {
+        let flip = |p: [f64; 2]| Vector2::new(p[0], -p[1]);
+        let (a, b) = (flip(a), flip(b));
+        if (b - a).length() < 1e-9 {
+            return;
+        }
+        self.current.push(Segment {
+            a,
+            b,
+            bulge: -bulge,
+        });
+    }
+
+    fn line(&mut self, a: [f64; 2], b: [f64; 2]) {
+        self.push(a, b, 0.0);
+    }
+
+    fn curve(&mut self, at: impl Fn(f64) -> [f64; 2]) {
+        let mut prev = at(0.0);
+        for k in 1..=CURVE_STEPS {
+            let p = at(k as f64 / CURVE_STEPS as f64);
+            self.line(prev, p);
+            prev = p;
+        }
+    }
+
+    /// An SVG endpoint arc (SVG 1.1, appendix F.6.5): circles become a single bulge segment,
+    /// ellipses are flattened.
+    #[allow(clippy::too_many_arguments)]
+    fn arc(
+        &mut self,
+        a: [f64; 2],
+        b: [f64; 2],
+        mut rx: f64,
+        mut ry: f64,
+        phi: f64,
+        large: bool,
+        sweep: bool,
+    ) {
+        if rx < 1e-12 || ry < 1e-12 {
+            self.line(a, b);
+            return;
+        }
+        let (sin, cos) = phi.sin_cos();
+        let (hx, hy) = ((a[0] - b[0]) / 2.0, (a[1] - b[1]) / 2.0);
+        let (x1, y1) = (cos * hx + sin * hy, -sin * hx + cos * hy);
+        let lambda = (x1 / rx).powi(2) + (y1 / ry).powi(2);
+        if lambda > 1.0 {
+            rx *= lambda.sqrt();
+            ry *= lambda.sqrt();
+        }
+        let num = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+        let den = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+        let sign = if large == sweep { -1.0 } else { 1.0 };
+        let coef = sign * (num / den).max(0.0).sqrt();
+        let (cx1, cy1) = (coef * rx * y1 / ry, -coef * ry * x1 / rx);
+
+        let angle = |ux: f64, uy: f64| uy.atan2(ux);
+        let theta = angle((x1 - cx1) / rx, (y1 - cy1) / ry);
+        let mut delta = angle((-x1 - cx1) / rx, (-y1 - cy1) / ry) - theta;
+        delta = delta.rem_euclid(2.0 * PI);
+        if !sweep && delta > 0.0 {
+            delta -= 2.0 * PI;
+        }
+
+        if (rx - ry).abs() <= 1e-9 * rx.max(ry) {
+            self.push(a, b, (delta / 4.0).tan());
+            return;
+        }
+        let (cx, cy) = (
+            cos * cx1 - sin * cy1 + (a[0] + b[0]) / 2.0,
+            sin * cx1 + cos * cy1 + (a[1] + b[1]) / 2.0,
+        );
+        self.curve(|t| {
+            if t == 1.0 {
+                return b;
+            }
+            let (st, ct) = (theta + delta * t).sin_cos();
+            [
+                cx + rx * cos * ct - ry * sin * st,
+                cy + rx * sin * ct + ry * cos * st,
+            ]
+        });
+    }
+
+    fn finish_closed(&mut self) {
+        let segments = std::mem::take(&mut self.current);
+        // Fewer than three straight sides enclose nothing.
+        if segments.len() < 3 && segments.iter().all(|s| s.bulge == 0.0) {
+            return;
+        }
+        let mut pl = Polyline::new_closed();
+        for s in segments {
+            pl.add_vertex(PlineVertex::new(s.a.x, s.a.y, s.bulge));
+        }
+        self.closed.push(pl);
+    }
+
+    fn finish_open(&mut self) {
+        self.loose.append(&mut self.current);
+    }
+}
+
+fn reflect(control: Option<[f64; 2]>, about: [f64; 2]) -> [f64; 2] {
+    control.map_or(about, |c| [2.0 * about[0] - c[0], 2.0 * about[1] - c[1]])
+}
+
+fn cubic_at(p0: [f64; 2], p1: [f64; 2], p2: [f64; 2], p3: [f64; 2], t: f64) -> [f64; 2] {
+    let u = 1.0 - t;
+    let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+    [
+        a * p0[0] + b * p1[0] + c * p2[0] + d * p3[0],
+        a * p0[1] + b * p1[1] + c * p2[1] + d * p3[1],
+    ]
+}
+
+fn quad_at(p0: [f64; 2], p1: [f64; 2], p2: [f64; 2], t: f64) -> [f64; 2] {
+    let u = 1.0 - t;
+    let (a, b, c) = (u * u, 2.0 * u * t, t * t);
+    [
+        a * p0[0] + b * p1[0] + c * p2[0],
+        a * p0[1] + b * p1[1] + c * p2[1],
+    ]
+}
+
+/// Tokenizer for SVG path data.
+struct PathData<'a> {
+    s: &'a [u8],
+    i: usize,
+}
+
+impl PathData<'_> {
+    fn skip_separators(&mut self) {
+        while self
+            .s
+            .get(self.i)
+            .is_some_and(|c| c.is_ascii_whitespace() || *c == b',')
+        {
+            self.i += 1;
+        }
+    }
+
+    fn at_number(&mut self) -> bool {
+        self.skip_separators();
+        self.s
+            .get(self.i)
+            .is_some_and(|c| c.is_ascii_digit() || matches!(c, b'-' | b'+' | b'.'))
+    }
+
+    fn command(&mut self) -> Result<Option<u8>, String> {
+        self.skip_separators();
+        match self.s.get(self.i) {
+            None => Ok(None),
+            Some(c) if c.is_ascii_alphabetic() => {
+                self.i += 1;
+                Ok(Some(*c))
+            }
+            Some(_) => Err(format!("expected a path command at offset {}", self.i)),
+        }
+    }
+
+    fn number(&mut self) -> Result<f64, String> {
+        self.skip_separators();
+        let start = self.i;
+        let mut end = start;
+        if matches!(self.s.get(end), Some(b'-' | b'+')) {
+            end += 1;
+        }
+        let mut seen_dot = false;
+        while let Some(&c) = self.s.get(end) {
+            if c.is_ascii_digit() {
+                end += 1;
+            } else if c == b'.' && !seen_dot {
+                seen_dot = true;
+                end += 1;
+            } else if matches!(c, b'e' | b'E') {
+                end += 1;
+                if matches!(self.s.get(end), Some(b'-' | b'+')) {
+                    end += 1;
+                }
+                while self.s.get(end).is_some_and(u8::is_ascii_digit) {
+                    end += 1;
+                }
+                break;
+            } else {
+                break;
+            }
+        }
+        let raw = std::str::from_utf8(&self.s[start..end]).unwrap_or_default();
+        let n = raw
+            .parse()
+            .map_err(|_| format!("expected a number at offset {start}"))?;
+        self.i = end;
+        Ok(n)
+    }
+
+    /// Arc flags may be written without separators (`a5 5 0 1010 10`).
+    fn flag(&mut self) -> Result<bool, String> {
+        self.skip_separators();
+        let flag = match self.s.get(self.i) {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(format!("expected an arc flag at offset {}", self.i)),
+        };
+        self.i += 1;
+        Ok(flag)
+    }
+}
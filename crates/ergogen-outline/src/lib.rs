@@ -1,18 +1,40 @@
 //! Outline generation logic.
 
 use indexmap::IndexMap;
-use regex::Regex;
 use std::collections::HashSet;
 
-use cavalier_contours::polyline::{PlineOffsetOptions, PlineOrientation, PlineSource};
+use cavalier_contours::core::math::Vector2;
+use cavalier_contours::polyline::{
+    PlineOffsetOptions, PlineOrientation, PlineSource, PlineSourceMut, seg_arc_radius_and_center,
+    seg_tangent_vector,
+};
 use ergogen_core::{Point, PointMeta};
 use ergogen_geometry::region::Region;
 use ergogen_geometry::{BooleanOp, Polyline, primitives};
-use ergogen_layout::{PointsOutput, anchor, parse_points};
+use ergogen_layout::anchor::{self, OutlineCentroid};
+use ergogen_layout::{Asymmetry, PointsOutput, filter};
 use ergogen_parser::{Error as ParserError, PreparedConfig, Value};
 
+use context::RegionCache;
+use corners::CornerOp;
+use hull::HullAlgorithm;
+
+pub use context::OutlineContext;
+
+pub mod check;
+pub mod compensation;
+pub mod context;
+mod corners;
+mod cutouts;
+mod hull;
 mod hulljs;
+mod import;
 mod makerjs_path;
+pub mod measure;
+mod pattern;
+pub mod sample;
+mod spline;
+mod text;
 
 #[derive(Debug, thiserror::Error)]
 pub enum OutlineError {
@@ -26,13 +48,28 @@ pub enum OutlineError {
     Unsupported(&'static str),
     #[error("path error: {0}")]
     Path(#[from] makerjs_path::MakerJsPathError),
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Asym {
-    Both,
-    Source,
-    Clone,
+    #[error("invalid outline sampling at \"{at}\": {message}")]
+    InvalidSample { at: String, message: String },
+    #[error("invalid corner selection at \"{at}\": {message}")]
+    InvalidCorners { at: String, message: String },
+    #[error("invalid text at \"{at}\": {message}")]
+    InvalidText { at: String, message: String },
+    #[error("invalid import at \"{at}\": {message}")]
+    InvalidImport { at: String, message: String },
+    #[error("invalid cutout at \"{at}\": {message}")]
+    InvalidCutout { at: String, message: String },
+    #[error("invalid pattern at \"{at}\": {message}")]
+    InvalidPattern { at: String, message: String },
+    #[error("invalid spline at \"{at}\": {message}")]
+    InvalidSpline { at: String, message: String },
+    #[error("invalid hull at \"{at}\": {message}")]
+    InvalidHull { at: String, message: String },
+    #[error("invalid export options at \"{at}\": {message}")]
+    InvalidExport { at: String, message: String },
+    #[error("unknown outline measurement \"{name}\"")]
+    UnknownMeasurement { name: String },
+    #[error("{message}")]
+    FailedMeasurement { name: String, message: String },
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -42,6 +79,8 @@ struct Placement {
     r: f64,
     mirrored: bool,
     bind_trbl: [f64; 4],
+    /// Width of the selected key, when the placement is one.
+    key_width: Option<f64>,
 }
 
 /// Minimal outline generator for the simplest upstream fixtures.
@@ -64,24 +103,48 @@ pub fn generate_outline_region_from_yaml_str(
 pub fn generate_outline_region(
     prepared: &PreparedConfig,
     outline_name: &str,
+) -> Result<Region, OutlineError> {
+    OutlineContext::new(prepared).region(outline_name)
+}
+
+pub(crate) fn generate_outline_region_with_points(
+    prepared: &PreparedConfig,
+    outline_name: &str,
+    points: &PointsOutput,
+    regions: &RegionCache,
 ) -> Result<Region, OutlineError> {
     let mut visiting = HashSet::<String>::new();
-    generate_outline_region_inner(prepared, outline_name, &mut visiting)
+    generate_outline_region_inner(prepared, outline_name, points, &mut visiting, regions)
 }
 
 fn generate_outline_region_inner(
     prepared: &PreparedConfig,
     outline_name: &str,
+    points: &PointsOutput,
     visiting: &mut HashSet<String>,
+    regions: &RegionCache,
 ) -> Result<Region, OutlineError> {
+    if let Some(region) = regions.borrow().get(outline_name) {
+        return Ok(region.clone());
+    }
     if !visiting.insert(outline_name.to_string()) {
         return Err(OutlineError::OutlineCycle {
             name: outline_name.to_string(),
         });
     }
 
-    let points = parse_points(&prepared.canonical, &prepared.units)?;
-    let ref_points = points_to_ref(&points);
+    let ref_points = points_to_ref(points);
+    // Anchors may aggregate to another outline's centroid; that outline sees the current chain
+    // of outlines being generated, so self references still end up as cycles.
+    let chain = visiting.clone();
+    let centroid = |name: &str| -> Result<[f64; 2], String> {
+        let region =
+            generate_outline_region_inner(prepared, name, points, &mut chain.clone(), regions)
+                .map_err(|e| e.to_string())?;
+        region
+            .centroid()
+            .ok_or_else(|| format!("outline \"{name}\" is empty"))
+    };
 
     let outline = prepared
         .canonical
@@ -109,7 +172,8 @@ fn generate_outline_region_inner(
             Value::Map(obj) => Some(obj),
             Value::String(s) => {
                 let (op, name) = parse_outline_ref(s);
-                let referenced = generate_outline_region_inner(prepared, name, visiting)?;
+                let referenced =
+                    generate_outline_region_inner(prepared, name, points, visiting, regions)?;
 
                 match op {
                     OutlineRefOp::Subtract => {
@@ -139,6 +203,9 @@ fn generate_outline_region_inner(
                             &mut carry_neg,
                         );
                     }
+                    OutlineRefOp::Xor => {
+                        apply_region_op(&mut region, "xor", referenced, &mut stack, &mut carry_neg);
+                    }
                     OutlineRefOp::Add => {
                         apply_region_op(&mut region, "add", referenced, &mut stack, &mut carry_neg);
                     }
@@ -178,9 +245,10 @@ fn generate_outline_region_inner(
                 return Err(OutlineError::Unsupported("name must be a string"));
             };
 
-            let mut referenced = generate_outline_region_inner(prepared, name, visiting)?;
+            let mut referenced =
+                generate_outline_region_inner(prepared, name, points, visiting, regions)?;
 
-            // MakerJS outlines.js applies: scale -> expand -> fillet (per-part).
+            // MakerJS outlines.js applies: scale -> expand -> fillet (per-part); chamfers come last.
             let scale = match obj.get("scale") {
                 None | Some(Value::Null) => 1.0,
                 Some(v) => eval_number(&prepared.units, v, "outlines.scale")?,
@@ -192,26 +260,38 @@ fn generate_outline_region_inner(
             if obj.contains_key("expand") {
                 let (amount, joints) =
                     parse_expand_spec(obj.get("expand"), obj.get("joints"), &prepared.units)?;
-                referenced = if amount == 0.0 {
-                    referenced
-                } else if referenced.pos.len() == 1
-                    && referenced.neg.is_empty()
-                    && try_rectangle_params(&referenced.pos[0]).is_some()
-                {
-                    // Preserve our existing "rectangle-only" behavior for fixtures that validate
-                    // pointy/beveled joints.
-                    expand_region_rect_only(&referenced, amount, joints)?
-                } else {
-                    expand_region_round(&referenced, amount)?
-                };
+                referenced = expand_region(&referenced, amount, joints)?;
             }
 
-            let fillet = match obj.get("fillet") {
-                None | Some(Value::Null) => 0.0,
-                Some(v) => eval_number(&prepared.units, v, "outlines.fillet")?,
-            };
-            if fillet != 0.0 {
-                referenced = fillet_region_round(&referenced, fillet)?;
+            match obj.get("fillet") {
+                None | Some(Value::Null) => {}
+                Some(v @ Value::Map(_)) => {
+                    let spec = corners::parse_corner_spec(
+                        CornerOp::Fillet,
+                        v,
+                        "outlines.fillet",
+                        &prepared.units,
+                        &ref_points,
+                    )?;
+                    referenced = corners::apply_corner_spec(&referenced, &spec);
+                }
+                Some(v) => {
+                    let fillet = eval_number(&prepared.units, v, "outlines.fillet")?;
+                    if fillet != 0.0 {
+                        referenced = fillet_region_round(&referenced, fillet)?;
+                    }
+                }
+            }
+
+            if let Some(v) = obj.get("chamfer").filter(|v| !matches!(v, Value::Null)) {
+                let spec = corners::parse_corner_spec(
+                    CornerOp::Chamfer,
+                    v,
+                    "outlines.chamfer",
+                    &prepared.units,
+                    &ref_points,
+                )?;
+                referenced = corners::apply_corner_spec(&referenced, &spec);
             }
 
             apply_region_op(&mut region, op, referenced, &mut stack, &mut carry_neg);
@@ -269,9 +349,15 @@ fn generate_outline_region_inner(
                     .units
                     .with_extra_vars([("sx".to_string(), w), ("sy".to_string(), h)]);
 
-                let placements = placements_for_where(where_v, asym, &points, &ref_points, &units)?;
+                let placements = placements_for_where(where_v, asym, points, &ref_points, &units)?;
                 for p in placements {
-                    let p = apply_adjust_if_present(obj.get("adjust"), p, &ref_points, &units)?;
+                    let p = apply_adjust_if_present(
+                        obj.get("adjust"),
+                        p,
+                        &ref_points,
+                        &units,
+                        &centroid,
+                    )?;
                     let (cx, cy, w, h) = if bound {
                         apply_bind_to_centered_rect((p.x, p.y), (w, h), p.bind_trbl, p.r)
                     } else {
@@ -307,9 +393,15 @@ fn generate_outline_region_inner(
                     ("sy".to_string(), radius * 2.0),
                 ]);
 
-                let placements = placements_for_where(where_v, asym, &points, &ref_points, &units)?;
+                let placements = placements_for_where(where_v, asym, points, &ref_points, &units)?;
                 for p in placements {
-                    let p = apply_adjust_if_present(obj.get("adjust"), p, &ref_points, &units)?;
+                    let p = apply_adjust_if_present(
+                        obj.get("adjust"),
+                        p,
+                        &ref_points,
+                        &units,
+                        &centroid,
+                    )?;
                     let c = primitives::circle((p.x, p.y), radius);
                     apply_region_op(
                         &mut region,
@@ -320,6 +412,107 @@ fn generate_outline_region_inner(
                     );
                 }
             }
+            "text" => {
+                let spec = text::parse_text_spec(obj, &prepared.units)?;
+                let placements =
+                    placements_for_where(where_v, asym, points, &ref_points, &prepared.units)?;
+                for p in placements {
+                    let p = apply_adjust_if_present(
+                        obj.get("adjust"),
+                        p,
+                        &ref_points,
+                        &prepared.units,
+                        &centroid,
+                    )?;
+                    apply_region_op(
+                        &mut region,
+                        op,
+                        text::text_region(&spec, p.x, p.y, p.r)?,
+                        &mut stack,
+                        &mut carry_neg,
+                    );
+                }
+            }
+            "pattern" => {
+                let spec = pattern::parse_pattern_spec(obj, &prepared.units)?;
+                let area =
+                    generate_outline_region_inner(prepared, &spec.name, points, visiting, regions)?;
+                let keepouts = spec
+                    .keepout
+                    .iter()
+                    .map(|name| {
+                        generate_outline_region_inner(prepared, name, points, visiting, regions)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                apply_region_op(
+                    &mut region,
+                    op,
+                    pattern::fill(&spec, &area, &keepouts)?,
+                    &mut stack,
+                    &mut carry_neg,
+                );
+            }
+            "switch_cutout" => {
+                let spec = cutouts::parse_switch_spec(obj, &prepared.units)?;
+                let placements =
+                    placements_for_where(where_v, asym, points, &ref_points, &prepared.units)?;
+                for p in placements {
+                    let p = apply_adjust_if_present(
+                        obj.get("adjust"),
+                        p,
+                        &ref_points,
+                        &prepared.units,
+                        &centroid,
+                    )?;
+                    apply_region_op(
+                        &mut region,
+                        op,
+                        cutouts::switch_region(&spec, p.x, p.y, p.r),
+                        &mut stack,
+                        &mut carry_neg,
+                    );
+                }
+            }
+            "stabilizer_cutout" => {
+                let spec = cutouts::parse_stabilizer_spec(obj, &prepared.units)?;
+                let default_width = prepared.units.get("$default_width").unwrap_or(18.0);
+                let placements =
+                    placements_for_where(where_v, asym, points, &ref_points, &prepared.units)?;
+                for p in placements {
+                    let p = apply_adjust_if_present(
+                        obj.get("adjust"),
+                        p,
+                        &ref_points,
+                        &prepared.units,
+                        &centroid,
+                    )?;
+                    let width = p.key_width.unwrap_or(default_width);
+                    if let Some(stabs) = cutouts::stabilizer_region(&spec, width, p.x, p.y, p.r)? {
+                        apply_region_op(&mut region, op, stabs, &mut stack, &mut carry_neg);
+                    }
+                }
+            }
+            "import" => {
+                let spec = import::parse_import_spec(obj, &prepared.units)?;
+                let placements =
+                    placements_for_where(where_v, asym, points, &ref_points, &prepared.units)?;
+                for p in placements {
+                    let p = apply_adjust_if_present(
+                        obj.get("adjust"),
+                        p,
+                        &ref_points,
+                        &prepared.units,
+                        &centroid,
+                    )?;
+                    apply_region_op(
+                        &mut region,
+                        op,
+                        import::import_region(&spec, p.x, p.y, p.r, p.mirrored),
+                        &mut stack,
+                        &mut carry_neg,
+                    );
+                }
+            }
             "polygon" => {
                 let points_v = obj
                     .get("points")
@@ -329,7 +522,7 @@ fn generate_outline_region_inner(
                 };
 
                 let placements =
-                    placements_for_where(where_v, asym, &points, &ref_points, &prepared.units)?;
+                    placements_for_where(where_v, asym, points, &ref_points, &prepared.units)?;
 
                 for p in placements {
                     let p = apply_adjust_if_present(
@@ -337,6 +530,7 @@ fn generate_outline_region_inner(
                         p,
                         &ref_points,
                         &prepared.units,
+                        &centroid,
                     )?;
                     let mut current = Point::new(
                         p.x,
@@ -349,13 +543,14 @@ fn generate_outline_region_inner(
                     let mut vertices: Vec<(f64, f64)> = Vec::with_capacity(steps.len());
 
                     for (idx, step) in steps.iter().enumerate() {
-                        current = anchor::parse_anchor(
+                        current = anchor::parse_anchor_with_outlines(
                             step,
                             &format!("outlines.points[{}]", idx + 1),
                             &ref_points,
                             current,
                             &prepared.units,
                             false,
+                            &centroid,
                         )?;
                         vertices.push((current.x, current.y));
                     }
@@ -375,6 +570,7 @@ fn generate_outline_region_inner(
                     None | Some(Value::Null) => 50.0,
                     Some(v) => eval_number(&prepared.units, v, "outlines.concavity")?,
                 };
+                let algorithm = hull::parse_algorithm(obj, &prepared.units)?;
                 // Upstream defaults `extend` to true when missing.
                 let extend = match obj.get("extend") {
                     None | Some(Value::Null) => true,
@@ -389,7 +585,7 @@ fn generate_outline_region_inner(
                 };
 
                 let placements =
-                    placements_for_where(where_v, asym, &points, &ref_points, &prepared.units)?;
+                    placements_for_where(where_v, asym, points, &ref_points, &prepared.units)?;
 
                 for p in placements {
                     let p = apply_adjust_if_present(
@@ -397,6 +593,7 @@ fn generate_outline_region_inner(
                         p,
                         &ref_points,
                         &prepared.units,
+                        &centroid,
                     )?;
 
                     let mut samples: Vec<[f64; 2]> = Vec::new();
@@ -417,10 +614,11 @@ fn generate_outline_region_inner(
                         last = parse_anchor_with_key_meta(
                             hp,
                             &format!("outlines.hull.points[{}]", idx + 1),
-                            &points,
+                            points,
                             &ref_points,
                             last,
                             &prepared.units,
+                            &centroid,
                         )?;
 
                         if !extend {
@@ -470,8 +668,16 @@ fn generate_outline_region_inner(
                         let _ = std::fs::write(path, out);
                     }
 
-                    let hull = hulljs::hull(samples, concavity);
-                    if std::env::var_os("ERGOGEN_DUMP_HULL_RAW").is_some() {
+                    let (islands, holes) = match algorithm {
+                        HullAlgorithm::Convex => (vec![hulljs::convex(samples)], Vec::new()),
+                        HullAlgorithm::Alpha { alpha } => hull::alpha_shape(&samples, alpha),
+                        HullAlgorithm::Concave | HullAlgorithm::Rounded { .. } => {
+                            (vec![hulljs::hull(samples, concavity)], Vec::new())
+                        }
+                    };
+                    if let [hull] = islands.as_slice()
+                        && std::env::var_os("ERGOGEN_DUMP_HULL_RAW").is_some()
+                    {
                         let dump_dir = std::env::temp_dir().join("ergogen-hull-dumps");
                         let _ = std::fs::create_dir_all(&dump_dir);
                         let mut fname =
@@ -479,26 +685,18 @@ fn generate_outline_region_inner(
                         fname = fname.replace(['/', '\\', ' '], "_");
                         let path = dump_dir.join(format!("{fname}.hull_raw.txt"));
                         let mut out = String::new();
-                        for p in &hull {
+                        for p in hull {
                             out.push_str(&format!("{},{}\n", p[0], p[1]));
                         }
                         let _ = std::fs::write(path, out);
                     }
-                    let hull = simplify_closed_ring_points(hull);
 
-                    let vertices: Vec<(f64, f64)> = hull
-                        .into_iter()
-                        .map(|v| position_xy((v[0], v[1]), p))
-                        .collect();
-
-                    let poly = primitives::polygon(&vertices);
-                    apply_region_op(
-                        &mut region,
-                        op,
-                        Region::from_pos(vec![poly]),
-                        &mut stack,
-                        &mut carry_neg,
-                    );
+                    let mut part =
+                        hull::rings_region(islands, holes, |v| position_xy((v[0], v[1]), p));
+                    if let HullAlgorithm::Rounded { radius } = algorithm {
+                        part = hull::round(&part, radius)?;
+                    }
+                    apply_region_op(&mut region, op, part, &mut stack, &mut carry_neg);
                 }
             }
             "path" => {
@@ -510,7 +708,7 @@ fn generate_outline_region_inner(
                 };
 
                 let placements =
-                    placements_for_where(where_v, asym, &points, &ref_points, &prepared.units)?;
+                    placements_for_where(where_v, asym, points, &ref_points, &prepared.units)?;
 
                 for p in placements {
                     let p = apply_adjust_if_present(
@@ -518,6 +716,7 @@ fn generate_outline_region_inner(
                         p,
                         &ref_points,
                         &prepared.units,
+                        &centroid,
                     )?;
 
                     let mut first_anchor: Option<Point> = None;
@@ -554,13 +753,14 @@ fn generate_outline_region_inner(
                         }
 
                         for (idx, sp) in seg_points.iter().enumerate() {
-                            last_anchor = anchor::parse_anchor(
+                            last_anchor = anchor::parse_anchor_with_outlines(
                                 sp,
                                 &format!("outlines.path.segments.{seg_index}.points[{idx}]"),
                                 &ref_points,
                                 last_anchor,
                                 &prepared.units,
                                 false,
+                                &centroid,
                             )?;
                             if first_anchor.is_none() {
                                 first_anchor = Some(last_anchor.clone());
@@ -619,6 +819,28 @@ fn generate_outline_region_inner(
                                 let segs = makerjs_path::bezier_curve_primitives(seed, None);
                                 prims.extend(segs);
                             }
+                            "catmull_rom" | "natural_spline" | "b_spline" => {
+                                let at = format!("outlines.path.segments.{seg_index}");
+                                if parsed_points.len() < 2 {
+                                    return Err(OutlineError::InvalidSpline {
+                                        at: format!("{at}.points"),
+                                        message: "spline segments require at least 2 points"
+                                            .to_string(),
+                                    });
+                                }
+                                let kind = match seg_type.as_str() {
+                                    "catmull_rom" => spline::SplineKind::CatmullRom,
+                                    "natural_spline" => spline::SplineKind::Natural,
+                                    _ => spline::SplineKind::BSpline,
+                                };
+                                let opts =
+                                    spline::parse_spline_options(seg_obj, &prepared.units, &at)?;
+                                prims.extend(spline::spline_primitives(
+                                    kind,
+                                    &parsed_points,
+                                    &opts,
+                                ));
+                            }
                             _ => {
                                 return Err(OutlineError::Unsupported(
                                     "unsupported path segment type",
@@ -707,6 +929,9 @@ fn generate_outline_region_inner(
     region.neg.extend(carry_neg);
 
     visiting.remove(outline_name);
+    regions
+        .borrow_mut()
+        .insert(outline_name.to_string(), region.clone());
     Ok(region)
 }
 
@@ -716,8 +941,11 @@ enum OutlineRefOp {
     Subtract,
     Intersect,
     Stack,
+    Xor,
 }
 
+/// Splits the operation prefix off an outline reference: `+` add (the default), `-` subtract,
+/// `~` intersect, `^` stack and `/` xor.
 fn parse_outline_ref(raw: &str) -> (OutlineRefOp, &str) {
     if let Some(rest) = raw.strip_prefix('-') {
         return (OutlineRefOp::Subtract, rest);
@@ -731,6 +959,9 @@ fn parse_outline_ref(raw: &str) -> (OutlineRefOp, &str) {
     if let Some(rest) = raw.strip_prefix('^') {
         return (OutlineRefOp::Stack, rest);
     }
+    if let Some(rest) = raw.strip_prefix('/') {
+        return (OutlineRefOp::Xor, rest);
+    }
     (OutlineRefOp::Add, raw)
 }
 
@@ -753,6 +984,7 @@ fn apply_region_op(
             }
             *region = intersect_region(region, &part);
         }
+        "xor" => *region = xor_region(region, &part),
         _ => apply_add_region(region, part),
     }
 }
@@ -811,6 +1043,23 @@ fn apply_sub_region(region: &mut Region, part: Region) {
     }
 }
 
+/// What only `a` covers and what only `b` covers: `(a - b, b - a)`. Comparing two revisions of an
+/// outline this way shows what was removed and what was added.
+pub fn difference_both(a: &Region, b: &Region) -> (Region, Region) {
+    let mut a_only = a.clone();
+    apply_sub_region(&mut a_only, b.clone());
+    let mut b_only = b.clone();
+    apply_sub_region(&mut b_only, a.clone());
+    (a_only, b_only)
+}
+
+/// Symmetric difference: everything covered by exactly one of `a` and `b`.
+fn xor_region(a: &Region, b: &Region) -> Region {
+    let (mut out, b_only) = difference_both(a, b);
+    apply_add_region(&mut out, b_only);
+    out
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ExpandJoints {
     Beveled,
@@ -818,13 +1067,6 @@ enum ExpandJoints {
     Pointy,
 }
 
-#[derive(Debug, Clone, Copy)]
-struct RectParams {
-    center: (f64, f64),
-    size: (f64, f64),
-    rotation_deg: f64,
-}
-
 fn parse_expand_spec(
     expand: Option<&Value>,
     joints: Option<&Value>,
@@ -886,47 +1128,6 @@ fn parse_expand_shorthand(
     Ok(Some((amount, joints)))
 }
 
-fn expand_region_rect_only(
-    region: &Region,
-    amount: f64,
-    joints: ExpandJoints,
-) -> Result<Region, OutlineError> {
-    if amount == 0.0 {
-        return Ok(region.clone());
-    }
-    if region.pos.len() != 1 || !region.neg.is_empty() {
-        return Err(OutlineError::Unsupported(
-            "expand only supports single-rectangle regions for now",
-        ));
-    }
-
-    let pl = &region.pos[0];
-    let Some(rect) = try_rectangle_params(pl) else {
-        return Err(OutlineError::Unsupported(
-            "expand only supports rectangle polylines for now",
-        ));
-    };
-
-    let size = (rect.size.0 + 2.0 * amount, rect.size.1 + 2.0 * amount);
-    let out = match joints {
-        ExpandJoints::Pointy => primitives::rectangle(rect.center, size, rect.rotation_deg),
-        ExpandJoints::Round => {
-            primitives::rounded_rectangle(rect.center, size, amount, rect.rotation_deg)
-        }
-        ExpandJoints::Beveled => {
-            // MakerJS-style bevel for a round-cap expansion: the cut distance along the expanded
-            // edges is smaller than `amount`.
-            let cut = amount * (2.0 - std::f64::consts::SQRT_2);
-            // Match upstream's decimal rounding so DXF semantic compare (1e-6 quantization) lands
-            // on the same side of half-way cases.
-            let cut = (cut / 1e-7).round() * 1e-7;
-            primitives::beveled_rectangle(rect.center, size, cut, rect.rotation_deg)
-        }
-    };
-
-    Ok(Region::from_pos(vec![out]))
-}
-
 fn scale_region(region: &Region, scale: f64) -> Region {
     if scale == 1.0 {
         return region.clone();
@@ -1020,52 +1221,116 @@ fn expand_region_round(region: &Region, expand: f64) -> Result<Region, OutlineEr
     Ok(out)
 }
 
-fn fillet_region_round(region: &Region, radius: f64) -> Result<Region, OutlineError> {
-    let r = radius.abs();
-    if r == 0.0 {
-        return Ok(region.clone());
+/// Offsets every boundary of `region` by `expand` (negative shrinks), with the given joints where
+/// the offset turns around a corner.
+///
+/// The offset itself always produces round joints: an arc of radius `|expand|` around the corner.
+/// Pointy and beveled joints replace those arcs afterwards, which keeps holes, islands and arcs of
+/// the original outline intact.
+fn expand_region(
+    region: &Region,
+    expand: f64,
+    joints: ExpandJoints,
+) -> Result<Region, OutlineError> {
+    let rounded = expand_region_round(region, expand)?;
+    if expand == 0.0 || joints == ExpandJoints::Round {
+        return Ok(rounded);
     }
-    // Approximate MakerJS `chain.fillet` via a morphological opening:
-    // inset by r, then offset back out by r (round joins).
-    let inset = expand_region_round(region, -r)?;
-    expand_region_round(&inset, r)
-}
 
-fn try_rectangle_params(pl: &Polyline<f64>) -> Option<RectParams> {
-    if !pl.is_closed || pl.vertex_data.len() != 4 {
-        return None;
-    }
-    for i in 0..4 {
-        if !pl.vertex_data[i].bulge_is_zero() {
-            return None;
-        }
+    let corners: Vec<Vector2<f64>> = region
+        .pos
+        .iter()
+        .chain(&region.neg)
+        .flat_map(|pl| pl.vertex_data.iter().map(|v| v.pos()))
+        .collect();
+    let sharpen = |pl: &Polyline<f64>| sharpen_joints(pl, expand.abs(), &corners, joints);
+
+    // Sharpened joints can reach into neighbouring islands, so merge again.
+    let mut out = Region::union_all(rounded.pos.iter().map(sharpen).collect());
+    let neg: Vec<Polyline<f64>> = rounded.neg.iter().map(sharpen).collect();
+    if !neg.is_empty() {
+        out.subtract_all(&neg);
     }
+    Ok(out)
+}
 
-    let v0 = pl.vertex_data[0];
-    let v1 = pl.vertex_data[1];
-    let v2 = pl.vertex_data[2];
-    let v3 = pl.vertex_data[3];
-
-    let cx = (v0.x + v1.x + v2.x + v3.x) / 4.0;
-    let cy = (v0.y + v1.y + v2.y + v3.y) / 4.0;
+/// Replaces the round joints of an offset polyline (arcs of radius `distance` centred on one of
+/// the original `corners`) with miters or bevels.
+fn sharpen_joints(
+    pl: &Polyline<f64>,
+    distance: f64,
+    corners: &[Vector2<f64>],
+    joints: ExpandJoints,
+) -> Polyline<f64> {
+    let eps = 1e-6 * distance.max(1.0);
+    let n = pl.vertex_count();
+    let mut out = Polyline::new_closed();
+    for i in 0..n {
+        let (v1, v2) = (pl.at(i), pl.at((i + 1) % n));
+        if v1.bulge_is_zero() {
+            out.add_vertex(v1);
+            continue;
+        }
+        let (radius, center) = seg_arc_radius_and_center(v1, v2);
+        let is_joint =
+            (radius - distance).abs() < eps && corners.iter().any(|c| (*c - center).length() < eps);
+        if !is_joint {
+            out.add_vertex(v1);
+            continue;
+        }
 
-    let dx01 = v1.x - v0.x;
-    let dy01 = v1.y - v0.y;
-    let dx12 = v2.x - v1.x;
-    let dy12 = v2.y - v1.y;
+        let (a, b) = (v1.pos(), v2.pos());
+        let ta = seg_tangent_vector(v1, v2, a).normalize();
+        let tb = seg_tangent_vector(v1, v2, b).normalize();
+        out.add(a.x, a.y, 0.0);
+        let miter = match joints {
+            ExpandJoints::Pointy => line_intersection(a, ta, b, tb),
+            _ => None,
+        };
+        if let Some(m) = miter {
+            out.add(m.x, m.y, 0.0);
+            continue;
+        }
+        // Bevel (or a miter too sharp to meet): cut along the tangent at the middle of the arc,
+        // like MakerJS does.
+        let mid = center + ((a - center) + (b - center)).normalize().scale(radius);
+        let tm = seg_tangent_vector(v1, v2, mid).normalize();
+        for p in [
+            line_intersection(a, ta, mid, tm),
+            line_intersection(mid, tm, b, tb),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            out.add(p.x, p.y, 0.0);
+        }
+    }
+    out.remove_redundant(1e-6).unwrap_or(out)
+}
 
-    let w = (dx01 * dx01 + dy01 * dy01).sqrt();
-    let h = (dx12 * dx12 + dy12 * dy12).sqrt();
-    if w == 0.0 || h == 0.0 {
+fn line_intersection(
+    p: Vector2<f64>,
+    dp: Vector2<f64>,
+    q: Vector2<f64>,
+    dq: Vector2<f64>,
+) -> Option<Vector2<f64>> {
+    let denom = dp.perp_dot(dq);
+    if denom.abs() < 1e-9 {
         return None;
     }
+    let t = (q - p).perp_dot(dq) / denom;
+    Some(p + dp.scale(t))
+}
 
-    let rotation_deg = dy01.atan2(dx01).to_degrees();
-    Some(RectParams {
-        center: (cx, cy),
-        size: (w, h),
-        rotation_deg,
-    })
+fn fillet_region_round(region: &Region, radius: f64) -> Result<Region, OutlineError> {
+    let r = radius.abs();
+    if r == 0.0 {
+        return Ok(region.clone());
+    }
+    // Approximate MakerJS `chain.fillet` via a morphological opening:
+    // inset by r, then offset back out by r (round joins).
+    let inset = expand_region_round(region, -r)?;
+    expand_region_round(&inset, r)
 }
 
 fn apply_adjust_if_present(
@@ -1073,6 +1338,7 @@ fn apply_adjust_if_present(
     p: Placement,
     ref_points: &IndexMap<String, Point>,
     units: &ergogen_parser::Units,
+    outlines: &OutlineCentroid<'_>,
 ) -> Result<Placement, OutlineError> {
     let Some(adjust) = adjust else {
         return Ok(p);
@@ -1089,14 +1355,22 @@ fn apply_adjust_if_present(
             mirrored: p.mirrored,
         },
     );
-    let adjusted =
-        anchor::parse_anchor(adjust, "outlines.adjust", ref_points, start, units, false)?;
+    let adjusted = anchor::parse_anchor_with_outlines(
+        adjust,
+        "outlines.adjust",
+        ref_points,
+        start,
+        units,
+        false,
+        outlines,
+    )?;
     Ok(Placement {
         x: adjusted.x,
         y: adjusted.y,
         r: adjusted.r,
         mirrored: p.mirrored,
         bind_trbl: p.bind_trbl,
+        key_width: p.key_width,
     })
 }
 
@@ -1128,18 +1402,18 @@ fn eval_number(units: &ergogen_parser::Units, v: &Value, at: &str) -> Result<f64
     }
 }
 
-fn parse_asym(v: Option<&Value>, where_v: &Value) -> Asym {
+fn parse_asym(v: Option<&Value>, where_v: &Value) -> Asymmetry {
     let default = if matches!(where_v, Value::Bool(true)) {
-        Asym::Both
+        Asymmetry::Both
     } else {
-        Asym::Source
+        Asymmetry::Source
     };
     let Some(v) = v else { return default };
     let Value::String(s) = v else { return default };
     match s.as_str() {
-        "both" => Asym::Both,
-        "source" | "origin" | "base" | "primary" | "left" => Asym::Source,
-        "clone" | "image" | "derived" | "secondary" | "right" => Asym::Clone,
+        "both" => Asymmetry::Both,
+        "source" | "origin" | "base" | "primary" | "left" => Asymmetry::Source,
+        "clone" | "image" | "derived" | "secondary" | "right" => Asymmetry::Clone,
         _ => default,
     }
 }
@@ -1177,8 +1451,17 @@ fn parse_anchor_with_key_meta(
     ref_points: &IndexMap<String, Point>,
     start: AnchorWithKeyMeta,
     units: &ergogen_parser::Units,
+    outlines: &OutlineCentroid<'_>,
 ) -> Result<AnchorWithKeyMeta, OutlineError> {
-    let point = anchor::parse_anchor(raw, at, ref_points, start.point.clone(), units, false)?;
+    let point = anchor::parse_anchor_with_outlines(
+        raw,
+        at,
+        ref_points,
+        start.point.clone(),
+        units,
+        false,
+        outlines,
+    )?;
 
     let mut width = start.width;
     let mut height = start.height;
@@ -1344,168 +1627,24 @@ fn position_xy(p: (f64, f64), placement: Placement) -> (f64, f64) {
 
 fn placements_for_where(
     where_v: &Value,
-    asym: Asym,
+    asym: Asymmetry,
     points: &PointsOutput,
     ref_points: &IndexMap<String, Point>,
     units: &ergogen_parser::Units,
 ) -> Result<Vec<Placement>, OutlineError> {
-    match where_v {
-        Value::Bool(true) => {
-            let mut out = Vec::new();
-            for p in points.values() {
-                let mirrored = p.meta.mirrored.unwrap_or(false);
-                if (asym == Asym::Source && mirrored) || (asym == Asym::Clone && !mirrored) {
-                    continue;
-                }
-                out.push(Placement {
-                    x: p.x,
-                    y: p.y,
-                    r: p.r,
-                    mirrored,
-                    bind_trbl: p.meta.bind,
-                });
-            }
-            Ok(out)
-        }
-        // Upstream `where` defaults to a single point at [0, 0].
-        Value::Null => Ok(vec![Placement {
-            x: 0.0,
-            y: 0.0,
-            r: 0.0,
-            mirrored: false,
-            bind_trbl: [0.0; 4],
-        }]),
-        Value::Bool(false) => Ok(Vec::new()),
-        Value::String(s) if looks_like_regex_literal(s) => {
-            let re =
-                parse_regex_literal(s).map_err(|_| OutlineError::Unsupported("invalid regex"))?;
-
-            let mut out = Vec::new();
-            for (name, p) in points.iter() {
-                if !re.is_match(name) && !p.meta.tags.iter().any(|t| re.is_match(t)) {
-                    continue;
-                }
-                let mirrored = p.meta.mirrored.unwrap_or(false);
-                if (asym == Asym::Source && mirrored) || (asym == Asym::Clone && !mirrored) {
-                    continue;
-                }
-                out.push(Placement {
-                    x: p.x,
-                    y: p.y,
-                    r: p.r,
-                    mirrored,
-                    bind_trbl: p.meta.bind,
-                });
-            }
-            Ok(out)
-        }
-        Value::Seq(seq) if seq.iter().all(|v| matches!(v, Value::String(_))) => {
-            let mut wanted: Vec<&str> = Vec::with_capacity(seq.len());
-            for v in seq {
-                let Value::String(s) = v else { continue };
-                wanted.push(s.as_str());
-            }
-            if wanted.is_empty() {
-                return Ok(Vec::new());
-            }
-
-            let mut out = Vec::new();
-            for p in points.values() {
-                let mirrored = p.meta.mirrored.unwrap_or(false);
-                if (asym == Asym::Source && mirrored) || (asym == Asym::Clone && !mirrored) {
-                    continue;
-                }
-                if !p.meta.tags.iter().any(|t| wanted.contains(&t.as_str())) {
-                    continue;
-                }
-                out.push(Placement {
-                    x: p.x,
-                    y: p.y,
-                    r: p.r,
-                    mirrored,
-                    bind_trbl: p.meta.bind,
-                });
-            }
-            Ok(out)
-        }
-        other => {
-            let start = Point::new(0.0, 0.0, 0.0, PointMeta::default());
-            let base = anchor::parse_anchor(
-                other,
-                "outlines.where",
-                ref_points,
-                start.clone(),
-                units,
-                false,
-            )?;
-
-            match asym {
-                Asym::Source => Ok(vec![Placement {
-                    x: base.x,
-                    y: base.y,
-                    r: base.r,
-                    mirrored: base.meta.mirrored,
-                    bind_trbl: [0.0; 4],
-                }]),
-                Asym::Clone => {
-                    let m = anchor::parse_anchor(
-                        other,
-                        "outlines.where",
-                        ref_points,
-                        start,
-                        units,
-                        true,
-                    )?;
-                    Ok(vec![Placement {
-                        x: m.x,
-                        y: m.y,
-                        r: m.r,
-                        mirrored: m.meta.mirrored,
-                        bind_trbl: [0.0; 4],
-                    }])
-                }
-                Asym::Both => {
-                    let m = anchor::parse_anchor(
-                        other,
-                        "outlines.where",
-                        ref_points,
-                        start,
-                        units,
-                        true,
-                    )?;
-                    if (base.x - m.x).abs() < 1e-9
-                        && (base.y - m.y).abs() < 1e-9
-                        && (base.r - m.r).abs() < 1e-9
-                    {
-                        Ok(vec![Placement {
-                            x: base.x,
-                            y: base.y,
-                            r: base.r,
-                            mirrored: base.meta.mirrored,
-                            bind_trbl: [0.0; 4],
-                        }])
-                    } else {
-                        Ok(vec![
-                            Placement {
-                                x: base.x,
-                                y: base.y,
-                                r: base.r,
-                                mirrored: base.meta.mirrored,
-                                bind_trbl: [0.0; 4],
-                            },
-                            Placement {
-                                x: m.x,
-                                y: m.y,
-                                r: m.r,
-                                mirrored: m.meta.mirrored,
-                                bind_trbl: [0.0; 4],
-                            },
-                        ])
-                    }
-                }
-            }
-        }
-    }
+    let selected =
+        filter::select_points(where_v, asym, points, ref_points, units, "outlines.where")?;
+    Ok(selected
+        .into_iter()
+        .map(|s| Placement {
+            x: s.x,
+            y: s.y,
+            r: s.r,
+            mirrored: s.mirrored,
+            bind_trbl: s.bind,
+            key_width: points.get(&s.name).map(|p| p.meta.width),
+        })
+        .collect())
 }
 
 fn apply_bind_to_centered_rect(
@@ -1540,33 +1679,6 @@ fn apply_bind_to_centered_rect(
     (cx + dx, cy + dy, w2, h2)
 }
 
-fn looks_like_regex_literal(s: &str) -> bool {
-    s.starts_with('/') && s.len() >= 2 && s[1..].contains('/')
-}
-
-fn parse_regex_literal(raw: &str) -> Result<Regex, ()> {
-    // JS-style: /pattern/flags (we only care about `i` today)
-    let mut chars = raw.chars();
-    if chars.next() != Some('/') {
-        return Err(());
-    }
-
-    let last_slash = raw.rfind('/').ok_or(())?;
-    if last_slash == 0 {
-        return Err(());
-    }
-
-    let pat = &raw[1..last_slash];
-    let flags = &raw[last_slash + 1..];
-    let mut pat = pat.to_string();
-
-    if flags.contains('i') {
-        pat = format!("(?i){pat}");
-    }
-
-    Regex::new(&pat).map_err(|_| ())
-}
-
 #[cfg(test)]
 mod property_tests {
     use super::*;
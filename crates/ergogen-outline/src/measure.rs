@@ -0,0 +1,381 @@
+//! Outline measurements, readable from any expression as `outlines.<name>.<field>`:
+//!
+//! ```yaml
+//! outlines.screws:
+//!   - what: circle
+//!     radius: 1.1
+//!     where: ...
+//!     adjust.shift: [outlines.board.width / 2 - 4, 0]
+//! cases.bottom:
+//!   - name: board
+//!     extrude: outlines.board.min_rect_height / 20
+//! ```
+//!
+//! Fields are the bounding box (`min_x`, `min_y`, `max_x`, `max_y`, `width`, `height`), `area`
+//! (holes subtracted), `perimeter` (holes included), the number of `islands` and `holes`, and the
+//! minimum-area enclosing rectangle (`min_rect_width` along `min_rect_angle` degrees,
+//! `min_rect_height` across it, with the width the longer side).
+
+use std::collections::HashSet;
+
+use cavalier_contours::polyline::PlineSource;
+use ergogen_geometry::region::Region;
+use ergogen_parser::Value;
+
+use crate::{OutlineContext, OutlineError, parse_outline_ref};
+
+/// Arcs are flattened this finely (in mm) for the enclosing rectangle.
+const ARC_TOLERANCE: f64 = 1e-4;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Measurements {
+    pub min_x: f64,
+    pub min_y: f64,
+    pub max_x: f64,
+    pub max_y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub area: f64,
+    pub perimeter: f64,
+    pub islands: usize,
+    pub holes: usize,
+    pub min_rect_width: f64,
+    pub min_rect_height: f64,
+    pub min_rect_angle: f64,
+}
+
+impl Measurements {
+    /// Every field by name, in a stable order.
+    pub fn fields(&self) -> [(&'static str, f64); 13] {
+        [
+            ("min_x", self.min_x),
+            ("min_y", self.min_y),
+            ("max_x", self.max_x),
+            ("max_y", self.max_y),
+            ("width", self.width),
+            ("height", self.height),
+            ("area", self.area),
+            ("perimeter", self.perimeter),
+            ("islands", self.islands as f64),
+            ("holes", self.holes as f64),
+            ("min_rect_width", self.min_rect_width),
+            ("min_rect_height", self.min_rect_height),
+            ("min_rect_angle", self.min_rect_angle),
+        ]
+    }
+}
+
+/// Measures a generated outline. An empty region measures all zeros.
+pub fn measure(region: &Region) -> Measurements {
+    let mut out = Measurements {
+        islands: region.pos.len(),
+        holes: region.neg.len(),
+        ..Default::default()
+    };
+    let extents: Vec<_> = region.pos.iter().filter_map(|pl| pl.extents()).collect();
+    if extents.is_empty() {
+        return out;
+    }
+    out.min_x = extents
+        .iter()
+        .map(|e| e.min_x)
+        .fold(f64::INFINITY, f64::min);
+    out.min_y = extents
+        .iter()
+        .map(|e| e.min_y)
+        .fold(f64::INFINITY, f64::min);
+    out.max_x = extents
+        .iter()
+        .map(|e| e.max_x)
+        .fold(f64::NEG_INFINITY, f64::max);
+    out.max_y = extents
+        .iter()
+        .map(|e| e.max_y)
+        .fold(f64::NEG_INFINITY, f64::max);
+    out.width = out.max_x - out.min_x;
+    out.height = out.max_y - out.min_y;
+
+    let pos: f64 = region.pos.iter().map(|pl| pl.area().abs()).sum();
+    let neg: f64 = region.neg.iter().map(|pl| pl.area().abs()).sum();
+    out.area = pos - neg;
+    out.perimeter = region
+        .pos
+        .iter()
+        .chain(&region.neg)
+        .map(|pl| pl.path_length())
+        .sum();
+
+    let points: Vec<[f64; 2]> = region
+        .pos
+        .iter()
+        .flat_map(|pl| {
+            let flat = pl
+                .arcs_to_approx_lines(ARC_TOLERANCE)
+                .unwrap_or_else(|| pl.clone());
+            flat.vertex_data
+                .iter()
+                .map(|v| [v.x, v.y])
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    let (w, h, angle) = min_area_rect(&convex_hull(points));
+    out.min_rect_width = w;
+    out.min_rect_height = h;
+    out.min_rect_angle = angle;
+    out
+}
+
+/// Counter-clockwise hull (Andrew's monotone chain), without collinear points.
+fn convex_hull(mut points: Vec<[f64; 2]>) -> Vec<[f64; 2]> {
+    points.sort_by(|a, b| a[0].total_cmp(&b[0]).then(a[1].total_cmp(&b[1])));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+    let cross = |o: [f64; 2], a: [f64; 2], b: [f64; 2]| {
+        (a[0] - o[0]) * (b[1] - o[1]) - (a[1] - o[1]) * (b[0] - o[0])
+    };
+    let mut hull: Vec<[f64; 2]> = Vec::with_capacity(points.len() * 2);
+    for pass in [points.clone(), points.into_iter().rev().collect()] {
+        let start = hull.len();
+        for p in pass {
+            while hull.len() >= start + 2
+                && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(p);
+        }
+        // The last point of each half is the first of the other.
+        hull.pop();
+    }
+    hull
+}
+
+/// `(width, height, angle)` of the smallest rectangle around `hull`, which has one side along a
+/// hull edge. The width is the longer side; the angle is its direction, in `(-90, 90]` degrees.
+fn min_area_rect(hull: &[[f64; 2]]) -> (f64, f64, f64) {
+    let n = hull.len();
+    let mut best: Option<(f64, f64, f64)> = None;
+    for i in 0..n {
+        let (a, b) = (hull[i], hull[(i + 1) % n]);
+        let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+        let len = dx.hypot(dy);
+        if len == 0.0 {
+            continue;
+        }
+        let (ux, uy) = (dx / len, dy / len);
+        let (mut u0, mut u1, mut v0, mut v1) = (
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+        );
+        for p in hull {
+            let u = p[0] * ux + p[1] * uy;
+            let v = -p[0] * uy + p[1] * ux;
+            (u0, u1, v0, v1) = (u0.min(u), u1.max(u), v0.min(v), v1.max(v));
+        }
+        let (w, h) = (u1 - u0, v1 - v0);
+        if best.is_none_or(|(bw, bh, _)| w * h < bw * bh - 1e-9) {
+            let angle = uy.atan2(ux).to_degrees();
+            best = Some(if w >= h {
+                (w, h, angle)
+            } else {
+                (h, w, angle + 90.0)
+            });
+        }
+    }
+    let Some((w, h, mut angle)) = best else {
+        return (0.0, 0.0, 0.0);
+    };
+    while angle > 90.0 + 1e-9 {
+        angle -= 180.0;
+    }
+    while angle <= -90.0 + 1e-9 {
+        angle += 180.0;
+    }
+    let angle = (angle * 1e9).round() / 1e9;
+    (w, h, if angle == 0.0 { 0.0 } else { angle })
+}
+
+/// Extends the units of `ctx` by the measurements of every outline that some expression of the
+/// config refers to.
+///
+/// Outlines are measured in dependency order: an outline whose expressions (or whose referenced
+/// outlines' expressions) use another outline's measurements is generated after that one. A
+/// measurement that cannot be resolved is recorded in `ctx` instead, and only fails what uses it
+/// (see [`check_measurements`]).
+pub(crate) fn resolve_measurements(ctx: &mut OutlineContext<'_>) {
+    let mut needed: Vec<String> = Vec::new();
+    for (name, field) in measurement_refs(&ctx.prepared().canonical) {
+        let ident = format!("outlines.{name}.{field}");
+        if ctx.prepared().units.get(&ident).is_some() {
+            continue;
+        }
+        if !Measurements::default()
+            .fields()
+            .iter()
+            .any(|(f, _)| *f == field)
+        {
+            let message = OutlineError::UnknownMeasurement {
+                name: ident.clone(),
+            }
+            .to_string();
+            ctx.fail_measurement(ident, message);
+            continue;
+        }
+        if !needed.contains(&name) {
+            needed.push(name);
+        }
+    }
+
+    let mut done = HashSet::new();
+    for name in needed {
+        // A failure is already recorded for `name`.
+        let _ = measure_in_order(ctx, &name, &mut done, &mut Vec::new());
+    }
+}
+
+/// Fails with the first measurement used by `config` (or by an outline it refers to) that could
+/// not be resolved.
+pub(crate) fn check_measurements(
+    ctx: &OutlineContext<'_>,
+    config: &Value,
+) -> Result<(), OutlineError> {
+    let mut refs = Vec::new();
+    collect_refs(
+        &ctx.prepared().canonical,
+        config,
+        &mut refs,
+        &mut HashSet::new(),
+    );
+    for (name, field) in refs {
+        let failed = ctx
+            .failed_measurement(&format!("outlines.{name}.{field}"))
+            .or_else(|| ctx.failed_measurement(&format!("outlines.{name}")));
+        if let Some(err) = failed {
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
+fn measure_in_order(
+    ctx: &mut OutlineContext<'_>,
+    name: &str,
+    done: &mut HashSet<String>,
+    stack: &mut Vec<String>,
+) -> Result<(), OutlineError> {
+    if let Some(err) = ctx.failed_measurement(&format!("outlines.{name}")) {
+        return Err(err);
+    }
+    if done.contains(name) {
+        return Ok(());
+    }
+    if stack.iter().any(|n| n == name) {
+        return Err(OutlineError::OutlineCycle {
+            name: name.to_string(),
+        });
+    }
+    if let Err(err) = measure_outline(ctx, name, done, stack) {
+        ctx.fail_measurement(
+            format!("outlines.{name}"),
+            format!("cannot measure outline \"{name}\": {err}"),
+        );
+        return Err(err);
+    }
+    done.insert(name.to_string());
+    Ok(())
+}
+
+fn measure_outline(
+    ctx: &mut OutlineContext<'_>,
+    name: &str,
+    done: &mut HashSet<String>,
+    stack: &mut Vec<String>,
+) -> Result<(), OutlineError> {
+    let canonical = &ctx.prepared().canonical;
+    let Some(outline) = outline_config(canonical, name) else {
+        return Err(OutlineError::UnknownMeasurement {
+            name: format!("outlines.{name}"),
+        });
+    };
+    stack.push(name.to_string());
+    let mut refs = Vec::new();
+    collect_refs(canonical, outline, &mut refs, &mut HashSet::new());
+    let mut deps: Vec<String> = Vec::new();
+    for (dep, _) in refs {
+        if !deps.contains(&dep) {
+            deps.push(dep);
+        }
+    }
+    for dep in deps {
+        measure_in_order(ctx, &dep, done, stack)?;
+    }
+    stack.pop();
+
+    let fields = measure(&ctx.region(name)?)
+        .fields()
+        .map(|(field, v)| (format!("outlines.{name}.{field}"), v));
+    ctx.extend_units(fields);
+    Ok(())
+}
+
+fn outline_config<'a>(canonical: &'a Value, name: &str) -> Option<&'a Value> {
+    canonical
+        .get_path("outlines")
+        .and_then(Value::as_map)
+        .and_then(|m| m.get(name))
+}
+
+/// The measurements `config` uses, including through the outlines it refers to by name.
+fn collect_refs(
+    canonical: &Value,
+    config: &Value,
+    refs: &mut Vec<(String, String)>,
+    seen: &mut HashSet<String>,
+) {
+    for r in measurement_refs(config) {
+        if !refs.contains(&r) {
+            refs.push(r);
+        }
+    }
+    for_each_string(config, &mut |s| {
+        let (_, referenced) = parse_outline_ref(s);
+        if seen.insert(referenced.to_string())
+            && let Some(other) = outline_config(canonical, referenced)
+        {
+            collect_refs(canonical, other, refs, seen);
+        }
+    });
+}
+
+/// Every `outlines.<name>.<field>` identifier in the strings of `value`.
+fn measurement_refs(value: &Value) -> Vec<(String, String)> {
+    let mut out = Vec::new();
+    for_each_string(value, &mut |s| {
+        let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '$' || c == '.';
+        for token in s.split(|c: char| !is_ident(c)) {
+            let Some(rest) = token.strip_prefix("outlines.") else {
+                continue;
+            };
+            if let Some((name, field)) = rest.split_once('.')
+                && !name.is_empty()
+                && !field.is_empty()
+            {
+                out.push((name.to_string(), field.to_string()));
+            }
+        }
+    });
+    out
+}
+
+fn for_each_string(value: &Value, f: &mut impl FnMut(&str)) {
+    match value {
+        Value::String(s) => f(s),
+        Value::Seq(items) => items.iter().for_each(|v| for_each_string(v, f)),
+        Value::Map(m) => m.values().for_each(|v| for_each_string(v, f)),
+        _ => {}
+    }
+}
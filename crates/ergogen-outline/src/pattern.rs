@@ -0,0 +1,335 @@
+//! `what: pattern` outlines: lightening cutouts filling another outline.
+//!
+//! ```yaml
+//! outlines.lightening:
+//!   - what: pattern
+//!     name: case             # the outline to fill
+//!     style: honeycomb       # honeycomb | slots | circles | voronoi
+//!     size: 6                # hexagon flat-to-flat, hole diameter, slot width or Voronoi cell size
+//!     wall: 1.5              # material left between cutouts
+//!     margin: 3              # material kept along the boundary and around keep-outs
+//!     keepout: [switches, screws]
+//!     length: 15             # slots only (default 3 * size)
+//!     angle: 30              # turns the whole pattern
+//!     seed: 7                # voronoi only
+//! outlines.case_shell:
+//!   - name: case
+//!   - name: lightening
+//!     operation: subtract
+//! ```
+//!
+//! The result holds the cutouts themselves, ready to be subtracted. Cutouts crossing the margin are
+//! clipped to it, and clipped pieces smaller than a quarter of a whole cutout are dropped. The
+//! pattern is laid out in absolute coordinates, centered on the filled area, so `where` and
+//! `adjust` do not apply.
+
+use cavalier_contours::polyline::PlineSource;
+use ergogen_geometry::region::Region;
+use ergogen_geometry::{Polyline, primitives};
+use ergogen_parser::{Units, Value};
+
+use crate::{ExpandJoints, OutlineError, eval_number, expand_region, intersect_region};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Style {
+    Honeycomb,
+    Slots,
+    Circles,
+    Voronoi,
+}
+
+pub(crate) struct PatternSpec {
+    /// Outline to fill.
+    pub(crate) name: String,
+    /// Outlines that stay solid (plus `margin`).
+    pub(crate) keepout: Vec<String>,
+    style: Style,
+    size: f64,
+    wall: f64,
+    margin: f64,
+    length: f64,
+    angle: f64,
+    seed: u64,
+}
+
+fn err(key: &str, message: impl Into<String>) -> OutlineError {
+    OutlineError::InvalidPattern {
+        at: format!("outlines.{key}"),
+        message: message.into(),
+    }
+}
+
+pub(crate) fn parse_pattern_spec(
+    obj: &indexmap::IndexMap<String, Value>,
+    units: &Units,
+) -> Result<PatternSpec, OutlineError> {
+    let number = |key: &str| {
+        obj.get(key)
+            .filter(|v| !matches!(v, Value::Null))
+            .map(|v| eval_number(units, v, &format!("outlines.{key}")))
+            .transpose()
+    };
+    let Some(Value::String(name)) = obj.get("name") else {
+        return Err(err("name", "\"name\" must name the outline to fill"));
+    };
+    let keepout = match obj.get("keepout") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::String(s)) => vec![s.clone()],
+        Some(Value::Seq(seq)) => seq
+            .iter()
+            .map(|v| match v {
+                Value::String(s) => Ok(s.clone()),
+                _ => Err(err("keepout", "must name outlines")),
+            })
+            .collect::<Result<_, _>>()?,
+        Some(_) => return Err(err("keepout", "must name outlines")),
+    };
+    let style = match obj.get("style") {
+        None | Some(Value::Null) => Style::Honeycomb,
+        Some(Value::String(s)) => match s.as_str() {
+            "honeycomb" => Style::Honeycomb,
+            "slots" => Style::Slots,
+            "circles" => Style::Circles,
+            "voronoi" => Style::Voronoi,
+            other => return Err(err("style", format!("unknown pattern style \"{other}\""))),
+        },
+        Some(_) => return Err(err("style", "must be a string")),
+    };
+
+    let size = number("size")?.unwrap_or(6.0);
+    if size <= 0.0 {
+        return Err(err("size", "must be positive"));
+    }
+    let wall = number("wall")?.unwrap_or(1.5);
+    if wall <= 0.0 {
+        return Err(err("wall", "must be positive"));
+    }
+    let margin = number("margin")?.unwrap_or(wall);
+    if margin < 0.0 {
+        return Err(err("margin", "must not be negative"));
+    }
+    let length = number("length")?.unwrap_or(size * 3.0);
+    if length < size {
+        return Err(err("length", "must be at least the slot width (size)"));
+    }
+    let seed = number("seed")?.unwrap_or(0.0);
+
+    Ok(PatternSpec {
+        name: name.clone(),
+        keepout,
+        style,
+        size,
+        wall,
+        margin,
+        length,
+        angle: number("angle")?.unwrap_or(0.0),
+        seed: seed.abs() as u64,
+    })
+}
+
+/// The cutouts for `area`, keeping `spec.margin` from its boundary and from every keep-out.
+pub(crate) fn fill(
+    spec: &PatternSpec,
+    area: &Region,
+    keepouts: &[Region],
+) -> Result<Region, OutlineError> {
+    let mut inner = expand_region(area, -spec.margin, ExpandJoints::Round)?;
+    for keepout in keepouts {
+        let grown = expand_region(keepout, spec.margin, ExpandJoints::Round)?;
+        if !grown.pos.is_empty() && !inner.pos.is_empty() {
+            inner.subtract_all(&grown.pos);
+        }
+    }
+    let Some((min, max)) = extents(&inner) else {
+        return Ok(Region::empty());
+    };
+
+    let center = [(min[0] + max[0]) / 2.0, (min[1] + max[1]) / 2.0];
+    let reach = ((max[0] - min[0]).hypot(max[1] - min[1])) / 2.0 + spec.size + spec.wall;
+    let (sin, cos) = spec.angle.to_radians().sin_cos();
+    let place = |mut pl: Polyline<f64>| {
+        for v in &mut pl.vertex_data {
+            let (x, y) = (v.x, v.y);
+            v.x = center[0] + x * cos - y * sin;
+            v.y = center[1] + x * sin + y * cos;
+        }
+        pl
+    };
+
+    let (cells, whole) = cells(spec, reach);
+    let mut out = Region::empty();
+    for cell in cells.into_iter().map(place) {
+        let Some(e) = cell.extents() else {
+            continue;
+        };
+        if e.max_x < min[0] || e.min_x > max[0] || e.max_y < min[1] || e.min_y > max[1] {
+            continue;
+        }
+        let pieces = intersect_region(&inner, &Region::from_pos(vec![cell]));
+        let kept: Vec<Polyline<f64>> = pieces
+            .pos
+            .into_iter()
+            .filter(|pl| pl.area().abs() >= whole / 4.0)
+            .collect();
+        if !kept.is_empty() {
+            out.pos.extend(kept);
+            out.neg.extend(pieces.neg);
+        }
+    }
+    Ok(out)
+}
+
+fn extents(region: &Region) -> Option<([f64; 2], [f64; 2])> {
+    let mut bounds: Option<([f64; 2], [f64; 2])> = None;
+    for e in region.pos.iter().filter_map(|pl| pl.extents()) {
+        let (min, max) = bounds.get_or_insert(([e.min_x, e.min_y], [e.max_x, e.max_y]));
+        *min = [min[0].min(e.min_x), min[1].min(e.min_y)];
+        *max = [max[0].max(e.max_x), max[1].max(e.max_y)];
+    }
+    bounds
+}
+
+/// Unclipped cutouts covering a `reach`-radius square around the origin, plus the area of a whole
+/// cutout.
+fn cells(spec: &PatternSpec, reach: f64) -> (Vec<Polyline<f64>>, f64) {
+    let (size, wall) = (spec.size, spec.wall);
+    match spec.style {
+        Style::Honeycomb => {
+            // Pointy-top hexagons: flats face left and right, `size` apart.
+            let radius = size / 3f64.sqrt();
+            let hex = |[x, y]: [f64; 2]| {
+                let vertices: Vec<(f64, f64)> = (0..6)
+                    .map(|k| {
+                        let a = (30.0 + 60.0 * f64::from(k)).to_radians();
+                        (x + radius * a.cos(), y + radius * a.sin())
+                    })
+                    .collect();
+                primitives::polygon(&vertices)
+            };
+            let whole = 3.0 * 3f64.sqrt() / 2.0 * radius * radius;
+            (hex_lattice(size + wall, reach).map(hex).collect(), whole)
+        }
+        Style::Circles => {
+            let circle = |[x, y]: [f64; 2]| primitives::circle((x, y), size / 2.0);
+            let whole = std::f64::consts::PI * size * size / 4.0;
+            (hex_lattice(size + wall, reach).map(circle).collect(), whole)
+        }
+        Style::Slots => {
+            let (px, py) = (spec.length + wall, size + wall);
+            let half = (spec.length - size) / 2.0;
+            let (nx, ny) = ((reach / px).ceil() as i64 + 1, (reach / py).ceil() as i64);
+            let mut out = Vec::new();
+            for j in -ny..=ny {
+                let offset = if j % 2 == 0 { 0.0 } else { px / 2.0 };
+                for i in -nx..=nx {
+                    let (x, y) = (i as f64 * px + offset, j as f64 * py);
+                    out.push(primitives::capsule((x - half, y), (x + half, y), size));
+                }
+            }
+            let whole = (spec.length - size) * size + std::f64::consts::PI * size * size / 4.0;
+            (out, whole)
+        }
+        Style::Voronoi => (voronoi(spec, reach), size * size),
+    }
+}
+
+/// Centers of a hexagonal lattice with neighbours `pitch` apart.
+fn hex_lattice(pitch: f64, reach: f64) -> impl Iterator<Item = [f64; 2]> {
+    let dy = pitch * 3f64.sqrt() / 2.0;
+    let (nx, ny) = (
+        (reach / pitch).ceil() as i64 + 1,
+        (reach / dy).ceil() as i64,
+    );
+    (-ny..=ny).flat_map(move |j| {
+        let offset = if j % 2 == 0 { 0.0 } else { pitch / 2.0 };
+        (-nx..=nx).map(move |i| [i as f64 * pitch + offset, j as f64 * dy])
+    })
+}
+
+/// Voronoi cells around jittered grid seeds, each shrunk by half a wall on every side.
+fn voronoi(spec: &PatternSpec, reach: f64) -> Vec<Polyline<f64>> {
+    let pitch = spec.size + spec.wall;
+    let n = (reach / pitch).ceil() as i64 + 1;
+    let mut rng = SplitMix64(spec.seed);
+    let mut seeds = Vec::new();
+    for j in -n..=n {
+        for i in -n..=n {
+            let jitter = |rng: &mut SplitMix64| (rng.next_f64() - 0.5) * 0.7 * pitch;
+            seeds.push([
+                i as f64 * pitch + jitter(&mut rng),
+                j as f64 * pitch + jitter(&mut rng),
+            ]);
+        }
+    }
+
+    let mut out = Vec::new();
+    for (i, s) in seeds.iter().enumerate() {
+        let box_half = pitch * 2.0;
+        let mut cell = vec![
+            [s[0] - box_half, s[1] - box_half],
+            [s[0] + box_half, s[1] - box_half],
+            [s[0] + box_half, s[1] + box_half],
+            [s[0] - box_half, s[1] + box_half],
+        ];
+        for (j, o) in seeds.iter().enumerate() {
+            let d = [o[0] - s[0], o[1] - s[1]];
+            let dist = d[0].hypot(d[1]);
+            if i == j || dist > pitch * 3.0 {
+                continue;
+            }
+            // Keep the side of the bisector facing `s`, pulled back by half a wall.
+            let normal = [d[0] / dist, d[1] / dist];
+            let limit = dist / 2.0 - spec.wall / 2.0;
+            cell = clip_half_plane(&cell, *s, normal, limit);
+            if cell.is_empty() {
+                break;
+            }
+        }
+        if cell.len() >= 3 {
+            let vertices: Vec<(f64, f64)> = cell.iter().map(|p| (p[0], p[1])).collect();
+            let pl = primitives::polygon(&vertices);
+            if pl.area().abs() > 1e-9 {
+                out.push(pl);
+            }
+        }
+    }
+    out
+}
+
+/// Sutherland–Hodgman step: the part of convex `poly` where `(p - origin) · normal <= limit`.
+fn clip_half_plane(
+    poly: &[[f64; 2]],
+    origin: [f64; 2],
+    normal: [f64; 2],
+    limit: f64,
+) -> Vec<[f64; 2]> {
+    let side =
+        |p: &[f64; 2]| (p[0] - origin[0]) * normal[0] + (p[1] - origin[1]) * normal[1] - limit;
+    let mut out = Vec::with_capacity(poly.len() + 1);
+    for (k, a) in poly.iter().enumerate() {
+        let b = &poly[(k + 1) % poly.len()];
+        let (sa, sb) = (side(a), side(b));
+        if sa <= 0.0 {
+            out.push(*a);
+        }
+        if (sa <= 0.0) != (sb <= 0.0) {
+            let t = sa / (sa - sb);
+            out.push([a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t]);
+        }
+    }
+    out
+}
+
+/// Small deterministic generator so `seed` always gives the same pattern.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_f64(&mut self) -> f64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
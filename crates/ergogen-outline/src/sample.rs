@@ -0,0 +1,257 @@
+//! Zones of points sampled along a generated outline (`from_outline`).
+//!
+//! ```yaml
+//! points.zones.screws.from_outline:
+//!   name: board
+//!   count: 6          # or `spacing: 40`, or `corners: true`
+//!   inset: 4
+//! ```
+//!
+//! Points are named `<zone>_<n>` (from 1) and rotated along the edge in the outline's
+//! counter-clockwise direction. `inset` moves them inwards; corner points are inset from both edges.
+//!
+//! The sampled outline, and any outline it references, never sees its own samples. `where: true`
+//! skips sampled points everywhere; select them by name, zone or tag instead.
+
+use cavalier_contours::core::math::Vector2;
+use cavalier_contours::polyline::{
+    PlineSource, PlineSourceMut, PlineVertex, seg_arc_radius_and_center, seg_length,
+    seg_tangent_vector,
+};
+use ergogen_geometry::Polyline;
+use ergogen_layout::{
+    BindSource, BindTrace, PointsOutput, SideBind, parse_points_with_bind_trace, place_free_key,
+};
+use ergogen_parser::{PreparedConfig, Value};
+
+use crate::context::RegionCache;
+use crate::{OutlineError, eval_number, generate_outline_region_with_points};
+
+/// Turns sharper than this (in degrees) count as corners.
+const CORNER_TURN: f64 = 10.0;
+/// Arcs sweeping at least this much (in degrees) count as a rounded corner, sampled at their middle.
+const CORNER_ARC: f64 = 45.0;
+
+enum Mode {
+    Spacing(f64),
+    Count(usize),
+    Corners,
+}
+
+/// All points of the config: the regular zones, followed by every `from_outline` zone.
+///
+/// The sampled outlines are generated from the regular points only, so they cannot depend on
+/// other sampled points.
+pub fn parse_points_with_outline_samples(
+    prepared: &PreparedConfig,
+) -> Result<PointsOutput, OutlineError> {
+    Ok(place_points(prepared, &RegionCache::default())?.0)
+}
+
+/// [`parse_points_with_outline_samples`] with the autobind trace, leaving the sampled outlines (and
+/// the outlines they reference) in `regions`. Those are generated without the sampled points, and
+/// stay that way for everything else using the cache.
+///
+/// Sampled points are traced with their stored binds, as autobind never sees them.
+pub(crate) fn place_points(
+    prepared: &PreparedConfig,
+    regions: &RegionCache,
+) -> Result<(PointsOutput, BindTrace), OutlineError> {
+    let (mut points, mut trace) =
+        parse_points_with_bind_trace(&prepared.canonical, &prepared.units)?;
+    let Some(Value::Map(zones)) = prepared.canonical.get_path("points.zones") else {
+        return Ok((points, trace));
+    };
+
+    let base = points.clone();
+    for (zone_name, zone) in zones {
+        let Some(spec) = zone.get_path("from_outline") else {
+            continue;
+        };
+        let at = format!("points.zones.{zone_name}.from_outline");
+        let (outline, poses) = sample_zone(prepared, &base, regions, spec, &at)?;
+        for (i, pose) in poses.into_iter().enumerate() {
+            let name = format!("{zone_name}_{}", i + 1);
+            if points.contains_key(&name) {
+                return Err(ergogen_layout::LayoutError::DuplicateKey { name }.into());
+            }
+            let mut p =
+                place_free_key(&prepared.canonical, &prepared.units, zone_name, &name, pose)?;
+            p.meta.from_outline = Some(outline.to_string());
+            trace.insert(
+                name.clone(),
+                p.meta.bind.map(|amount| SideBind {
+                    amount,
+                    source: BindSource::None,
+                }),
+            );
+            points.insert(name, p);
+        }
+    }
+    Ok((points, trace))
+}
+
+/// The sampled outline's name and the poses along it.
+fn sample_zone<'a>(
+    prepared: &PreparedConfig,
+    base: &PointsOutput,
+    regions: &RegionCache,
+    spec: &'a Value,
+    at: &str,
+) -> Result<(&'a str, Vec<[f64; 3]>), OutlineError> {
+    let err = |message: &str| OutlineError::InvalidSample {
+        at: at.to_string(),
+        message: message.to_string(),
+    };
+    let Value::Map(m) = spec else {
+        return Err(err("from_outline must be an object"));
+    };
+    let Some(Value::String(outline)) = m.get("name") else {
+        return Err(err("\"name\" must name an outline"));
+    };
+    let number = |key: &str| {
+        m.get(key)
+            .map(|v| eval_number(&prepared.units, v, &format!("{at}.{key}")))
+            .transpose()
+    };
+    let corners = matches!(m.get("corners"), Some(Value::Bool(true)));
+    let mode = match (number("spacing")?, number("count")?, corners) {
+        (Some(spacing), None, false) if spacing > 0.0 => Mode::Spacing(spacing),
+        (None, Some(count), false) if count >= 1.0 && count.fract() == 0.0 => {
+            Mode::Count(count as usize)
+        }
+        (None, None, true) => Mode::Corners,
+        (Some(_), None, false) => return Err(err("\"spacing\" must be positive")),
+        (None, Some(_), false) => return Err(err("\"count\" must be a positive whole number")),
+        _ => {
+            return Err(err(
+                "exactly one of spacing, count or corners: true is required",
+            ));
+        }
+    };
+    let inset = number("inset")?.unwrap_or(0.0);
+
+    if prepared
+        .canonical
+        .get_path(&format!("outlines.{outline}"))
+        .is_none()
+    {
+        return Err(err(&format!("unknown outline \"{outline}\"")));
+    }
+    let region = generate_outline_region_with_points(prepared, outline, base, regions)?;
+
+    let mut out = Vec::new();
+    for pline in &region.pos {
+        let mut pline = pline.clone();
+        if pline.area() < 0.0 {
+            pline.invert_direction_mut();
+        }
+        out.extend(match mode {
+            Mode::Spacing(spacing) => {
+                let length = pline.path_length();
+                let n = ((length / spacing) - 1e-9).ceil().max(1.0) as usize;
+                sample_at(&pline, (0..n).map(|i| i as f64 * spacing), inset)
+            }
+            Mode::Count(count) => {
+                let length = pline.path_length();
+                let step = length / count as f64;
+                sample_at(&pline, (0..count).map(|i| i as f64 * step), inset)
+            }
+            Mode::Corners => corners_of(&pline, inset),
+        });
+    }
+    Ok((outline, out))
+}
+
+fn segments(pline: &Polyline<f64>) -> impl Iterator<Item = (PlineVertex<f64>, PlineVertex<f64>)> {
+    let n = pline.vertex_count();
+    (0..n).map(move |i| (pline.at(i), pline.at((i + 1) % n)))
+}
+
+/// Poses at the given distances along the (counter-clockwise) perimeter.
+fn sample_at(
+    pline: &Polyline<f64>,
+    distances: impl Iterator<Item = f64>,
+    inset: f64,
+) -> Vec<[f64; 3]> {
+    let segs: Vec<_> = segments(pline).collect();
+    let lengths: Vec<f64> = segs.iter().map(|(a, b)| seg_length(*a, *b)).collect();
+    let mut out = Vec::new();
+    for mut d in distances {
+        let mut idx = 0;
+        while idx + 1 < segs.len() && d > lengths[idx] {
+            d -= lengths[idx];
+            idx += 1;
+        }
+        let (v1, v2) = segs[idx];
+        let pos = point_on_seg(v1, v2, d.min(lengths[idx]));
+        let tangent = seg_tangent_vector(v1, v2, pos).normalize();
+        out.push(pose(pos, tangent, tangent, inset));
+    }
+    out
+}
+
+/// Sharp vertices, plus the middle of every arc that turns far enough to be a rounded corner.
+fn corners_of(pline: &Polyline<f64>, inset: f64) -> Vec<[f64; 3]> {
+    let segs: Vec<_> = segments(pline).collect();
+    let n = segs.len();
+    let mut out = Vec::new();
+    for i in 0..n {
+        let (p1, p2) = segs[(i + n - 1) % n];
+        let (v1, v2) = segs[i];
+        let incoming = seg_tangent_vector(p1, p2, p2.pos()).normalize();
+        let outgoing = seg_tangent_vector(v1, v2, v1.pos()).normalize();
+        let turn = incoming.perp_dot(outgoing).atan2(incoming.dot(outgoing));
+        if turn.to_degrees().abs() >= CORNER_TURN {
+            out.push(pose(v1.pos(), incoming, outgoing, inset));
+        }
+
+        let sweep = 4.0 * v1.bulge.atan();
+        if sweep.to_degrees().abs() >= CORNER_ARC {
+            let mid = point_on_seg(v1, v2, seg_length(v1, v2) / 2.0);
+            let tangent = seg_tangent_vector(v1, v2, mid).normalize();
+            out.push(pose(mid, tangent, tangent, inset));
+        }
+    }
+    out
+}
+
+pub(crate) fn point_on_seg(v1: PlineVertex<f64>, v2: PlineVertex<f64>, d: f64) -> Vector2<f64> {
+    if v1.bulge_is_zero() {
+        let length = seg_length(v1, v2);
+        let t = if length > 0.0 { d / length } else { 0.0 };
+        return v1.pos() + (v2.pos() - v1.pos()).scale(t);
+    }
+    let (radius, center) = seg_arc_radius_and_center(v1, v2);
+    let angle = (d / radius) * v1.bulge.signum();
+    let (sin, cos) = angle.sin_cos();
+    let r = v1.pos() - center;
+    center + Vector2::new(r.x * cos - r.y * sin, r.x * sin + r.y * cos)
+}
+
+/// Position moved inwards by `inset` from both the incoming and the outgoing edge, rotated along
+/// the bisector of the two directions (which is the edge itself away from corners).
+fn pose(pos: Vector2<f64>, incoming: Vector2<f64>, outgoing: Vector2<f64>, inset: f64) -> [f64; 3] {
+    // Counter-clockwise, so the inside is on the left.
+    let n1 = Vector2::new(-incoming.y, incoming.x);
+    let n2 = Vector2::new(-outgoing.y, outgoing.x);
+    let denom = 1.0 + n1.dot(n2);
+    let shift = if denom.abs() < 1e-9 {
+        n2.scale(inset)
+    } else {
+        (n1 + n2).scale(inset / denom)
+    };
+    let direction = incoming + outgoing;
+    let r = if direction.length() < 1e-9 {
+        outgoing.y.atan2(outgoing.x)
+    } else {
+        direction.y.atan2(direction.x)
+    };
+    let p = pos + shift;
+    [p.x, p.y, clean(r.to_degrees())]
+}
+
+fn clean(v: f64) -> f64 {
+    let v = (v * 1e9).round() / 1e9;
+    if v == 0.0 { 0.0 } else { v }
+}
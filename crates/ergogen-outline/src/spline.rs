@@ -0,0 +1,384 @@
+//! Smooth `catmull_rom`, `natural_spline` and `b_spline` segments for `what: path`.
+//!
+//! ```yaml
+//! segments:
+//!   - type: catmull_rom    # or natural_spline, b_spline
+//!     points: [...]        # anchors, or the control polygon for b_spline
+//!     tension: 0.2         # 0 (default) is the plain curve, 1 pulls it straight between knots
+//!     tolerance: 0.01      # largest allowed deviation from the exact curve, in mm
+//!     arcs: true           # fit tangent arcs (biarcs) instead of line segments
+//! ```
+//!
+//! `catmull_rom` and `natural_spline` interpolate their anchors with cubic pieces and differ only
+//! in the tangents: Catmull-Rom uses the neighbouring anchors, `natural_spline` solves for the
+//! C2-continuous natural cubic spline through them. `b_spline` is the uniform cubic B-spline of
+//! its control polygon, as drawn in CAD: it only passes through the first and last point, which are
+//! pinned by reflecting their neighbours. `tension` scales the tangents at the knots (the anchors,
+//! or the joints between B-spline pieces) down.
+
+use ergogen_parser::{Units, Value};
+
+use crate::makerjs_path::{MakerArc, Primitive};
+use crate::{OutlineError, eval_number};
+
+/// Subdivision depth limit, for pathological tolerances.
+const MAX_DEPTH: u32 = 16;
+
+type Cubic = [[f64; 2]; 4];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SplineKind {
+    CatmullRom,
+    Natural,
+    BSpline,
+}
+
+pub(crate) struct SplineOptions {
+    tension: f64,
+    tolerance: f64,
+    arcs: bool,
+}
+
+pub(crate) fn parse_spline_options(
+    seg: &indexmap::IndexMap<String, Value>,
+    units: &Units,
+    at: &str,
+) -> Result<SplineOptions, OutlineError> {
+    let number = |key: &str, default: f64| match seg.get(key) {
+        None | Some(Value::Null) => Ok(default),
+        Some(v) => eval_number(units, v, &format!("{at}.{key}")),
+    };
+    let err = |key: &str, message: &str| OutlineError::InvalidSpline {
+        at: format!("{at}.{key}"),
+        message: message.to_string(),
+    };
+    let tension = number("tension", 0.0)?;
+    if !(0.0..=1.0).contains(&tension) {
+        return Err(err("tension", "must be between 0 and 1"));
+    }
+    let tolerance = number("tolerance", 0.01)?;
+    if tolerance <= 0.0 {
+        return Err(err("tolerance", "must be positive"));
+    }
+    let arcs = match seg.get("arcs") {
+        None | Some(Value::Null) => false,
+        Some(Value::Bool(b)) => *b,
+        Some(_) => return Err(err("arcs", "must be a boolean")),
+    };
+    Ok(SplineOptions {
+        tension,
+        tolerance,
+        arcs,
+    })
+}
+
+/// Lines (or arcs) approximating the spline of `points`.
+pub(crate) fn spline_primitives(
+    kind: SplineKind,
+    points: &[[f64; 2]],
+    opts: &SplineOptions,
+) -> Vec<Primitive> {
+    let cubics = match kind {
+        SplineKind::CatmullRom => hermite_cubics(points, &catmull_rom_tangents(points)),
+        SplineKind::Natural => hermite_cubics(points, &natural_tangents(points)),
+        SplineKind::BSpline => b_spline_cubics(points),
+    };
+    let keep = 1.0 - opts.tension;
+    let mut out = Vec::new();
+    for [p0, c1, c2, p3] in cubics {
+        let cubic = [
+            p0,
+            [
+                p0[0] + (c1[0] - p0[0]) * keep,
+                p0[1] + (c1[1] - p0[1]) * keep,
+            ],
+            [
+                p3[0] + (c2[0] - p3[0]) * keep,
+                p3[1] + (c2[1] - p3[1]) * keep,
+            ],
+            p3,
+        ];
+        if opts.arcs {
+            biarcs(&cubic, opts.tolerance, 0, &mut out);
+        } else {
+            flatten(&cubic, opts.tolerance, 0, &mut out);
+        }
+    }
+    out
+}
+
+/// Bezier pieces between consecutive anchors with the given tangents.
+fn hermite_cubics(points: &[[f64; 2]], tangents: &[[f64; 2]]) -> Vec<Cubic> {
+    (0..points.len() - 1)
+        .map(|i| {
+            let (p0, p3) = (points[i], points[i + 1]);
+            let (m0, m1) = (tangents[i], tangents[i + 1]);
+            [
+                p0,
+                [p0[0] + m0[0] / 3.0, p0[1] + m0[1] / 3.0],
+                [p3[0] - m1[0] / 3.0, p3[1] - m1[1] / 3.0],
+                p3,
+            ]
+        })
+        .collect()
+}
+
+/// Bezier pieces of the uniform cubic B-spline of `control`, extended by the reflections of the
+/// second and second to last points so that it starts and ends on the first and last point.
+fn b_spline_cubics(control: &[[f64; 2]]) -> Vec<Cubic> {
+    let n = control.len() - 1;
+    let reflect = |a: [f64; 2], b: [f64; 2]| [2.0 * a[0] - b[0], 2.0 * a[1] - b[1]];
+    let mut q = Vec::with_capacity(n + 3);
+    q.push(reflect(control[0], control[1]));
+    q.extend_from_slice(control);
+    q.push(reflect(control[n], control[n - 1]));
+
+    let mix = |w: [f64; 3], a: [f64; 2], b: [f64; 2], c: [f64; 2]| {
+        let sum = w[0] + w[1] + w[2];
+        [
+            (w[0] * a[0] + w[1] * b[0] + w[2] * c[0]) / sum,
+            (w[0] * a[1] + w[1] * b[1] + w[2] * c[1]) / sum,
+        ]
+    };
+    q.windows(4)
+        .map(|w| {
+            [
+                mix([1.0, 4.0, 1.0], w[0], w[1], w[2]),
+                mix([0.0, 2.0, 1.0], w[0], w[1], w[2]),
+                mix([0.0, 1.0, 2.0], w[0], w[1], w[2]),
+                mix([1.0, 4.0, 1.0], w[1], w[2], w[3]),
+            ]
+        })
+        .collect()
+}
+
+fn catmull_rom_tangents(p: &[[f64; 2]]) -> Vec<[f64; 2]> {
+    let n = p.len() - 1;
+    (0..=n)
+        .map(|i| {
+            let (a, b, div) = match i {
+                0 => (p[0], p[1], 1.0),
+                i if i == n => (p[n - 1], p[n], 1.0),
+                i => (p[i - 1], p[i + 1], 2.0),
+            };
+            [(b[0] - a[0]) / div, (b[1] - a[1]) / div]
+        })
+        .collect()
+}
+
+/// Tangents of the natural cubic spline through `p` (zero curvature at both ends), from the
+/// tridiagonal system `m[i-1] + 4 m[i] + m[i+1] = 3 (p[i+1] - p[i-1])`.
+fn natural_tangents(p: &[[f64; 2]]) -> Vec<[f64; 2]> {
+    let n = p.len() - 1;
+    if n == 1 {
+        let d = [p[1][0] - p[0][0], p[1][1] - p[0][1]];
+        return vec![d, d];
+    }
+    let rhs = |i: usize, axis: usize| match i {
+        0 => 3.0 * (p[1][axis] - p[0][axis]),
+        i if i == n => 3.0 * (p[n][axis] - p[n - 1][axis]),
+        i => 3.0 * (p[i + 1][axis] - p[i - 1][axis]),
+    };
+    let diag = |i: usize| if i == 0 || i == n { 2.0 } else { 4.0 };
+
+    // Thomas algorithm; every off-diagonal entry is 1.
+    let mut c = vec![0.0; n + 1];
+    let mut d = vec![[0.0; 2]; n + 1];
+    c[0] = 1.0 / diag(0);
+    d[0] = [rhs(0, 0) / diag(0), rhs(0, 1) / diag(0)];
+    for i in 1..=n {
+        let denom = diag(i) - c[i - 1];
+        c[i] = 1.0 / denom;
+        let prev = d[i - 1];
+        d[i] = [(rhs(i, 0) - prev[0]) / denom, (rhs(i, 1) - prev[1]) / denom];
+    }
+    let mut m = vec![[0.0; 2]; n + 1];
+    m[n] = d[n];
+    for i in (0..n).rev() {
+        let next = m[i + 1];
+        m[i] = [d[i][0] - c[i] * next[0], d[i][1] - c[i] * next[1]];
+    }
+    m
+}
+
+fn split(c: &Cubic) -> (Cubic, Cubic) {
+    let mid = |a: [f64; 2], b: [f64; 2]| [(a[0] + b[0]) / 2.0, (a[1] + b[1]) / 2.0];
+    let (ab, bc, cd) = (mid(c[0], c[1]), mid(c[1], c[2]), mid(c[2], c[3]));
+    let (abc, bcd) = (mid(ab, bc), mid(bc, cd));
+    let m = mid(abc, bcd);
+    ([c[0], ab, abc, m], [m, bcd, cd, c[3]])
+}
+
+fn at(c: &Cubic, t: f64) -> [f64; 2] {
+    let u = 1.0 - t;
+    let w = [u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t];
+    [
+        (0..4).map(|k| w[k] * c[k][0]).sum(),
+        (0..4).map(|k| w[k] * c[k][1]).sum(),
+    ]
+}
+
+fn distance_to_segment(p: [f64; 2], a: [f64; 2], b: [f64; 2]) -> f64 {
+    let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+    let len2 = dx * dx + dy * dy;
+    let t = if len2 == 0.0 {
+        0.0
+    } else {
+        (((p[0] - a[0]) * dx + (p[1] - a[1]) * dy) / len2).clamp(0.0, 1.0)
+    };
+    (p[0] - a[0] - t * dx).hypot(p[1] - a[1] - t * dy)
+}
+
+/// Adaptive subdivision until both control points are within `tolerance` of the chord.
+fn flatten(c: &Cubic, tolerance: f64, depth: u32, out: &mut Vec<Primitive>) {
+    let flat = distance_to_segment(c[1], c[0], c[3]).max(distance_to_segment(c[2], c[0], c[3]));
+    if flat <= tolerance || depth >= MAX_DEPTH {
+        if c[0] != c[3] {
+            out.push(Primitive::Line { a: c[0], b: c[3] });
+        }
+        return;
+    }
+    let (l, r) = split(c);
+    flatten(&l, tolerance, depth + 1, out);
+    flatten(&r, tolerance, depth + 1, out);
+}
+
+/// A piece of a biarc: `bulge` as in cavalier polylines (0 for a line).
+#[derive(Clone, Copy)]
+struct Piece {
+    a: [f64; 2],
+    b: [f64; 2],
+    bulge: f64,
+}
+
+impl Piece {
+    fn center_radius(&self) -> ([f64; 2], f64) {
+        let (dx, dy) = (self.b[0] - self.a[0], self.b[1] - self.a[1]);
+        let k = (1.0 - self.bulge * self.bulge) / (4.0 * self.bulge);
+        let center = [
+            (self.a[0] + self.b[0]) / 2.0 - dy * k,
+            (self.a[1] + self.b[1]) / 2.0 + dx * k,
+        ];
+        let radius = dx.hypot(dy) * (1.0 + self.bulge * self.bulge) / (4.0 * self.bulge.abs());
+        (center, radius)
+    }
+
+    fn distance(&self, p: [f64; 2]) -> f64 {
+        if self.bulge.abs() < 1e-9 {
+            return distance_to_segment(p, self.a, self.b);
+        }
+        let (center, radius) = self.center_radius();
+        ((p[0] - center[0]).hypot(p[1] - center[1]) - radius).abs()
+    }
+
+    fn primitive(&self) -> Primitive {
+        if self.bulge.abs() < 1e-9 {
+            return Primitive::Line {
+                a: self.a,
+                b: self.b,
+            };
+        }
+        let (center, radius) = self.center_radius();
+        let angle = |p: [f64; 2]| {
+            (p[1] - center[1])
+                .atan2(p[0] - center[0])
+                .to_degrees()
+                .rem_euclid(360.0)
+        };
+        // Arcs are counter-clockwise; chaining flips clockwise ones back.
+        let (a, b) = if self.bulge > 0.0 {
+            (self.a, self.b)
+        } else {
+            (self.b, self.a)
+        };
+        Primitive::Arc {
+            arc: MakerArc {
+                origin: center,
+                radius,
+                start_angle_deg: angle(a),
+                end_angle_deg: angle(b),
+            },
+            a,
+            b,
+            reversed: false,
+        }
+    }
+}
+
+fn unit(v: [f64; 2]) -> Option<[f64; 2]> {
+    let len = v[0].hypot(v[1]);
+    (len > 1e-12).then(|| [v[0] / len, v[1] / len])
+}
+
+/// The equal-tangent-length biarc between the ends of `c`, matching its end tangents.
+fn biarc(c: &Cubic) -> Option<[Piece; 2]> {
+    let (p0, p1) = (c[0], c[3]);
+    let t0 = unit([c[1][0] - p0[0], c[1][1] - p0[1]])
+        .or_else(|| unit([c[2][0] - p0[0], c[2][1] - p0[1]]))?;
+    let t1 = unit([p1[0] - c[2][0], p1[1] - c[2][1]])
+        .or_else(|| unit([p1[0] - c[1][0], p1[1] - c[1][1]]))?;
+    let v = [p1[0] - p0[0], p1[1] - p0[1]];
+    let t = [t0[0] + t1[0], t0[1] + t1[1]];
+    let vt = v[0] * t[0] + v[1] * t[1];
+    let vv = v[0] * v[0] + v[1] * v[1];
+    let dot = t0[0] * t1[0] + t0[1] * t1[1];
+    // |v - d t| = 2d
+    let d = if (1.0 - dot).abs() < 1e-12 {
+        vv / (2.0 * vt)
+    } else {
+        let a = 2.0 * (dot - 1.0);
+        (2.0 * vt - (4.0 * vt * vt - 4.0 * a * vv).sqrt()) / (2.0 * a)
+    };
+    if !d.is_finite() || d <= 0.0 {
+        return None;
+    }
+    let joint = [
+        (p0[0] + d * t0[0] + p1[0] - d * t1[0]) / 2.0,
+        (p0[1] + d * t0[1] + p1[1] - d * t1[1]) / 2.0,
+    ];
+    let signed_angle =
+        |u: [f64; 2], w: [f64; 2]| (u[0] * w[1] - u[1] * w[0]).atan2(u[0] * w[0] + u[1] * w[1]);
+    let chord1 = [joint[0] - p0[0], joint[1] - p0[1]];
+    let chord2 = [p1[0] - joint[0], p1[1] - joint[1]];
+    Some([
+        Piece {
+            a: p0,
+            b: joint,
+            bulge: (signed_angle(t0, chord1) / 2.0).tan(),
+        },
+        Piece {
+            a: joint,
+            b: p1,
+            bulge: (signed_angle(chord2, t1) / 2.0).tan(),
+        },
+    ])
+}
+
+/// Biarcs within `tolerance` of `c`, splitting it where one biarc is not close enough.
+fn biarcs(c: &Cubic, tolerance: f64, depth: u32, out: &mut Vec<Primitive>) {
+    let flat = distance_to_segment(c[1], c[0], c[3]).max(distance_to_segment(c[2], c[0], c[3]));
+    if flat <= tolerance / 4.0 {
+        if c[0] != c[3] {
+            out.push(Primitive::Line { a: c[0], b: c[3] });
+        }
+        return;
+    }
+    if let Some(pieces) = biarc(c) {
+        let error = (1..10)
+            .map(|k| {
+                let p = at(c, f64::from(k) / 10.0);
+                pieces[0].distance(p).min(pieces[1].distance(p))
+            })
+            .fold(0.0, f64::max);
+        if error <= tolerance || depth >= MAX_DEPTH {
+            out.extend(pieces.iter().map(Piece::primitive));
+            return;
+        }
+    } else if depth >= MAX_DEPTH {
+        out.push(Primitive::Line { a: c[0], b: c[3] });
+        return;
+    }
+    let (l, r) = split(c);
+    biarcs(&l, tolerance, depth + 1, out);
+    biarcs(&r, tolerance, depth + 1, out);
+}
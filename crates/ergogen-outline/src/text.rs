@@ -0,0 +1,429 @@
+//! `what: text` outlines: strings turned into closed shapes.
+//!
+//! ```yaml
+//! outlines.plate:
+//!   - what: text
+//!     where: matrix_pinky_bottom
+//!     text: rev 2
+//!     size: 4            # cap height
+//!     font: fonts/Inter.ttf  # optional TTF/OTF (relative to the bundle); built-in otherwise
+//!     stroke: 0.5        # line width of the built-in single-line font (default size / 8)
+//!     align: center      # left (default) | center | right
+//!     valign: middle     # baseline (default) | middle | top
+//!     rotate: 90
+//!     spacing: 0.5       # extra space between letters
+//! ```
+//!
+//! The built-in font only knows upper case letters (lower case is drawn as upper case), digits
+//! and common punctuation. Text is never mirrored, so it stays readable on mirrored points.
+
+use std::path::Path;
+
+use ergogen_geometry::region::Region;
+use ergogen_geometry::{PlineVertex, Polyline, primitives};
+use ergogen_parser::{Units, Value};
+
+use crate::{OutlineError, eval_number};
+
+/// The built-in font is drawn on a grid with this cap height.
+const GRID_HEIGHT: f64 = 6.0;
+/// Gap between built-in glyphs, in grid units.
+const GRID_GAP: f64 = 1.0;
+/// Segments per Bézier curve when flattening font outlines.
+const CURVE_STEPS: usize = 8;
+
+/// Single-line glyphs: character, advance width and strokes (`|`-separated polylines of `x,y`
+/// grid points). A stroke with one repeated point is a dot.
+const GLYPHS: &[(char, f64, &str)] = &[
+    (' ', 3.0, ""),
+    ('A', 4.0, "0,0 0,4 2,6 4,4 4,0|0,3 4,3"),
+    ('B', 4.0, "0,0 0,6 3,6 4,5 4,4 3,3 0,3|3,3 4,2 4,1 3,0 0,0"),
+    ('C', 4.0, "4,1 3,0 1,0 0,1 0,5 1,6 3,6 4,5"),
+    ('D', 4.0, "0,0 0,6 2,6 4,4 4,2 2,0 0,0"),
+    ('E', 4.0, "4,0 0,0 0,6 4,6|0,3 3,3"),
+    ('F', 4.0, "0,0 0,6 4,6|0,3 3,3"),
+    ('G', 4.0, "4,5 3,6 1,6 0,5 0,1 1,0 3,0 4,1 4,3 2,3"),
+    ('H', 4.0, "0,0 0,6|4,0 4,6|0,3 4,3"),
+    ('I', 2.0, "0,0 2,0|1,0 1,6|0,6 2,6"),
+    ('J', 4.0, "0,1 1,0 3,0 4,1 4,6"),
+    ('K', 4.0, "0,0 0,6|4,6 0,2|1,3 4,0"),
+    ('L', 4.0, "0,6 0,0 4,0"),
+    ('M', 4.0, "0,0 0,6 2,3 4,6 4,0"),
+    ('N', 4.0, "0,0 0,6 4,0 4,6"),
+    ('O', 4.0, "1,0 0,1 0,5 1,6 3,6 4,5 4,1 3,0 1,0"),
+    ('P', 4.0, "0,0 0,6 3,6 4,5 4,4 3,3 0,3"),
+    ('Q', 4.0, "1,0 0,1 0,5 1,6 3,6 4,5 4,1 3,0 1,0|2,2 4,0"),
+    ('R', 4.0, "0,0 0,6 3,6 4,5 4,4 3,3 0,3|2,3 4,0"),
+    ('S', 4.0, "4,5 3,6 1,6 0,5 0,4 1,3 3,3 4,2 4,1 3,0 1,0 0,1"),
+    ('T', 4.0, "0,6 4,6|2,6 2,0"),
+    ('U', 4.0, "0,6 0,1 1,0 3,0 4,1 4,6"),
+    ('V', 4.0, "0,6 2,0 4,6"),
+    ('W', 4.0, "0,6 1,0 2,3 3,0 4,6"),
+    ('X', 4.0, "0,0 4,6|0,6 4,0"),
+    ('Y', 4.0, "0,6 2,3 4,6|2,3 2,0"),
+    ('Z', 4.0, "0,6 4,6 0,0 4,0"),
+    ('0', 4.0, "1,0 0,1 0,5 1,6 3,6 4,5 4,1 3,0 1,0|0,1 4,5"),
+    ('1', 4.0, "1,5 2,6 2,0|1,0 3,0"),
+    ('2', 4.0, "0,5 1,6 3,6 4,5 4,4 0,0 4,0"),
+    (
+        '3',
+        4.0,
+        "0,5 1,6 3,6 4,5 4,4 3,3 4,2 4,1 3,0 1,0 0,1|1,3 3,3",
+    ),
+    ('4', 4.0, "3,0 3,6 0,2 4,2"),
+    ('5', 4.0, "4,6 0,6 0,3 3,3 4,2 4,1 3,0 0,0"),
+    ('6', 4.0, "3,6 1,6 0,5 0,1 1,0 3,0 4,1 4,2 3,3 0,3"),
+    ('7', 4.0, "0,6 4,6 1,0"),
+    (
+        '8',
+        4.0,
+        "1,3 0,4 0,5 1,6 3,6 4,5 4,4 3,3 1,3 0,2 0,1 1,0 3,0 4,1 4,2 3,3",
+    ),
+    ('9', 4.0, "1,0 3,0 4,1 4,5 3,6 1,6 0,5 0,4 1,3 4,3"),
+    ('.', 1.0, "0.5,0 0.5,0"),
+    (',', 1.0, "1,0 0,-1"),
+    (':', 1.0, "0.5,1 0.5,1|0.5,5 0.5,5"),
+    ('\'', 1.0, "0.5,6 0.5,4"),
+    ('!', 1.0, "0.5,6 0.5,2|0.5,0 0.5,0"),
+    ('?', 4.0, "0,5 1,6 3,6 4,5 4,4 2,3 2,2|2,0 2,0"),
+    ('-', 3.0, "0,3 3,3"),
+    ('_', 4.0, "0,0 4,0"),
+    ('+', 4.0, "0,3 4,3|2,1 2,5"),
+    ('=', 4.0, "0,2 4,2|0,4 4,4"),
+    ('*', 4.0, "2,1 2,5|0,2 4,4|0,4 4,2"),
+    ('/', 4.0, "0,0 4,6"),
+    ('#', 4.0, "1,0 1,6|3,0 3,6|0,2 4,2|0,4 4,4"),
+    ('(', 2.0, "2,6 0,4 0,2 2,0"),
+    (')', 2.0, "0,6 2,4 2,2 0,0"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Align {
+    Start,
+    Middle,
+    End,
+}
+
+pub(crate) struct TextSpec {
+    text: String,
+    size: f64,
+    font: Option<Vec<u8>>,
+    stroke: f64,
+    align: Align,
+    valign: Align,
+    rotate: f64,
+    spacing: f64,
+}
+
+pub(crate) fn parse_text_spec(
+    obj: &indexmap::IndexMap<String, Value>,
+    units: &Units,
+) -> Result<TextSpec, OutlineError> {
+    let err = |key: &str, message: String| OutlineError::InvalidText {
+        at: format!("outlines.{key}"),
+        message,
+    };
+    let number = |key: &str| {
+        obj.get(key)
+            .filter(|v| !matches!(v, Value::Null))
+            .map(|v| eval_number(units, v, &format!("outlines.{key}")))
+            .transpose()
+    };
+
+    let text = match obj.get("text") {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Number(n)) => n.to_string(),
+        _ => return Err(err("text", "\"text\" must be a string".to_string())),
+    };
+    let size = number("size")?.unwrap_or(5.0);
+    if size <= 0.0 {
+        return Err(err("size", "must be positive".to_string()));
+    }
+    let font = match obj.get("font") {
+        None | Some(Value::Null) => None,
+        Some(Value::String(s)) if s == "builtin" => None,
+        Some(Value::String(path)) => {
+            let bytes = ergogen_core::vfs::read_bytes(Path::new(path))
+                .map_err(|e| err("font", format!("cannot read \"{path}\": {e}")))?;
+            ttf_parser::Face::parse(&bytes, 0)
+                .map_err(|e| err("font", format!("\"{path}\" is not a usable font: {e}")))?;
+            Some(bytes)
+        }
+        Some(_) => return Err(err("font", "must be a path or \"builtin\"".to_string())),
+    };
+    let align = |key: &str, start: &str, middle: &str, end: &str| match obj.get(key) {
+        None | Some(Value::Null) => Ok(Align::Start),
+        Some(Value::String(s)) if s == start => Ok(Align::Start),
+        Some(Value::String(s)) if s == middle => Ok(Align::Middle),
+        Some(Value::String(s)) if s == end => Ok(Align::End),
+        _ => Err(err(
+            key,
+            format!("must be one of {start}, {middle} or {end}"),
+        )),
+    };
+
+    let stroke = number("stroke")?.unwrap_or(size / 8.0);
+    if stroke <= 0.0 {
+        return Err(err("stroke", "must be positive".to_string()));
+    }
+
+    Ok(TextSpec {
+        text,
+        size,
+        font,
+        stroke,
+        align: align("align", "left", "center", "right")?,
+        valign: align("valign", "baseline", "middle", "top")?,
+        rotate: number("rotate")?.unwrap_or(0.0),
+        spacing: number("spacing")?.unwrap_or(0.0),
+    })
+}
+
+/// The text laid out from `[x, y]` (baseline start, before alignment) along `r` degrees.
+pub(crate) fn text_region(spec: &TextSpec, x: f64, y: f64, r: f64) -> Result<Region, OutlineError> {
+    let (local, width) = match &spec.font {
+        None => builtin_text(spec)?,
+        Some(bytes) => font_text(spec, bytes)?,
+    };
+
+    let dx = match spec.align {
+        Align::Start => 0.0,
+        Align::Middle => -width / 2.0,
+        Align::End => -width,
+    };
+    let dy = match spec.valign {
+        Align::Start => 0.0,
+        Align::Middle => -spec.size / 2.0,
+        Align::End => -spec.size,
+    };
+    let (sin, cos) = (r + spec.rotate).to_radians().sin_cos();
+    let place = |pl: &Polyline<f64>| {
+        let mut pl = pl.clone();
+        for v in &mut pl.vertex_data {
+            let (lx, ly) = (v.x + dx, v.y + dy);
+            v.x = x + lx * cos - ly * sin;
+            v.y = y + lx * sin + ly * cos;
+        }
+        pl
+    };
+    Ok(Region {
+        pos: local.pos.iter().map(place).collect(),
+        neg: local.neg.iter().map(place).collect(),
+    })
+}
+
+/// Builtin glyph strokes thickened by `stroke`, plus the advance width of the whole line.
+fn builtin_text(spec: &TextSpec) -> Result<(Region, f64), OutlineError> {
+    let unit = spec.size / GRID_HEIGHT;
+    let (mut pos, mut neg) = (Vec::new(), Vec::new());
+    let mut cursor = 0.0;
+    let mut width = 0.0;
+    for c in spec.text.chars() {
+        let upper = c.to_ascii_uppercase();
+        let Some((_, advance, strokes)) = GLYPHS.iter().find(|(g, _, _)| *g == upper) else {
+            return Err(OutlineError::InvalidText {
+                at: "outlines.text".to_string(),
+                message: format!("the built-in font has no glyph for '{c}'"),
+            });
+        };
+        let mut capsules = Vec::new();
+        for stroke in strokes.split('|').filter(|s| !s.is_empty()) {
+            let pts: Vec<(f64, f64)> = stroke
+                .split(' ')
+                .map(|p| {
+                    let (gx, gy) = p.split_once(',').expect("glyph point");
+                    let gx: f64 = gx.parse().expect("glyph x");
+                    let gy: f64 = gy.parse().expect("glyph y");
+                    (cursor + gx * unit, gy * unit)
+                })
+                .collect();
+            for pair in pts.windows(2) {
+                capsules.push(primitives::capsule(pair[0], pair[1], spec.stroke));
+            }
+        }
+        // Merging glyph by glyph keeps the pairwise union small.
+        let glyph = Region::union_all(capsules);
+        pos.extend(glyph.pos);
+        neg.extend(glyph.neg);
+        width = cursor + advance * unit;
+        cursor = width + GRID_GAP * unit + spec.spacing;
+    }
+    Ok((merge(pos, neg), width))
+}
+
+/// Islands (which may touch, e.g. glyphs with tight spacing) minus holes, as one valid region.
+fn merge(pos: Vec<Polyline<f64>>, neg: Vec<Polyline<f64>>) -> Region {
+    let mut region = Region::union_all(pos);
+    if !neg.is_empty() {
+        region.subtract_all(&neg);
+    }
+    region
+}
+
+/// Glyph outlines of a TTF/OTF font, scaled so capitals are `size` tall.
+fn font_text(spec: &TextSpec, bytes: &[u8]) -> Result<(Region, f64), OutlineError> {
+    let face = ttf_parser::Face::parse(bytes, 0).map_err(|e| OutlineError::InvalidText {
+        at: "outlines.font".to_string(),
+        message: e.to_string(),
+    })?;
+    let cap = face
+        .capital_height()
+        .filter(|h| *h > 0)
+        .map(f64::from)
+        .unwrap_or(f64::from(face.units_per_em()) * 0.7);
+    let scale = spec.size / cap;
+
+    let (mut pos, mut neg) = (Vec::new(), Vec::new());
+    let mut cursor = 0.0;
+    let mut width = 0.0;
+    for c in spec.text.chars() {
+        let Some(id) = face.glyph_index(c) else {
+            return Err(OutlineError::InvalidText {
+                at: "outlines.text".to_string(),
+                message: format!("the font has no glyph for '{c}'"),
+            });
+        };
+        let mut builder = ContourBuilder {
+            origin: cursor,
+            scale,
+            contours: Vec::new(),
+        };
+        face.outline_glyph(id, &mut builder);
+        let glyph = glyph_region(builder.contours);
+        pos.extend(glyph.pos);
+        neg.extend(glyph.neg);
+
+        let advance = f64::from(face.glyph_hor_advance(id).unwrap_or(0)) * scale;
+        width = cursor + advance;
+        cursor = width + spec.spacing;
+    }
+    Ok((merge(pos, neg), width))
+}
+
+/// Sorts glyph contours into islands and holes: a contour inside an odd number of others is a
+/// hole. This works for both TrueType and CFF winding conventions.
+fn glyph_region(contours: Vec<Vec<[f64; 2]>>) -> Region {
+    let plines: Vec<Polyline<f64>> = contours
+        .into_iter()
+        .filter(|c| c.len() >= 3)
+        .map(|c| {
+            let mut pl = Polyline::new_closed();
+            for [x, y] in c {
+                pl.vertex_data.push(PlineVertex::new(x, y, 0.0));
+            }
+            pl
+        })
+        .collect();
+    let (mut outer, mut holes) = (Vec::new(), Vec::new());
+    for (i, pl) in plines.iter().enumerate() {
+        let [x, y] = [pl.vertex_data[0].x, pl.vertex_data[0].y];
+        let depth = plines
+            .iter()
+            .enumerate()
+            .filter(|(j, other)| *j != i && contains(other, x, y))
+            .count();
+        if depth % 2 == 0 {
+            outer.push(pl.clone());
+        } else {
+            holes.push(pl.clone());
+        }
+    }
+    merge(outer, holes)
+}
+
+/// Even-odd point in polygon test (straight edges only).
+fn contains(pl: &Polyline<f64>, x: f64, y: f64) -> bool {
+    let v = &pl.vertex_data;
+    let mut inside = false;
+    let mut j = v.len() - 1;
+    for i in 0..v.len() {
+        let (a, b) = (&v[i], &v[j]);
+        if (a.y > y) != (b.y > y) && x < (b.x - a.x) * (y - a.y) / (b.y - a.y) + a.x {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+/// Collects flattened glyph contours, already scaled and moved to the pen position.
+struct ContourBuilder {
+    origin: f64,
+    scale: f64,
+    contours: Vec<Vec<[f64; 2]>>,
+}
+
+impl ContourBuilder {
+    fn point(&self, x: f32, y: f32) -> [f64; 2] {
+        [
+            self.origin + f64::from(x) * self.scale,
+            f64::from(y) * self.scale,
+        ]
+    }
+
+    fn last(&self) -> [f64; 2] {
+        self.contours
+            .last()
+            .and_then(|c| c.last())
+            .copied()
+            .unwrap_or([self.origin, 0.0])
+    }
+
+    fn push(&mut self, p: [f64; 2]) {
+        if let Some(c) = self.contours.last_mut() {
+            c.push(p);
+        }
+    }
+}
+
+impl ttf_parser::OutlineBuilder for ContourBuilder {
+    fn move_to(&mut self, x: f32, y: f32) {
+        let p = self.point(x, y);
+        self.contours.push(vec![p]);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let p = self.point(x, y);
+        self.push(p);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let (p0, p1, p2) = (self.last(), self.point(x1, y1), self.point(x, y));
+        for i in 1..=CURVE_STEPS {
+            let t = i as f64 / CURVE_STEPS as f64;
+            let u = 1.0 - t;
+            self.push([
+                u * u * p0[0] + 2.0 * u * t * p1[0] + t * t * p2[0],
+                u * u * p0[1] + 2.0 * u * t * p1[1] + t * t * p2[1],
+            ]);
+        }
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let (p0, p1, p2, p3) = (
+            self.last(),
+            self.point(x1, y1),
+            self.point(x2, y2),
+            self.point(x, y),
+        );
+        for i in 1..=CURVE_STEPS {
+            let t = i as f64 / CURVE_STEPS as f64;
+            let u = 1.0 - t;
+            let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+            self.push([
+                a * p0[0] + b * p1[0] + c * p2[0] + d * p3[0],
+                a * p0[1] + b * p1[1] + c * p2[1] + d * p3[1],
+            ]);
+        }
+    }
+
+    fn close(&mut self) {
+        // Contours end on their start point; drop the duplicate so the polyline closes cleanly.
+        if let Some(c) = self.contours.last_mut()
+            && c.len() > 1
+            && c.first() == c.last()
+        {
+            c.pop();
+        }
+    }
+}
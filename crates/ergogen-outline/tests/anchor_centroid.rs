@@ -0,0 +1,51 @@
+use ergogen_outline::generate_outline_region_from_yaml_str;
+
+const YAML: &str = r#"
+points:
+  zones:
+    matrix:
+outlines:
+  board:
+    - what: rectangle
+      size: [40, 20]
+      adjust.shift: [10, 0]
+    - what: rectangle
+      size: [20, 20]
+      adjust.shift: [20, 20]
+  marker:
+    - what: circle
+      radius: 1
+      adjust.aggregate:
+        method: centroid
+        outline: board
+  selfish:
+    - what: circle
+      radius: 1
+      adjust.aggregate:
+        method: centroid
+        outline: selfish
+"#;
+
+#[test]
+fn centroid_of_a_generated_outline() {
+    // 800mm² centred on [10, 0] plus 400mm² centred on [20, 20].
+    let region = generate_outline_region_from_yaml_str(YAML, "board").unwrap();
+    let [x, y] = region.centroid().unwrap();
+    assert!(
+        (x - 40.0 / 3.0).abs() < 1e-6 && (y - 20.0 / 3.0).abs() < 1e-6,
+        "[{x}, {y}]"
+    );
+
+    let marker = generate_outline_region_from_yaml_str(YAML, "marker").unwrap();
+    let [mx, my] = marker.centroid().unwrap();
+    assert!(
+        (mx - x).abs() < 1e-3 && (my - y).abs() < 1e-3,
+        "[{mx}, {my}]"
+    );
+}
+
+#[test]
+fn centroid_of_itself_is_a_cycle() {
+    let err = generate_outline_region_from_yaml_str(YAML, "selfish").unwrap_err();
+    assert!(err.to_string().contains("selfish"), "{err}");
+}
@@ -0,0 +1,78 @@
+use ergogen_outline::measure::measure;
+use ergogen_outline::{difference_both, generate_outline_region};
+use ergogen_parser::PreparedConfig;
+
+const OUTLINES: &str = r#"
+points.zones.matrix:
+outlines:
+  outer:
+    - what: rectangle
+      size: 40
+  inner:
+    - what: rectangle
+      size: 30
+  shifted:
+    - what: rectangle
+      size: 40
+      adjust.shift: [20, 0]
+"#;
+
+fn area_of(extra: &str, name: &str) -> (f64, usize, usize) {
+    let prepared = PreparedConfig::from_yaml_str(&format!("{OUTLINES}{extra}")).unwrap();
+    let m = measure(&generate_outline_region(&prepared, name).unwrap());
+    (m.area, m.islands, m.holes)
+}
+
+fn assert_close(got: f64, want: f64) {
+    assert!((got - want).abs() < 1e-6, "got={got} want={want}");
+}
+
+#[test]
+fn xor_of_nested_outlines_is_a_frame() {
+    let (area, islands, holes) = area_of("  frame:\n    - outer\n    - /inner\n", "frame");
+    assert_close(area, 1600.0 - 900.0);
+    assert_eq!((islands, holes), (1, 1));
+
+    // The same through `operation`, in either order.
+    let (area, ..) = area_of(
+        "  frame:\n    - inner\n    - name: outer\n      operation: xor\n",
+        "frame",
+    );
+    assert_close(area, 700.0);
+}
+
+#[test]
+fn xor_of_overlapping_outlines_drops_the_overlap() {
+    let (area, islands, holes) = area_of("  changed:\n    - outer\n    - /shifted\n", "changed");
+    assert_close(area, 2.0 * 20.0 * 40.0);
+    assert_eq!((islands, holes), (2, 0));
+}
+
+#[test]
+fn xor_onto_nothing_is_the_part() {
+    let (area, ..) = area_of("  only:\n    - /outer\n", "only");
+    assert_close(area, 1600.0);
+}
+
+#[test]
+fn difference_both_splits_removed_from_added() {
+    let prepared = PreparedConfig::from_yaml_str(OUTLINES).unwrap();
+    let before = generate_outline_region(&prepared, "outer").unwrap();
+    let after = generate_outline_region(&prepared, "shifted").unwrap();
+    let (removed, added) = difference_both(&before, &after);
+
+    let removed = measure(&removed);
+    assert_close(removed.area, 800.0);
+    assert_close(removed.min_x, -20.0);
+    assert_close(removed.max_x, 0.0);
+    let added = measure(&added);
+    assert_close(added.area, 800.0);
+    assert_close(added.min_x, 20.0);
+    assert_close(added.max_x, 40.0);
+
+    // Nested outlines only lose the ring.
+    let inner = generate_outline_region(&prepared, "inner").unwrap();
+    let (removed, added) = difference_both(&before, &inner);
+    assert_close(measure(&removed).area, 700.0);
+    assert_eq!(measure(&added).islands, 0);
+}
@@ -0,0 +1,176 @@
+use ergogen_geometry::region::Region;
+use ergogen_outline::check::{CheckOptions, Violation, ViolationKind, check_region};
+use ergogen_outline::generate_outline_region_from_yaml_str;
+
+fn generate(parts: &str) -> Region {
+    let yaml = format!(
+        r#"
+points.zones.matrix:
+outlines:
+  part:
+{parts}
+"#
+    );
+    generate_outline_region_from_yaml_str(&yaml, "part").unwrap()
+}
+
+fn check(parts: &str, options: CheckOptions) -> Vec<Violation> {
+    check_region(&generate(parts), &options)
+}
+
+fn assert_at(v: &Violation, x: f64, y: f64, tolerance: f64) {
+    assert!(
+        (v.at[0] - x).abs() < tolerance && (v.at[1] - y).abs() < tolerance,
+        "{v}"
+    );
+}
+
+/// Two 20mm squares joined by a 20mm long, 0.5mm wide neck.
+const DUMBBELL: &str = r#"    - what: rectangle
+      size: 20
+      adjust.shift: [-20, 0]
+    - what: rectangle
+      size: 20
+      adjust.shift: [20, 0]
+    - what: rectangle
+      size: [20, 0.5]"#;
+
+#[test]
+fn clean_outlines_pass() {
+    let options = CheckOptions {
+        min_feature: 1.5,
+        min_inside_radius: 1.0,
+        min_area: 1.0,
+    };
+    let rounded = "    - what: rectangle\n      size: [40, 20]\n      corner: 2";
+    assert_eq!(check(rounded, options), vec![]);
+    // Limits of 0 turn the checks off.
+    assert_eq!(check(DUMBBELL, CheckOptions::default()), vec![]);
+}
+
+#[test]
+fn thin_features_are_found_once_with_their_width() {
+    let options = CheckOptions {
+        min_feature: 1.0,
+        ..Default::default()
+    };
+    let violations = check(DUMBBELL, options);
+    assert_eq!(violations.len(), 1, "{violations:?}");
+    let ViolationKind::ThinFeature { width } = violations[0].kind else {
+        panic!("{:?}", violations[0]);
+    };
+    assert!((width - 0.5).abs() < 1e-6, "{width}");
+    assert_at(&violations[0], 0.0, 0.0, 1.0);
+
+    // The neck is fine for a thinner tool, and the right-angled corners never count.
+    let options = CheckOptions {
+        min_feature: 0.4,
+        ..Default::default()
+    };
+    assert_eq!(check(DUMBBELL, options), vec![]);
+}
+
+#[test]
+fn thin_walls_around_holes_are_found() {
+    let parts = r#"    - what: rectangle
+      size: 20
+    - what: circle
+      radius: 9.7
+      operation: subtract"#;
+    let options = CheckOptions {
+        min_feature: 0.5,
+        ..Default::default()
+    };
+    let violations = check(parts, options);
+    assert!(!violations.is_empty());
+    for v in &violations {
+        let ViolationKind::ThinFeature { width } = v.kind else {
+            panic!("{v:?}");
+        };
+        assert!((width - 0.3).abs() < 0.01, "{v}");
+    }
+}
+
+#[test]
+fn inside_corners_need_the_bit_radius() {
+    let options = CheckOptions {
+        min_inside_radius: 1.0,
+        ..Default::default()
+    };
+    // The neck meets the squares at four sharp inside corners.
+    let violations = check(DUMBBELL, options);
+    assert_eq!(violations.len(), 4, "{violations:?}");
+    for v in &violations {
+        assert_eq!(v.kind, ViolationKind::TightCorner { radius: 0.0 });
+        assert!((v.at[0].abs() - 10.0).abs() < 1e-6 && (v.at[1].abs() - 0.25).abs() < 1e-6);
+    }
+
+    // Holes are inside corners all the way round: a bit of radius 1 cannot cut a 1mm hole.
+    let hole = |radius: f64| {
+        format!(
+            "    - what: rectangle\n      size: 20\n    - what: circle\n      radius: {radius}\n      operation: subtract"
+        )
+    };
+    assert_eq!(check(&hole(2.0), options), vec![]);
+    let violations = check(&hole(0.5), options);
+    assert!(!violations.is_empty());
+    for v in &violations {
+        assert_eq!(v.kind, ViolationKind::TightCorner { radius: 0.5 });
+        assert!((v.at[0].hypot(v.at[1]) - 0.5).abs() < 1e-6, "{v}");
+    }
+}
+
+#[test]
+fn tiny_islands_and_holes_are_found() {
+    let parts = r#"    - what: rectangle
+      size: 20
+    - what: circle
+      radius: 0.3
+      operation: subtract
+    - what: rectangle
+      size: 0.5
+      adjust.shift: [30, 0]"#;
+    let options = CheckOptions {
+        min_area: 1.0,
+        ..Default::default()
+    };
+    let violations = check(parts, options);
+    assert_eq!(violations.len(), 2, "{violations:?}");
+    let island = violations
+        .iter()
+        .find(|v| matches!(v.kind, ViolationKind::SmallIsland { .. }))
+        .unwrap();
+    assert_eq!(island.kind, ViolationKind::SmallIsland { area: 0.25 });
+    assert_at(island, 30.0, 0.0, 1e-6);
+    let hole = violations
+        .iter()
+        .find(|v| matches!(v.kind, ViolationKind::SmallHole { .. }))
+        .unwrap();
+    assert_at(hole, 0.0, 0.0, 1e-6);
+}
+
+#[test]
+fn open_and_self_intersecting_contours_are_found() {
+    use cavalier_contours::polyline::PlineSourceMut;
+    use ergogen_geometry::Polyline;
+
+    let mut bowtie = Polyline::new_closed();
+    for (x, y) in [(0.0, 0.0), (10.0, 10.0), (10.0, 0.0), (0.0, 10.0)] {
+        bowtie.add(x, y, 0.0);
+    }
+    let mut open = Polyline::new();
+    open.add(20.0, 0.0, 0.0);
+    open.add(30.0, 0.0, 0.0);
+    let region = Region::from_pos(vec![bowtie, open]);
+
+    let violations = check_region(&region, &CheckOptions::default());
+    assert_eq!(violations.len(), 2, "{violations:?}");
+    assert_eq!(violations[0].kind, ViolationKind::OpenContour);
+    assert_at(&violations[0], 20.0, 0.0, 1e-9);
+    assert_eq!(violations[1].kind, ViolationKind::SelfIntersection);
+    assert_at(&violations[1], 5.0, 5.0, 1e-9);
+    assert_eq!(
+        violations[1].to_string(),
+        "self-intersection at (5.000, 5.000)"
+    );
+}
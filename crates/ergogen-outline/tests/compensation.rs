@@ -0,0 +1,162 @@
+use cavalier_contours::polyline::PlineSource;
+use ergogen_geometry::Polyline;
+use ergogen_geometry::region::Region;
+use ergogen_outline::compensation::{CornerRelief, ExportOptions, compensate, export_options};
+use ergogen_outline::generate_outline_region;
+use ergogen_parser::PreparedConfig;
+
+/// A 40mm plate with a `hole` subtracted, exported with `export`.
+fn prepare(hole: &str, export: &str) -> PreparedConfig {
+    let yaml = format!(
+        r#"
+points.zones.matrix:
+outlines:
+  plate:
+    - what: rectangle
+      size: 40
+    - what: rectangle
+      size: {hole}
+      operation: subtract
+export:
+  plate:
+{export}
+"#
+    );
+    PreparedConfig::from_yaml_str(&yaml).unwrap()
+}
+
+fn exported(prepared: &PreparedConfig) -> (Region, Region) {
+    let region = generate_outline_region(prepared, "plate").unwrap();
+    let options = export_options(prepared, "plate").unwrap();
+    let cut = compensate(&region, &options).unwrap();
+    (region, cut)
+}
+
+fn extents(pl: &Polyline<f64>) -> (f64, f64, f64, f64) {
+    let e = pl.extents().unwrap();
+    (e.min_x, e.min_y, e.max_x, e.max_y)
+}
+
+fn assert_close(got: f64, want: f64) {
+    assert!((got - want).abs() < 1e-6, "got={got} want={want}");
+}
+
+fn has_vertex(pl: &Polyline<f64>, x: f64, y: f64) -> bool {
+    pl.vertex_data
+        .iter()
+        .any(|v| (v.x - x).abs() < 1e-6 && (v.y - y).abs() < 1e-6)
+}
+
+#[test]
+fn kerf_grows_the_part_and_shrinks_the_holes() {
+    let prepared = prepare("14", "    kerf: 0.2");
+    let (region, cut) = exported(&prepared);
+
+    // The design stays as it is.
+    assert_eq!(extents(&region.pos[0]), (-20.0, -20.0, 20.0, 20.0));
+    assert_eq!(extents(&region.neg[0]), (-7.0, -7.0, 7.0, 7.0));
+
+    let (x0, y0, x1, y1) = extents(&cut.pos[0]);
+    assert_close(x1 - x0, 40.2);
+    assert_close(y1 - y0, 40.2);
+    assert_eq!(cut.neg.len(), 1);
+    let (x0, y0, x1, y1) = extents(&cut.neg[0]);
+    assert_close(x1 - x0, 13.8);
+    assert_close(y1 - y0, 13.8);
+}
+
+#[test]
+fn kerf_and_tool_diameter_add_up() {
+    let options = ExportOptions {
+        kerf: 0.1,
+        tool_diameter: 3.175,
+        inside_corner_relief: CornerRelief::None,
+    };
+    assert_close(options.offset(), 1.6375);
+    let prepared = prepare("14", "    kerf: 0.1\n    tool_diameter: 3.175");
+    assert_eq!(export_options(&prepared, "plate").unwrap(), options);
+    // Other outlines are exported as designed.
+    assert_eq!(
+        export_options(&prepared, "other").unwrap(),
+        ExportOptions::default()
+    );
+}
+
+#[test]
+fn dogbones_reach_diagonally_into_inside_corners() {
+    let prepared = prepare(
+        "14",
+        "    tool_diameter: 2\n    inside_corner_relief: dogbone",
+    );
+    let (_, cut) = exported(&prepared);
+    let hole = &cut.neg[0];
+    // The bit (radius 1) touches every corner of the hole from its center on the diagonal.
+    let reach = 7.0 - 0.5f64.sqrt();
+    for (sx, sy) in [(1.0, 1.0), (-1.0, 1.0), (-1.0, -1.0), (1.0, -1.0)] {
+        assert!(has_vertex(hole, sx * reach, sy * reach), "{sx} {sy}");
+    }
+    let (x0, _, x1, _) = extents(hole);
+    assert_close(x1 - x0, 2.0 * reach);
+    // The outer corners are convex and just get rounded.
+    let (x0, _, x1, _) = extents(&cut.pos[0]);
+    assert_close(x1 - x0, 42.0);
+}
+
+#[test]
+fn tbones_notch_across_the_short_side() {
+    let prepared = prepare(
+        "[20, 6]",
+        "    tool_diameter: 2\n    inside_corner_relief: tbone",
+    );
+    let (_, cut) = exported(&prepared);
+    let hole = &cut.neg[0];
+    // The bit stays against the long sides and reaches the ends of the slot.
+    let (x0, y0, x1, y1) = extents(hole);
+    assert_close(x0, -10.0);
+    assert_close(x1, 10.0);
+    assert_close(y0, -2.0);
+    assert_close(y1, 2.0);
+    for (sx, sy) in [(1.0, 1.0), (-1.0, 1.0), (-1.0, -1.0), (1.0, -1.0)] {
+        assert!(has_vertex(hole, sx * 10.0, sy * 2.0), "{sx} {sy}");
+    }
+}
+
+#[test]
+fn reliefs_also_notch_inside_corners_of_the_outer_contour() {
+    let yaml = r#"
+points.zones.matrix:
+outlines:
+  plate:
+    - what: rectangle
+      size: [40, 20]
+      adjust.shift: [0, -10]
+    - what: rectangle
+      size: [20, 40]
+      adjust.shift: [-10, 0]
+export:
+  plate:
+    tool_diameter: 2
+    inside_corner_relief: dogbone
+"#;
+    let prepared = PreparedConfig::from_yaml_str(yaml).unwrap();
+    let (_, cut) = exported(&prepared);
+    // The L has one inside corner, at the origin.
+    let reach = 0.5f64.sqrt();
+    assert!(has_vertex(&cut.pos[0], reach, reach));
+}
+
+#[test]
+fn invalid_export_options_are_errors() {
+    let err = |export: &str| {
+        export_options(&prepare("14", export), "plate")
+            .unwrap_err()
+            .to_string()
+    };
+    assert!(err("    kerf: -0.1").contains("must not be negative"));
+    assert!(err("    inside_corner_relief: dogbone").contains("needs a tool_diameter"));
+    assert!(
+        err("    tool_diameter: 2\n    inside_corner_relief: mousebite")
+            .contains("unknown inside_corner_relief \"mousebite\"")
+    );
+    assert!(err("    offset: 1").contains("unknown option \"offset\""));
+}
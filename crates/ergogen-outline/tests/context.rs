@@ -0,0 +1,101 @@
+use std::path::PathBuf;
+
+use cavalier_contours::polyline::PlineSource;
+use ergogen_geometry::region::Region;
+use ergogen_outline::{OutlineContext, OutlineError, generate_outline_region};
+use ergogen_parser::PreparedConfig;
+
+fn workspace_root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .and_then(|p| p.parent())
+        .expect("workspace root")
+        .to_path_buf()
+}
+
+fn vertices(region: &Region) -> Vec<Vec<(f64, f64, f64)>> {
+    region
+        .pos
+        .iter()
+        .chain(&region.neg)
+        .map(|pl| pl.vertex_data.iter().map(|v| (v.x, v.y, v.bulge)).collect())
+        .collect()
+}
+
+fn assert_same(a: &Region, b: &Region, name: &str) {
+    assert_eq!(
+        (a.pos.len(), a.neg.len()),
+        (b.pos.len(), b.neg.len()),
+        "{name}"
+    );
+    assert_eq!(vertices(a), vertices(b), "{name}");
+}
+
+#[test]
+fn shared_outlines_match_separately_generated_ones() {
+    let yaml =
+        std::fs::read_to_string(workspace_root().join("fixtures/upstream/fixtures/big.yaml"))
+            .unwrap();
+    let prepared = PreparedConfig::from_yaml_str(&yaml).unwrap();
+    let names: Vec<String> = prepared
+        .canonical
+        .get_path("outlines")
+        .and_then(|v| v.as_map())
+        .unwrap()
+        .keys()
+        .cloned()
+        .collect();
+    assert!(!names.is_empty());
+
+    let ctx = OutlineContext::new(&prepared);
+    // Twice, so the second round comes from the cache.
+    for _ in 0..2 {
+        for name in &names {
+            let shared = ctx.region(name).unwrap();
+            let separate = generate_outline_region(&prepared, name).unwrap();
+            assert_same(&shared, &separate, name);
+        }
+    }
+}
+
+#[test]
+fn measurements_resolve_once_for_the_whole_context() {
+    let yaml = r#"
+points.zones.matrix:
+outlines:
+  board:
+    - what: rectangle
+      size: [40, 20]
+  tab:
+    - what: rectangle
+      size: [outlines.board.width / 4, 5]
+"#;
+    let prepared = PreparedConfig::from_yaml_str(yaml).unwrap();
+    let ctx = OutlineContext::new(&prepared);
+    assert_eq!(ctx.prepared().units.get("outlines.board.width"), Some(40.0));
+    // The config itself is left alone.
+    assert_eq!(prepared.units.get("outlines.board.width"), None);
+    let tab = ctx.region("tab").unwrap();
+    let e = tab.pos[0].extents().unwrap();
+    assert_eq!(e.max_x - e.min_x, 10.0);
+}
+
+#[test]
+fn failures_are_not_cached() {
+    let yaml = r#"
+points.zones.matrix:
+outlines:
+  a:
+    - b
+  b:
+    - a
+"#;
+    let prepared = PreparedConfig::from_yaml_str(yaml).unwrap();
+    let ctx = OutlineContext::new(&prepared);
+    for _ in 0..2 {
+        assert!(matches!(
+            ctx.region("a"),
+            Err(OutlineError::OutlineCycle { .. })
+        ));
+    }
+}
@@ -0,0 +1,95 @@
+use cavalier_contours::polyline::PlineSource;
+use ergogen_geometry::region::Region;
+use ergogen_outline::generate_outline_region_from_yaml_str;
+
+/// An L made of two rectangles: five convex corners and one concave corner at [0, 5].
+fn generate(modifier: &str) -> Result<Region, ergogen_outline::OutlineError> {
+    let yaml = format!(
+        r#"
+points:
+  zones:
+    matrix:
+      columns:
+        a:
+      rows:
+        r:
+outlines:
+  board:
+    - what: rectangle
+      size: [20, 10]
+    - what: rectangle
+      size: [10, 20]
+      adjust.shift: [5, 5]
+  case:
+    - what: outline
+      name: board
+{modifier}
+"#
+    );
+    generate_outline_region_from_yaml_str(&yaml, "case")
+}
+
+fn arcs(region: &Region) -> Vec<f64> {
+    region.pos[0]
+        .vertex_data
+        .iter()
+        .filter(|v| v.bulge != 0.0)
+        .map(|v| v.bulge)
+        .collect()
+}
+
+#[test]
+fn fillet_only_convex_corners() {
+    let region = generate("      fillet: { radius: 2, corners: convex }").unwrap();
+    let arcs = arcs(&region);
+    assert_eq!(arcs.len(), 5);
+    assert!(arcs.iter().all(|b| *b > 0.0));
+    // Each 90° convex corner loses (1 - π/4) r².
+    let lost = 5.0 * (1.0 - std::f64::consts::FRAC_PI_4) * 4.0;
+    assert!((region.pos[0].area() - (300.0 - lost)).abs() < 1e-6);
+}
+
+#[test]
+fn fillet_only_concave_corners() {
+    let region = generate("      fillet: { radius: 2, corners: concave }").unwrap();
+    let arcs = arcs(&region);
+    assert_eq!(arcs.len(), 1);
+    assert!(arcs[0] < 0.0);
+}
+
+#[test]
+fn max_angle_and_indices_narrow_the_selection() {
+    let region = generate("      fillet: { radius: 2, max_angle: 80 }").unwrap();
+    assert!(arcs(&region).is_empty());
+
+    let region = generate("      fillet: { radius: 2, indices: [0] }").unwrap();
+    assert_eq!(arcs(&region).len(), 1);
+}
+
+#[test]
+fn corners_near_points() {
+    // The key sits at the origin; its closest corner is the concave one.
+    let region = generate("      fillet: { radius: 2, near: matrix_a_r }").unwrap();
+    assert_eq!(arcs(&region), vec![(-std::f64::consts::FRAC_PI_8).tan()]);
+
+    let region = generate("      fillet: { radius: 2, near: matrix_a_r, within: 12 }").unwrap();
+    assert_eq!(arcs(&region).len(), 4);
+
+    let err = generate("      fillet: { radius: 2, near: nope }").unwrap_err();
+    assert!(err.to_string().contains("unknown point"), "{err}");
+}
+
+#[test]
+fn chamfer_cuts_every_corner() {
+    let region = generate("      chamfer: 1").unwrap();
+    assert_eq!(region.pos[0].vertex_data.len(), 12);
+    assert!(arcs(&region).is_empty());
+    // Five convex corners lose a 0.5mm² triangle each, the concave corner gains one.
+    assert!((region.pos[0].area() - (300.0 - 4.0 * 0.5)).abs() < 1e-6);
+}
+
+#[test]
+fn plain_fillet_keeps_its_convex_only_rounding() {
+    let region = generate("      fillet: 2").unwrap();
+    assert_eq!(arcs(&region).len(), 5);
+}
@@ -0,0 +1,175 @@
+use cavalier_contours::polyline::PlineSource;
+use ergogen_geometry::region::Region;
+use ergogen_outline::generate_outline_region_from_yaml_str;
+
+fn generate(keys: &str, part: &str) -> Result<Region, ergogen_outline::OutlineError> {
+    let yaml = format!(
+        r#"
+points:
+  zones:
+    matrix:
+{keys}
+outlines:
+  cut:
+    - what: {part}
+"#
+    );
+    generate_outline_region_from_yaml_str(&yaml, "cut")
+}
+
+const ONE_KEY: &str = "      columns.only:\n      rows.home:";
+
+fn area(region: &Region) -> f64 {
+    let pos: f64 = region.pos.iter().map(|pl| pl.area().abs()).sum();
+    let neg: f64 = region.neg.iter().map(|pl| pl.area().abs()).sum();
+    pos - neg
+}
+
+fn extents(pl: &ergogen_geometry::Polyline<f64>) -> (f64, f64, f64, f64) {
+    let e = pl.extents().unwrap();
+    (e.min_x, e.min_y, e.max_x, e.max_y)
+}
+
+fn assert_close(got: f64, want: f64) {
+    assert!((got - want).abs() < 1e-6, "got={got} want={want}");
+}
+
+#[test]
+fn mx_cutout_shrinks_by_the_kerf() {
+    let region = generate(ONE_KEY, "switch_cutout\n      where: true").unwrap();
+    assert_close(area(&region), 196.0);
+    let region = generate(ONE_KEY, "switch_cutout\n      where: true\n      kerf: 0.2").unwrap();
+    assert_close(area(&region), 13.8 * 13.8);
+}
+
+#[test]
+fn notched_profiles_add_side_or_top_notches() {
+    let notched = generate(
+        ONE_KEY,
+        "switch_cutout\n      where: true\n      profile: mx_notched",
+    )
+    .unwrap();
+    assert_close(area(&notched), 196.0 + 4.0 * 0.8 * 3.1);
+    let (x0, y0, x1, y1) = extents(&notched.pos[0]);
+    assert_close(x1 - x0, 15.6);
+    assert_close(y1 - y0, 14.0);
+
+    let opening = generate(
+        ONE_KEY,
+        "switch_cutout\n      where: true\n      profile: mx_opening",
+    )
+    .unwrap();
+    let (x0, y0, x1, y1) = extents(&opening.pos[0]);
+    assert_close(x1 - x0, 14.0);
+    assert_close(y1 - y0, 15.6);
+}
+
+#[test]
+fn other_profiles_have_their_own_sizes() {
+    for (profile, w, h) in [
+        ("alps", 15.5, 12.8),
+        ("choc", 13.8, 13.8),
+        ("choc_v2", 14.0, 14.0),
+        ("choc_mini", 13.5, 12.5),
+    ] {
+        let region = generate(
+            ONE_KEY,
+            &format!("switch_cutout\n      where: true\n      profile: {profile}"),
+        )
+        .unwrap();
+        let (x0, y0, x1, y1) = extents(&region.pos[0]);
+        assert_close(x1 - x0, w);
+        assert_close(y1 - y0, h);
+    }
+}
+
+#[test]
+fn cutouts_turn_with_the_key() {
+    let region = generate(
+        "      columns.only:\n      rows.home:\n      key.rotate: 45",
+        "switch_cutout\n      where: true",
+    )
+    .unwrap();
+    let (x0, _, x1, _) = extents(&region.pos[0]);
+    assert_close(x1 - x0, 14.0 * 2f64.sqrt());
+}
+
+#[test]
+fn stabilizers_follow_the_key_width() {
+    // A 1u key gets nothing, the 2u and 6.25u keys get a pair each.
+    let keys = r#"      columns:
+        one:
+        two:
+          key.width: 2u - 1
+          key.spread: 1.5u
+        space:
+          key.width: 6.25u - 1
+          key.spread: 4.125u
+      rows.home:"#;
+    let region = generate(keys, "stabilizer_cutout\n      where: true").unwrap();
+    assert_eq!(region.pos.len(), 4);
+    let mut centers: Vec<f64> = region
+        .pos
+        .iter()
+        .map(|pl| {
+            let (x0, _, x1, _) = extents(pl);
+            (x0 + x1) / 2.0
+        })
+        .collect();
+    centers.sort_by(f64::total_cmp);
+    // `key.spread` is each column's distance from the previous one.
+    let two = 1.5 * 19.0;
+    let space = two + 4.125 * 19.0;
+    assert_close(centers[0], two - 11.9);
+    assert_close(centers[1], two + 11.9);
+    assert_close(centers[2], space - 50.0);
+    assert_close(centers[3], space + 50.0);
+}
+
+#[test]
+fn explicit_stabilizer_size_overrides_the_key_width() {
+    let region = generate(
+        ONE_KEY,
+        "stabilizer_cutout\n      where: true\n      size: 7\n      kerf: 0.1",
+    )
+    .unwrap();
+    assert_eq!(region.pos.len(), 2);
+    let (x0, y0, x1, y1) = extents(&region.pos[1]);
+    assert_close((x0 + x1) / 2.0, 57.15);
+    assert_close(x1 - x0, 6.65);
+    assert_close(y1 - y0, 13.9);
+}
+
+#[test]
+fn cutouts_can_be_subtracted_from_a_plate() {
+    let yaml = r#"
+points:
+  zones:
+    matrix:
+      columns.only:
+      rows.home:
+outlines:
+  plate:
+    - what: rectangle
+      size: [40, 40]
+    - what: switch_cutout
+      where: true
+      operation: subtract
+"#;
+    let region = generate_outline_region_from_yaml_str(yaml, "plate").unwrap();
+    assert_eq!(region.neg.len(), 1);
+    assert_close(area(&region), 1600.0 - 196.0);
+}
+
+#[test]
+fn invalid_cutouts_are_errors() {
+    let err = generate(ONE_KEY, "switch_cutout\n      profile: topre").unwrap_err();
+    assert!(err.to_string().contains("unknown switch profile"), "{err}");
+    let err = generate(ONE_KEY, "switch_cutout\n      kerf: -1").unwrap_err();
+    assert!(err.to_string().contains("kerf"), "{err}");
+    let err = generate(ONE_KEY, "stabilizer_cutout\n      size: 4").unwrap_err();
+    assert!(
+        err.to_string().contains("no stabilizer spacing for 4u"),
+        "{err}"
+    );
+}
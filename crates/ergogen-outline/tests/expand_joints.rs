@@ -0,0 +1,117 @@
+use cavalier_contours::polyline::PlineSource;
+use ergogen_geometry::region::Region;
+use ergogen_outline::generate_outline_region_from_yaml_str;
+
+fn bbox(region: &Region) -> (f64, f64, f64, f64) {
+    let mut b = (
+        f64::INFINITY,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        f64::NEG_INFINITY,
+    );
+    for v in region.pos.iter().flat_map(|pl| pl.vertex_data.iter()) {
+        b = (b.0.min(v.x), b.1.min(v.y), b.2.max(v.x), b.3.max(v.y));
+    }
+    b
+}
+
+fn area(region: &Region) -> f64 {
+    let pos: f64 = region.pos.iter().map(|pl| pl.area().abs()).sum();
+    let neg: f64 = region.neg.iter().map(|pl| pl.area().abs()).sum();
+    pos - neg
+}
+
+fn generate(expand: &str, joints: &str) -> (Region, Region, Region) {
+    let yaml = format!(
+        r#"
+points:
+  zones:
+    matrix:
+outlines:
+  diamond:
+    - what: rectangle
+      size: 10
+      adjust.rotate: 45
+  frame:
+    - what: rectangle
+      size: 40
+    - what: rectangle
+      size: 20
+      operation: subtract
+  islands:
+    - what: rectangle
+      size: 10
+      adjust.rotate: 45
+    - what: rectangle
+      size: 10
+      adjust: {{ shift: [30, 0], rotate: 30 }}
+  d_out:
+    - what: outline
+      name: diamond
+      expand: {expand}
+      joints: {joints}
+  f_out:
+    - what: outline
+      name: frame
+      expand: {expand}
+      joints: {joints}
+  i_out:
+    - what: outline
+      name: islands
+      expand: {expand}
+      joints: {joints}
+"#
+    );
+    let g = |name| generate_outline_region_from_yaml_str(&yaml, name).unwrap();
+    (g("d_out"), g("f_out"), g("i_out"))
+}
+
+fn assert_close(got: f64, want: f64) {
+    assert!((got - want).abs() < 1e-6, "got={got} want={want}");
+}
+
+#[test]
+fn pointy_joints_meet_at_miters_on_rotated_shapes() {
+    let half = 5.0 * std::f64::consts::SQRT_2;
+    let (diamond, _, islands) = generate("2", "pointy");
+    assert_close(bbox(&diamond).2, half + 2.0 * std::f64::consts::SQRT_2);
+    assert_eq!(diamond.pos[0].vertex_data.len(), 4);
+    assert!(diamond.pos[0].vertex_data.iter().all(|v| v.bulge == 0.0));
+    assert_close(area(&diamond), 14.0 * 14.0);
+
+    assert_eq!(islands.pos.len(), 2);
+    assert_close(area(&islands), 2.0 * 14.0 * 14.0);
+}
+
+#[test]
+fn beveled_joints_cut_at_the_round_joint_tangent() {
+    let half = 5.0 * std::f64::consts::SQRT_2;
+    let (diamond, _, _) = generate("2", "beveled");
+    assert_close(bbox(&diamond).2, half + 2.0);
+    assert_eq!(diamond.pos[0].vertex_data.len(), 8);
+    assert!(diamond.pos[0].vertex_data.iter().all(|v| v.bulge == 0.0));
+}
+
+#[test]
+fn round_joints_keep_arcs() {
+    let (diamond, _, _) = generate("2", "round");
+    assert!(diamond.pos[0].vertex_data.iter().any(|v| v.bulge != 0.0));
+}
+
+#[test]
+fn negative_expand_shapes_holes_with_the_joints() {
+    // Shrinking the frame grows its hole; the hole's corners get the joints.
+    let (_, pointy, _) = generate("-2", "pointy");
+    assert_eq!(pointy.pos.len(), 1);
+    assert_eq!(pointy.neg.len(), 1);
+    assert_eq!(pointy.neg[0].vertex_data.len(), 4);
+    assert_close(area(&pointy), 36.0 * 36.0 - 24.0 * 24.0);
+
+    let (_, round, _) = generate("-2", "round");
+    assert!(round.neg[0].vertex_data.iter().any(|v| v.bulge != 0.0));
+    // Rounded hole corners leave more material.
+    assert!(area(&round) > area(&pointy));
+
+    let (diamond, _, _) = generate("-1", "pointy");
+    assert_close(area(&diamond), 8.0 * 8.0);
+}
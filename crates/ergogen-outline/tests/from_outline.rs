@@ -0,0 +1,160 @@
+use ergogen_layout::BindSource;
+use ergogen_outline::measure::measure;
+use ergogen_outline::sample::parse_points_with_outline_samples;
+use ergogen_outline::{OutlineContext, generate_outline_region};
+use ergogen_parser::PreparedConfig;
+
+fn config(sampling: &str) -> PreparedConfig {
+    let yaml = format!(
+        r#"
+points:
+  zones:
+    matrix:
+      key.width: 10
+    screws:
+      from_outline:
+        name: board
+{sampling}
+outlines:
+  board:
+    - what: rectangle
+      size: [100, 60]
+  holes:
+    - what: circle
+      where: /^screws_/
+      radius: 1
+"#
+    );
+    PreparedConfig::from_yaml_str(&yaml).unwrap()
+}
+
+fn screws(prepared: &PreparedConfig) -> Vec<(f64, f64, f64)> {
+    let points = parse_points_with_outline_samples(prepared).unwrap();
+    points
+        .iter()
+        .filter(|(name, _)| name.starts_with("screws_"))
+        .map(|(_, p)| (p.x, p.y, p.r))
+        .collect()
+}
+
+#[test]
+fn corners_are_inset_from_both_edges() {
+    let prepared = config("        corners: true\n        inset: 5");
+    let mut got: Vec<(f64, f64)> = screws(&prepared).iter().map(|&(x, y, _)| (x, y)).collect();
+    got.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let want = [(-45.0, -25.0), (-45.0, 25.0), (45.0, -25.0), (45.0, 25.0)];
+    assert_eq!(got.len(), 4, "{got:?}");
+    for (g, w) in got.iter().zip(want) {
+        assert!(
+            (g.0 - w.0).abs() < 1e-9 && (g.1 - w.1).abs() < 1e-9,
+            "{got:?}"
+        );
+    }
+}
+
+#[test]
+fn count_spreads_points_evenly_and_follows_the_edge() {
+    let prepared = config("        count: 8\n        inset: 2");
+    let got = screws(&prepared);
+    assert_eq!(got.len(), 8);
+    for (x, y, r) in got {
+        // Every point sits 2mm inside an edge, rotated along it (counter-clockwise).
+        let on_bottom = (y + 28.0).abs() < 1e-9 && r == 0.0;
+        let on_right = (x - 48.0).abs() < 1e-9 && r == 90.0;
+        let on_top = (y - 28.0).abs() < 1e-9 && r == 180.0;
+        let on_left = (x + 48.0).abs() < 1e-9 && r == -90.0;
+        assert!(
+            on_bottom || on_right || on_top || on_left,
+            "({x}, {y}, {r})"
+        );
+    }
+}
+
+#[test]
+fn spacing_rounds_up_to_cover_the_perimeter() {
+    // 320mm perimeter at most 50mm apart.
+    let prepared = config("        spacing: 50");
+    assert_eq!(screws(&prepared).len(), 7);
+}
+
+#[test]
+fn sampled_points_are_usable_in_where() {
+    let prepared = config("        corners: true\n        inset: 5");
+    let region = generate_outline_region(&prepared, "holes").unwrap();
+    assert_eq!(region.pos.len(), 4);
+}
+
+#[test]
+fn sampled_outline_keeps_its_size() {
+    let yaml = r#"
+points:
+  zones:
+    matrix:
+      columns:
+        a:
+        b:
+      rows:
+        bottom:
+        top:
+    screws:
+      from_outline:
+        name: board
+        corners: true
+        inset: 3
+outlines:
+  board:
+    - what: rectangle
+      where: true
+      size: 19
+  frame:
+    - what: rectangle
+      where: /.*/
+      size: 19
+  plate:
+    - name: frame
+      expand: 1
+  dots:
+    - what: circle
+      where: true
+      radius: 1
+"#;
+    let prepared = PreparedConfig::from_yaml_str(yaml).unwrap();
+    let ctx = OutlineContext::new(&prepared);
+    let board = measure(&ctx.region("board").unwrap());
+    assert!((board.width - 38.0).abs() < 1e-9, "{board:?}");
+    assert_eq!(ctx.points().unwrap().len(), 8);
+    // The trace covers the screws too, with the binds they were placed with.
+    let trace = ctx.bind_trace().unwrap();
+    assert_eq!(trace.len(), 8);
+    assert!(
+        trace["screws_1"]
+            .iter()
+            .all(|side| side.amount == 0.0 && side.source == BindSource::None)
+    );
+    // `where: true` skips the screws.
+    assert_eq!(ctx.region("dots").unwrap().pos.len(), 4);
+
+    // An explicit selection picks them up, except in an outline the screws are sampled from.
+    let yaml = yaml.replace("name: board", "name: plate");
+    let prepared = PreparedConfig::from_yaml_str(&yaml).unwrap();
+    let ctx = OutlineContext::new(&prepared);
+    let frame = measure(&ctx.region("frame").unwrap());
+    assert!((frame.width - 38.0).abs() < 1e-9, "{frame:?}");
+    let board = measure(&ctx.region("board").unwrap());
+    assert!((board.width - 38.0).abs() < 1e-9, "{board:?}");
+}
+
+#[test]
+fn unknown_outline_is_an_error() {
+    let yaml = r#"
+points:
+  zones:
+    screws:
+      from_outline:
+        name: missing
+        count: 3
+"#;
+    let prepared = PreparedConfig::from_yaml_str(yaml).unwrap();
+    let err = parse_points_with_outline_samples(&prepared).unwrap_err();
+    assert!(err.to_string().contains("unknown outline"), "{err}");
+}
@@ -0,0 +1,160 @@
+use std::path::PathBuf;
+
+use ergogen_export::dxf::{NormalizeOptions, compare_files_semantic};
+use ergogen_export::dxf_geom::dxf_from_region;
+use ergogen_outline::generate_outline_region_from_yaml_str;
+use ergogen_outline::measure::{Measurements, measure};
+
+fn fixture_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .and_then(|p| p.parent())
+        .expect("workspace root")
+        .join("fixtures/hull")
+}
+
+fn fixture_yaml() -> String {
+    std::fs::read_to_string(fixture_dir().join("hull_algorithms.yaml")).unwrap()
+}
+
+fn measured(yaml: &str, name: &str) -> Measurements {
+    measure(&generate_outline_region_from_yaml_str(yaml, name).unwrap())
+}
+
+fn assert_close(got: f64, want: f64) {
+    assert!((got - want).abs() < 1e-6, "got={got} want={want}");
+}
+
+#[test]
+fn hull_algorithms_match_golden_dxfs() {
+    let dir = fixture_dir();
+    let yaml = fixture_yaml();
+    let opts = NormalizeOptions::default();
+    let update = std::env::var("UPDATE_GOLDENS").is_ok();
+    let out_dir = std::env::temp_dir().join("ergogen-hull-goldens");
+    std::fs::create_dir_all(&out_dir).unwrap();
+
+    for name in ["concave", "convex", "alpha_wide", "alpha_split", "rounded"] {
+        let region = generate_outline_region_from_yaml_str(&yaml, name).unwrap();
+        let dxf = dxf_from_region(&region).unwrap().normalize(opts).unwrap();
+        let fname = format!("hull_algorithms___outlines_{name}_dxf.dxf");
+        let golden_path = dir.join(&fname);
+        let out_path = if update {
+            golden_path.clone()
+        } else {
+            out_dir.join(&fname)
+        };
+        std::fs::write(&out_path, dxf.to_dxf_string(opts).unwrap()).unwrap();
+        if let Err(e) = compare_files_semantic(&out_path, &golden_path, opts) {
+            panic!("DXF mismatch for {name}: {e:?}\n  ours: {out_path:?}");
+        }
+    }
+}
+
+#[test]
+fn convex_and_wide_alpha_hulls_have_no_notches() {
+    let yaml = fixture_yaml();
+    let concave = measured(&yaml, "concave");
+    let convex = measured(&yaml, "convex");
+    assert!(convex.area > concave.area);
+    // A convex outline fills its minimum rectangle only along hull edges, and has no inside
+    // corners for the concave hull's notches.
+    assert_eq!((convex.islands, convex.holes), (1, 0));
+
+    let wide = measured(&yaml, "alpha_wide");
+    assert_close(wide.area, convex.area);
+    assert_close(wide.perimeter, convex.perimeter);
+}
+
+#[test]
+fn tight_alpha_hulls_leave_gaps_open() {
+    let yaml = fixture_yaml();
+    // The two halves are more than 2 * 30 apart.
+    let split = measured(&yaml, "alpha_split");
+    assert_eq!((split.islands, split.holes), (2, 0));
+
+    // Keys around an empty middle leave a hole. The 1mm gaps between keys close, and so do the
+    // hole's corners, where the keys' corners are 1mm apart.
+    let names: Vec<String> = (1..=5)
+        .flat_map(|c| (1..=5).map(move |r| (c, r)))
+        .filter(|&(c, r)| c == 1 || c == 5 || r == 1 || r == 5)
+        .map(|(c, r)| format!("matrix_c{c}_r{r}"))
+        .collect();
+    let ring = format!(
+        r#"
+points.zones.matrix:
+  columns:
+    c1:
+    c2.rows: {{r2.skip: true, r3.skip: true, r4.skip: true}}
+    c3.rows: {{r2.skip: true, r3.skip: true, r4.skip: true}}
+    c4.rows: {{r2.skip: true, r3.skip: true, r4.skip: true}}
+    c5:
+  rows:
+    r1:
+    r2:
+    r3:
+    r4:
+    r5:
+outlines:
+  ring:
+    - what: hull
+      algorithm: alpha
+      alpha: 13
+      points: [{}]
+"#,
+        names.join(", ")
+    );
+    let m = measured(&ring, "ring");
+    assert_eq!((m.islands, m.holes), (1, 1));
+    assert_close(m.area, 94.0 * 94.0 - (58.0 * 58.0 - 4.0 * 0.5));
+}
+
+#[test]
+fn rounded_hulls_grow_by_the_radius_with_round_corners() {
+    let yaml = fixture_yaml();
+    let concave = measured(&yaml, "concave");
+    let rounded = measured(&yaml, "rounded");
+    assert_close(rounded.min_x, concave.min_x - 3.0);
+    assert_close(rounded.max_x, concave.max_x + 3.0);
+    assert_close(rounded.max_y, concave.max_y + 3.0);
+
+    let region = generate_outline_region_from_yaml_str(&yaml, "rounded").unwrap();
+    let arcs = region.pos[0]
+        .vertex_data
+        .iter()
+        .filter(|v| v.bulge != 0.0)
+        .count();
+    assert!(arcs >= 4, "{arcs}");
+}
+
+#[test]
+fn hulls_are_deterministic() {
+    let yaml = fixture_yaml();
+    for name in ["convex", "alpha_split", "rounded"] {
+        let a = generate_outline_region_from_yaml_str(&yaml, name).unwrap();
+        let b = generate_outline_region_from_yaml_str(&yaml, name).unwrap();
+        let vertices = |r: &ergogen_geometry::region::Region| {
+            r.pos
+                .iter()
+                .flat_map(|pl| pl.vertex_data.iter().map(|v| (v.x, v.y, v.bulge)))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vertices(&a), vertices(&b), "{name}");
+    }
+}
+
+#[test]
+fn invalid_hull_options_are_errors() {
+    let err = |options: &str| {
+        let yaml = format!(
+            "points.zones.matrix:\noutlines:\n  h:\n    - what: hull\n      points: [matrix]\n{options}"
+        );
+        generate_outline_region_from_yaml_str(&yaml, "h")
+            .unwrap_err()
+            .to_string()
+    };
+    assert!(err("      algorithm: blob\n").contains("unknown hull algorithm \"blob\""));
+    assert!(err("      algorithm: alpha\n").contains("\"alpha\" is required"));
+    assert!(err("      algorithm: alpha\n      alpha: 0\n").contains("must be positive"));
+    assert!(err("      algorithm: rounded\n      radius: -1\n").contains("must not be negative"));
+}
@@ -0,0 +1,223 @@
+use std::f64::consts::PI;
+
+use cavalier_contours::polyline::PlineSource;
+use ergogen_geometry::region::Region;
+use ergogen_outline::generate_outline_region_from_yaml_str;
+use indexmap::IndexMap;
+
+fn dxf(entities: &[String]) -> String {
+    format!(
+        "0\nSECTION\n2\nENTITIES\n{}0\nENDSEC\n0\nEOF\n",
+        entities.concat()
+    )
+}
+
+fn line(a: (f64, f64), b: (f64, f64)) -> String {
+    format!(
+        "0\nLINE\n10\n{}\n20\n{}\n11\n{}\n21\n{}\n",
+        a.0, a.1, b.0, b.1
+    )
+}
+
+/// Every test registers the same files, so parallel tests never see a different map.
+fn register_files() {
+    let square = [
+        line((0.0, 0.0), (10.0, 0.0)),
+        // Reversed and out of order on purpose.
+        line((0.0, 10.0), (10.0, 10.0)),
+        line((10.0, 10.0), (10.0, 0.0)),
+        line((0.0, 0.0), (0.0, 10.0)),
+        "0\nCIRCLE\n10\n5\n20\n5\n40\n2\n".to_string(),
+    ];
+    let half_disk = [
+        line((0.0, 5.0), (0.0, -5.0)),
+        "0\nARC\n10\n0\n20\n0\n40\n5\n50\n270\n51\n90\n".to_string(),
+    ];
+    let triangle =
+        ["0\nLWPOLYLINE\n70\n1\n10\n0\n20\n0\n10\n10\n20\n0\n10\n0\n20\n5\n".to_string()];
+    let open = [
+        line((0.0, 0.0), (10.0, 0.0)),
+        line((10.0, 0.0), (10.0, 10.0)),
+    ];
+    let text = ["0\nTEXT\n10\n0\n20\n0\n1\nhi\n".to_string()];
+
+    let mut files = IndexMap::new();
+    files.insert("import/square.dxf".to_string(), dxf(&square));
+    files.insert("import/half_disk.dxf".to_string(), dxf(&half_disk));
+    files.insert("import/triangle.dxf".to_string(), dxf(&triangle));
+    files.insert("import/open.dxf".to_string(), dxf(&open));
+    files.insert("import/text.dxf".to_string(), dxf(&text));
+    files.insert(
+        "import/shapes.svg".to_string(),
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 40 10">
+  <path id="box" d="M0 0 H10 V10 H0 Z" />
+  <path fill="none" d='M20,0 a5,5 0 0 1 10,0 z'/>
+</svg>"#
+            .to_string(),
+    );
+    files.insert(
+        "import/curve.path".to_string(),
+        "M0 0 L10 0 C10 5 5 10 0 10 Z".to_string(),
+    );
+    files.insert(
+        "import/ellipse.path".to_string(),
+        "M0 0 A10 5 0 0 1 20 0 Z".to_string(),
+    );
+    ergogen_core::vfs::set(files);
+}
+
+fn generate(part: &str) -> Result<Region, ergogen_outline::OutlineError> {
+    register_files();
+    let yaml = format!(
+        r#"
+points:
+  zones:
+    matrix:
+      columns.only:
+      rows.home:
+  mirror:
+    ref: matrix_only_home
+    distance: 30
+outlines:
+  shape:
+    - what: import
+{part}
+"#
+    );
+    generate_outline_region_from_yaml_str(&yaml, "shape")
+}
+
+fn area(region: &Region) -> f64 {
+    let pos: f64 = region.pos.iter().map(|pl| pl.area().abs()).sum();
+    let neg: f64 = region.neg.iter().map(|pl| pl.area().abs()).sum();
+    pos - neg
+}
+
+fn bbox(region: &Region) -> (f64, f64, f64, f64) {
+    let mut b = (
+        f64::INFINITY,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        f64::NEG_INFINITY,
+    );
+    for pl in &region.pos {
+        let e = pl.extents().unwrap();
+        b = (
+            b.0.min(e.min_x),
+            b.1.min(e.min_y),
+            b.2.max(e.max_x),
+            b.3.max(e.max_y),
+        );
+    }
+    b
+}
+
+fn has_arcs(region: &Region) -> bool {
+    region
+        .pos
+        .iter()
+        .chain(&region.neg)
+        .any(|pl| pl.vertex_data.iter().any(|v| v.bulge != 0.0))
+}
+
+fn assert_close(got: f64, want: f64) {
+    assert!((got - want).abs() < 1e-6, "got={got} want={want}");
+}
+
+#[test]
+fn dxf_lines_are_chained_and_nested_circles_become_holes() {
+    let region = generate("      file: import/square.dxf").unwrap();
+    assert_eq!((region.pos.len(), region.neg.len()), (1, 1));
+    assert_close(area(&region), 100.0 - 4.0 * PI);
+    assert!(has_arcs(&region));
+}
+
+#[test]
+fn dxf_arcs_are_kept_as_arcs() {
+    let region = generate("      file: import/half_disk.dxf").unwrap();
+    assert_close(area(&region), 12.5 * PI);
+    assert!(has_arcs(&region));
+    let (x0, y0, x1, y1) = bbox(&region);
+    assert_close(x0, 0.0);
+    assert_close(x1, 5.0);
+    assert_close(y0, -5.0);
+    assert_close(y1, 5.0);
+}
+
+#[test]
+fn svg_paths_flip_y_and_keep_circular_arcs() {
+    let region = generate("      file: import/shapes.svg").unwrap();
+    assert_eq!(region.pos.len(), 2);
+    assert_close(area(&region), 100.0 + 12.5 * PI);
+    assert!(has_arcs(&region));
+    // SVG y points down: the box drawn from y=0 to y=10 ends up below the origin, the arc bulges
+    // upwards.
+    let (x0, y0, x1, y1) = bbox(&region);
+    assert_close(x0, 0.0);
+    assert_close(y0, -10.0);
+    assert_close(x1, 30.0);
+    assert_close(y1, 5.0);
+}
+
+#[test]
+fn bare_path_data_flattens_beziers_and_ellipses() {
+    let region = generate("      file: import/curve.path").unwrap();
+    assert_eq!(region.pos.len(), 1);
+    let a = area(&region);
+    assert!(a > 50.0 && a < 100.0, "area={a}");
+
+    // Elliptical arcs are flattened; this one sweeps above the x axis once y is flipped.
+    let region = generate("      file: import/ellipse.path").unwrap();
+    let a = area(&region);
+    assert!((a - 25.0 * PI).abs() < 1.0, "area={a}");
+    assert!(!has_arcs(&region));
+    let (_, y0, _, y1) = bbox(&region);
+    assert_close(y0, 0.0);
+    assert!((y1 - 5.0).abs() < 0.1, "top={y1}");
+}
+
+#[test]
+fn where_adjust_and_scale_place_the_shape() {
+    let region = generate(
+        "      file: import/triangle.dxf\n      where: matrix_only_home\n      adjust.shift: [5, 5]\n      scale: 2",
+    )
+    .unwrap();
+    assert_close(area(&region), 100.0);
+    let (x0, y0, x1, y1) = bbox(&region);
+    assert_close(x0, 5.0);
+    assert_close(y0, 5.0);
+    assert_close(x1, 25.0);
+    assert_close(y1, 15.0);
+}
+
+#[test]
+fn mirrored_points_mirror_the_shape() {
+    let region = generate("      file: import/triangle.dxf\n      where: true").unwrap();
+    assert_eq!(region.pos.len(), 2);
+    let mut xs: Vec<(f64, f64)> = region
+        .pos
+        .iter()
+        .map(|pl| {
+            let e = pl.extents().unwrap();
+            (e.min_x, e.max_x)
+        })
+        .collect();
+    xs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_close(xs[0].0, 0.0);
+    assert_close(xs[0].1, 10.0);
+    assert_close(xs[1].0, 20.0);
+    assert_close(xs[1].1, 30.0);
+}
+
+#[test]
+fn open_contours_and_unsupported_entities_are_errors() {
+    let err = generate("      file: import/open.dxf").unwrap_err();
+    assert!(err.to_string().contains("open contour"), "{err}");
+    let err = generate("      file: import/text.dxf").unwrap_err();
+    assert!(
+        err.to_string().contains("unsupported entities TEXT"),
+        "{err}"
+    );
+    let err = generate("      file: import/missing.dxf").unwrap_err();
+    assert!(err.to_string().contains("cannot read"), "{err}");
+}
@@ -0,0 +1,134 @@
+use ergogen_outline::measure::{Measurements, measure};
+use ergogen_outline::{OutlineContext, generate_outline_region_from_yaml_str};
+use ergogen_parser::PreparedConfig;
+
+fn measured(parts: &str) -> Measurements {
+    let yaml = format!(
+        r#"
+points.zones.matrix:
+outlines:
+  part:
+{parts}
+"#
+    );
+    measure(&generate_outline_region_from_yaml_str(&yaml, "part").unwrap())
+}
+
+fn assert_close(got: f64, want: f64) {
+    assert!((got - want).abs() < 1e-6, "got={got} want={want}");
+}
+
+#[test]
+fn rectangles_with_holes_are_measured() {
+    let m = measured(
+        r#"    - what: rectangle
+      size: [40, 20]
+      adjust.shift: [10, 5]
+    - what: rectangle
+      size: 10
+      operation: subtract
+      adjust.shift: [10, 5]
+    - what: rectangle
+      size: 2
+      adjust.shift: [50, 0]"#,
+    );
+    assert_eq!(
+        (m.min_x, m.min_y, m.max_x, m.max_y),
+        (-10.0, -5.0, 51.0, 15.0)
+    );
+    assert_eq!((m.width, m.height), (61.0, 20.0));
+    assert_close(m.area, 800.0 - 100.0 + 4.0);
+    assert_close(m.perimeter, 120.0 + 40.0 + 8.0);
+    assert_eq!((m.islands, m.holes), (2, 1));
+}
+
+#[test]
+fn the_enclosing_rectangle_follows_rotated_outlines() {
+    let m = measured(
+        r#"    - what: rectangle
+      size: [30, 10]
+      adjust.rotate: 30"#,
+    );
+    assert_close(m.min_rect_width, 30.0);
+    assert_close(m.min_rect_height, 10.0);
+    assert_close(m.min_rect_angle, 30.0);
+    // The bounding box is larger than the part.
+    assert!(m.width > 30.0 && m.height > 10.0);
+
+    let m = measured("    - what: circle\n      radius: 5");
+    assert_close(m.area, 25.0 * std::f64::consts::PI);
+    assert!((m.min_rect_width - 10.0).abs() < 1e-3, "{m:?}");
+}
+
+#[test]
+fn outlines_can_use_the_measurements_of_others() {
+    let yaml = r#"
+points.zones.matrix:
+outlines:
+  board:
+    - what: rectangle
+      size: [40, 20]
+  screws:
+    - what: circle
+      radius: 1
+      adjust.shift: [outlines.board.width / 2 - 4, outlines.board.max_y - 4]
+  both:
+    - board
+    - what: rectangle
+      size: outlines.screws.max_x
+      operation: subtract
+"#;
+    let screws = measure(&generate_outline_region_from_yaml_str(yaml, "screws").unwrap());
+    assert_close(screws.min_x, 15.0);
+    assert_close(screws.max_y, 7.0);
+    // `both` needs `screws`, which needs `board`.
+    let both = measure(&generate_outline_region_from_yaml_str(yaml, "both").unwrap());
+    assert_close(both.area, 800.0 - 17.0 * 17.0);
+}
+
+#[test]
+fn bad_measurement_references_are_errors() {
+    let err = |outlines: &str| {
+        let yaml = format!("points.zones.matrix:\noutlines:\n{outlines}");
+        generate_outline_region_from_yaml_str(&yaml, "a")
+            .unwrap_err()
+            .to_string()
+    };
+    let unknown_field = "  a:\n    - what: rectangle\n      size: outlines.b.depth\n  b:\n    - what: rectangle\n      size: 1\n";
+    assert!(err(unknown_field).contains("outlines.b.depth"));
+    let unknown_outline = "  a:\n    - what: rectangle\n      size: outlines.c.width\n";
+    assert!(err(unknown_outline).contains("outlines.c"));
+    let cycle = "  a:\n    - what: rectangle\n      size: outlines.b.width\n  b:\n    - what: rectangle\n      size: outlines.a.width\n";
+    assert!(err(cycle).contains("cycle"), "{}", err(cycle));
+}
+
+#[test]
+fn failed_measurements_only_fail_what_uses_them() {
+    let yaml = r#"
+points.zones.matrix:
+outlines:
+  board:
+    - what: rectangle
+      size: [outlines.missing.width, 20]
+  frame:
+    - board
+  plate:
+    - what: rectangle
+      size: [outlines.tab.width * 2, 10]
+  tab:
+    - what: rectangle
+      size: 5
+cases.bottom:
+  - name: plate
+    extrude: outlines.missing.height
+"#;
+    let prepared = PreparedConfig::from_yaml_str(yaml).unwrap();
+    let ctx = OutlineContext::new(&prepared);
+    assert_close(measure(&ctx.region("plate").unwrap()).width, 10.0);
+    for name in ["board", "frame"] {
+        let err = ctx.region(name).unwrap_err().to_string();
+        assert!(err.contains("outlines.missing"), "{name}: {err}");
+    }
+    let cases = prepared.canonical.get_path("cases").unwrap();
+    assert!(ctx.check_measurements(cases).is_err());
+}
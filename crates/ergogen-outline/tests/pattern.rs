@@ -0,0 +1,160 @@
+use cavalier_contours::polyline::PlineSource;
+use ergogen_geometry::region::Region;
+use ergogen_outline::generate_outline_region_from_yaml_str;
+
+fn generate(part: &str, outline: &str) -> Result<Region, ergogen_outline::OutlineError> {
+    let yaml = format!(
+        r#"
+points:
+  zones:
+    matrix:
+outlines:
+  case:
+    - what: rectangle
+      size: [100, 60]
+  keepout:
+    - what: circle
+      radius: 10
+  lightening:
+    - what: pattern
+      name: case
+{part}
+  shell:
+    - name: case
+    - name: lightening
+      operation: subtract
+"#
+    );
+    generate_outline_region_from_yaml_str(&yaml, outline)
+}
+
+fn area(region: &Region) -> f64 {
+    let pos: f64 = region.pos.iter().map(|pl| pl.area().abs()).sum();
+    let neg: f64 = region.neg.iter().map(|pl| pl.area().abs()).sum();
+    pos - neg
+}
+
+/// Every vertex of every cutout lies within `[-hx, hx] x [-hy, hy]`.
+fn assert_inside(region: &Region, hx: f64, hy: f64) {
+    for pl in &region.pos {
+        let e = pl.extents().unwrap();
+        assert!(
+            e.min_x >= -hx - 1e-6 && e.max_x <= hx + 1e-6,
+            "x {}..{}",
+            e.min_x,
+            e.max_x
+        );
+        assert!(
+            e.min_y >= -hy - 1e-6 && e.max_y <= hy + 1e-6,
+            "y {}..{}",
+            e.min_y,
+            e.max_y
+        );
+    }
+}
+
+#[test]
+fn honeycomb_keeps_the_margin_from_the_boundary() {
+    let region = generate(
+        "      style: honeycomb\n      size: 6\n      wall: 1.5\n      margin: 3",
+        "lightening",
+    )
+    .unwrap();
+    assert!(region.pos.len() > 40, "{}", region.pos.len());
+    assert_inside(&region, 47.0, 27.0);
+    // Whole cells are hexagons 6mm across the flats.
+    let hexagons = region
+        .pos
+        .iter()
+        .filter(|pl| pl.vertex_count() == 6)
+        .count();
+    assert!(hexagons > 30, "{hexagons}");
+    let whole = region.pos.iter().find(|pl| pl.vertex_count() == 6).unwrap();
+    let e = whole.extents().unwrap();
+    assert!((e.max_x - e.min_x - 6.0).abs() < 1e-6);
+}
+
+#[test]
+fn keepouts_stay_solid_with_a_margin() {
+    let region = generate(
+        "      size: 4\n      wall: 1\n      margin: 2\n      keepout: keepout",
+        "lightening",
+    )
+    .unwrap();
+    for pl in &region.pos {
+        for v in &pl.vertex_data {
+            assert!(v.x.hypot(v.y) >= 12.0 - 1e-6, "({}, {})", v.x, v.y);
+        }
+    }
+}
+
+#[test]
+fn circles_keep_their_arcs() {
+    let region = generate(
+        "      style: circles\n      size: 5\n      wall: 2\n      margin: 2",
+        "lightening",
+    )
+    .unwrap();
+    assert_inside(&region, 48.0, 28.0);
+    let full = region
+        .pos
+        .iter()
+        .filter(|pl| (pl.area().abs() - std::f64::consts::PI * 6.25).abs() < 1e-6)
+        .count();
+    assert!(full > 40, "{full}");
+}
+
+#[test]
+fn slots_are_staggered_stadiums() {
+    let region = generate(
+        "      style: slots\n      size: 4\n      length: 16\n      wall: 2\n      margin: 2",
+        "lightening",
+    )
+    .unwrap();
+    assert_inside(&region, 48.0, 28.0);
+    let whole = region
+        .pos
+        .iter()
+        .filter(|pl| {
+            let e = pl.extents().unwrap();
+            (e.max_x - e.min_x - 16.0).abs() < 1e-6 && (e.max_y - e.min_y - 4.0).abs() < 1e-6
+        })
+        .count();
+    assert!(whole > 10, "{whole}");
+}
+
+#[test]
+fn voronoi_is_deterministic_per_seed() {
+    let part = |seed: u32| {
+        format!("      style: voronoi\n      size: 8\n      wall: 1.5\n      seed: {seed}")
+    };
+    let a = generate(&part(1), "lightening").unwrap();
+    let b = generate(&part(1), "lightening").unwrap();
+    let c = generate(&part(2), "lightening").unwrap();
+    assert_inside(&a, 48.5, 28.5);
+    assert!(a.pos.len() > 20, "{}", a.pos.len());
+    assert!((area(&a) - area(&b)).abs() < 1e-9);
+    assert!((area(&a) - area(&c)).abs() > 1e-6);
+}
+
+#[test]
+fn cutouts_subtract_from_the_filled_outline() {
+    let lightening = generate("      size: 6\n      wall: 2", "lightening").unwrap();
+    let shell = generate("      size: 6\n      wall: 2", "shell").unwrap();
+    assert!(!shell.neg.is_empty());
+    assert!((area(&shell) - (6000.0 - area(&lightening))).abs() < 1e-3);
+}
+
+#[test]
+fn invalid_patterns_are_errors() {
+    let err = generate("      style: stars", "lightening").unwrap_err();
+    assert!(err.to_string().contains("unknown pattern style"), "{err}");
+    let err = generate("      wall: 0", "lightening").unwrap_err();
+    assert!(err.to_string().contains("wall"), "{err}");
+    let err = generate(
+        "      style: slots\n      size: 5\n      length: 3",
+        "lightening",
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("length"), "{err}");
+}
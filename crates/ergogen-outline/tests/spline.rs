@@ -0,0 +1,165 @@
+use cavalier_contours::polyline::PlineSource;
+use ergogen_geometry::region::Region;
+use ergogen_outline::{OutlineError, generate_outline_region_from_yaml_str};
+
+/// Anchors on the upper half of a radius 10 circle, every 30 degrees.
+fn half_circle() -> Vec<(f64, f64)> {
+    (0..=6)
+        .map(|k| {
+            let a = (30.0 * f64::from(k)).to_radians();
+            (10.0 * a.cos(), 10.0 * a.sin())
+        })
+        .collect()
+}
+
+fn generate(kind: &str, options: &str) -> Result<Region, OutlineError> {
+    let points: String = half_circle()
+        .iter()
+        .map(|(x, y)| format!("            - ref: point\n              shift: [{x}, {y}]\n"))
+        .collect();
+    let yaml = format!(
+        r#"
+points.zones.point:
+outlines:
+  curve:
+    - what: path
+      segments:
+        - type: {kind}
+{options}
+          points:
+{points}"#
+    );
+    generate_outline_region_from_yaml_str(&yaml, "curve")
+}
+
+fn area(region: &Region) -> f64 {
+    region.pos.iter().map(|pl| pl.area().abs()).sum()
+}
+
+fn has_arcs(region: &Region) -> bool {
+    region
+        .pos
+        .iter()
+        .any(|pl| pl.vertex_data.iter().any(|v| v.bulge != 0.0))
+}
+
+fn has_vertex(region: &Region, x: f64, y: f64) -> bool {
+    region.pos[0]
+        .vertex_data
+        .iter()
+        .any(|v| (v.x - x).abs() < 1e-6 && (v.y - y).abs() < 1e-6)
+}
+
+#[test]
+fn catmull_rom_passes_through_every_anchor() {
+    let region = generate("catmull_rom", "").unwrap();
+    assert_eq!(region.pos.len(), 1);
+    assert!(!has_arcs(&region));
+    for (x, y) in half_circle() {
+        assert!(has_vertex(&region, x, y), "({x}, {y})");
+    }
+    // Close to the half disk the anchors sample.
+    let half_disk = std::f64::consts::PI * 50.0;
+    assert!((area(&region) - half_disk).abs() < 2.0, "{}", area(&region));
+}
+
+#[test]
+fn natural_spline_interpolates_with_its_own_tangents() {
+    let natural = generate("natural_spline", "").unwrap();
+    let cr = generate("catmull_rom", "").unwrap();
+    for (x, y) in half_circle() {
+        assert!(has_vertex(&natural, x, y), "({x}, {y})");
+    }
+    assert!((area(&natural) - area(&cr)).abs() > 1e-3);
+    let half_disk = std::f64::consts::PI * 50.0;
+    assert!(
+        (area(&natural) - half_disk).abs() < 2.0,
+        "{}",
+        area(&natural)
+    );
+}
+
+#[test]
+fn b_spline_follows_its_control_polygon() {
+    let region = generate("b_spline", "").unwrap();
+    // Pinned to the ends, but inside the polygon everywhere else.
+    assert!(has_vertex(&region, 10.0, 0.0));
+    assert!(has_vertex(&region, -10.0, 0.0));
+    assert!(!has_vertex(&region, 0.0, 10.0));
+    assert!(area(&region) < 150.0, "{}", area(&region));
+    // The knot between the pieces around the top control point: (P[2] + 4 P[3] + P[4]) / 6.
+    let sin60 = 60f64.to_radians().sin();
+    assert!(has_vertex(&region, 0.0, (40.0 + 20.0 * sin60) / 6.0));
+}
+
+#[test]
+fn full_tension_gives_the_anchor_polygon() {
+    let region = generate("catmull_rom", "          tension: 1").unwrap();
+    // Six 30 degree slices of the inscribed polygon.
+    assert!((area(&region) - 150.0).abs() < 1e-6, "{}", area(&region));
+}
+
+#[test]
+fn tighter_tolerance_uses_more_segments() {
+    let coarse = generate("catmull_rom", "          tolerance: 0.5").unwrap();
+    let fine = generate("catmull_rom", "          tolerance: 0.001").unwrap();
+    assert!(fine.pos[0].vertex_count() > coarse.pos[0].vertex_count());
+}
+
+#[test]
+fn biarcs_keep_the_shape_with_fewer_segments() {
+    let lines = generate("b_spline", "          tolerance: 0.001").unwrap();
+    let arcs = generate(
+        "b_spline",
+        "          tolerance: 0.001\n          arcs: true",
+    )
+    .unwrap();
+    assert!(has_arcs(&arcs));
+    assert!(arcs.pos[0].vertex_count() < lines.pos[0].vertex_count());
+    assert!((area(&arcs) - area(&lines)).abs() < 0.05);
+}
+
+#[test]
+fn invalid_spline_options_are_errors() {
+    let err = generate("catmull_rom", "          tension: 2").unwrap_err();
+    assert!(
+        matches!(err, OutlineError::InvalidSpline { ref at, .. } if at == "outlines.path.segments.0.tension"),
+        "{err}"
+    );
+    let err = generate("b_spline", "          tolerance: 0").unwrap_err();
+    assert!(err.to_string().contains("segments.0.tolerance"), "{err}");
+    let err = generate("natural_spline", "          arcs: 1").unwrap_err();
+    assert!(err.to_string().contains("must be a boolean"), "{err}");
+}
+
+#[test]
+fn splines_continue_from_the_previous_segment() {
+    // The spline starts where the line ended and wiggles through an inflection.
+    let yaml = r#"
+points.zones.point:
+outlines:
+  curve:
+    - what: path
+      segments:
+        - type: line
+          points:
+            - ref: point
+              shift: [0, -10]
+            - ref: point
+              shift: [30, -10]
+        - type: natural_spline
+          arcs: true
+          points:
+            - ref: point
+              shift: [20, 0]
+            - ref: point
+              shift: [10, 5]
+            - ref: point
+              shift: [0, 0]
+"#;
+    let region = generate_outline_region_from_yaml_str(yaml, "curve").unwrap();
+    assert_eq!(region.pos.len(), 1);
+    assert!(has_arcs(&region));
+    assert!(has_vertex(&region, 30.0, -10.0));
+    assert!(has_vertex(&region, 10.0, 5.0));
+}
@@ -0,0 +1,317 @@
+use cavalier_contours::polyline::PlineSource;
+use ergogen_geometry::region::Region;
+use ergogen_outline::generate_outline_region_from_yaml_str;
+
+fn generate(part: &str) -> Result<Region, ergogen_outline::OutlineError> {
+    let yaml = format!(
+        r#"
+points:
+  zones:
+    matrix:
+outlines:
+  label:
+    - what: text
+{part}
+  plate:
+    - what: rectangle
+      size: [40, 20]
+    - what: outline
+      name: label
+      operation: subtract
+"#
+    );
+    generate_outline_region_from_yaml_str(&yaml, "label")
+}
+
+fn area(region: &Region) -> f64 {
+    let pos: f64 = region.pos.iter().map(|pl| pl.area().abs()).sum();
+    let neg: f64 = region.neg.iter().map(|pl| pl.area().abs()).sum();
+    pos - neg
+}
+
+fn bbox(region: &Region) -> (f64, f64, f64, f64) {
+    let mut b = (
+        f64::INFINITY,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+        f64::NEG_INFINITY,
+    );
+    for pl in &region.pos {
+        let e = pl.extents().unwrap();
+        b = (
+            b.0.min(e.min_x),
+            b.1.min(e.min_y),
+            b.2.max(e.max_x),
+            b.3.max(e.max_y),
+        );
+    }
+    b
+}
+
+fn assert_close(got: f64, want: f64) {
+    assert!((got - want).abs() < 1e-6, "got={got} want={want}");
+}
+
+#[test]
+fn builtin_font_strokes_are_closed_shapes() {
+    // 'H' and 'I' are separate islands; 'O' is a ring with a hole.
+    let region = generate("      text: HI\n      size: 6\n      stroke: 0.5").unwrap();
+    assert_eq!(region.pos.len(), 2);
+    assert!(region.neg.is_empty());
+
+    let region = generate("      text: o\n      size: 6\n      stroke: 0.5").unwrap();
+    assert_eq!((region.pos.len(), region.neg.len()), (1, 1));
+
+    // Strokes stick out by half their width around the 6mm cap height.
+    let (_, min_y, _, max_y) = bbox(&region);
+    assert_close(min_y, -0.25);
+    assert_close(max_y, 6.25);
+}
+
+#[test]
+fn alignment_rotation_and_spacing() {
+    let text = "      text: II\n      size: 6\n      stroke: 0.5";
+    let bbox_of = |extra: &str| bbox(&generate(&format!("{text}\n{extra}")).unwrap());
+    let assert_bbox = |got: (f64, f64, f64, f64), want: (f64, f64, f64, f64)| {
+        for (g, w) in [
+            (got.0, want.0),
+            (got.1, want.1),
+            (got.2, want.2),
+            (got.3, want.3),
+        ] {
+            assert_close(g, w);
+        }
+    };
+
+    // Two 2-unit glyphs with a 1-unit gap (one unit being 1mm), plus half the stroke around.
+    assert_bbox(bbox_of(""), (-0.25, -0.25, 5.25, 6.25));
+    assert_bbox(bbox_of("      spacing: 1"), (-0.25, -0.25, 6.25, 6.25));
+    assert_bbox(
+        bbox_of("      align: center\n      valign: middle"),
+        (-2.75, -3.25, 2.75, 3.25),
+    );
+    assert_bbox(
+        bbox_of("      align: right\n      rotate: 90"),
+        (-6.25, -5.25, 0.25, 0.25),
+    );
+
+    let err = generate("      text: II\n      stroke: 0").unwrap_err();
+    assert!(err.to_string().contains("stroke"), "{err}");
+}
+
+#[test]
+fn text_is_boolean_compatible() {
+    let yaml_part = "      text: HI\n      size: 6\n      align: center\n      valign: middle";
+    let label = generate(yaml_part).unwrap();
+    let yaml = format!(
+        r#"
+points:
+  zones:
+    matrix:
+outlines:
+  label:
+    - what: text
+{yaml_part}
+  plate:
+    - what: rectangle
+      size: [40, 20]
+    - what: outline
+      name: label
+      operation: subtract
+"#
+    );
+    let plate = generate_outline_region_from_yaml_str(&yaml, "plate").unwrap();
+    assert_close(area(&plate), 800.0 - area(&label));
+}
+
+#[test]
+fn unknown_builtin_glyph_is_an_error() {
+    let err = generate("      text: \"€\"").unwrap_err();
+    assert!(err.to_string().contains("no glyph"), "{err}");
+}
+
+#[test]
+fn ttf_font_outlines_with_holes() {
+    let path = std::env::temp_dir().join(format!("ergogen-text-{}.ttf", std::process::id()));
+    std::fs::write(&path, boxes_ttf()).unwrap();
+    let region = generate(&format!(
+        "      text: OI\n      size: 7\n      font: {}",
+        path.display()
+    ))
+    .unwrap();
+    std::fs::remove_file(&path).ok();
+
+    // 'O' is a 6x7 box with a 3x4 hole, 'I' a 2x7 bar starting at its 7mm advance.
+    assert_eq!((region.pos.len(), region.neg.len()), (2, 1));
+    assert_close(area(&region), 42.0 - 12.0 + 14.0);
+    assert_close(bbox(&region).2, 9.0);
+}
+
+#[test]
+fn ttf_fonts_load_from_virtual_files() {
+    let font = boxes_ttf();
+    assert!(String::from_utf8(font.clone()).is_err());
+    ergogen_core::vfs::insert_bytes("fonts/boxes.ttf", font);
+    let region = generate("      text: OI\n      size: 7\n      font: fonts/boxes.ttf");
+    ergogen_core::vfs::clear();
+
+    let region = region.unwrap();
+    assert_eq!((region.pos.len(), region.neg.len()), (2, 1));
+    assert_close(area(&region), 42.0 - 12.0 + 14.0);
+}
+
+#[test]
+fn every_builtin_glyph_renders() {
+    let region = generate(
+        "      text: \"ABCDEFGHIJKLMNOPQRSTUVWXYZ 0123456789 .,:'!?-_+=*/#()\"\n      size: 4",
+    )
+    .unwrap();
+    assert!(area(&region) > 0.0);
+}
+
+/// A minimal TrueType font with two box glyphs, 'O' (with a hole) and 'I', 700 units tall.
+fn boxes_ttf() -> Vec<u8> {
+    fn be16(out: &mut Vec<u8>, v: i32) {
+        out.extend_from_slice(&(v as u16).to_be_bytes());
+    }
+    fn glyph(contours: &[&[(i32, i32)]]) -> Vec<u8> {
+        let pts: Vec<(i32, i32)> = contours.iter().flat_map(|c| c.iter().copied()).collect();
+        let mut g = Vec::new();
+        be16(&mut g, contours.len() as i32);
+        be16(&mut g, pts.iter().map(|p| p.0).min().unwrap());
+        be16(&mut g, pts.iter().map(|p| p.1).min().unwrap());
+        be16(&mut g, pts.iter().map(|p| p.0).max().unwrap());
+        be16(&mut g, pts.iter().map(|p| p.1).max().unwrap());
+        let mut end = -1;
+        for c in contours {
+            end += c.len() as i32;
+            be16(&mut g, end);
+        }
+        be16(&mut g, 0); // no instructions
+        g.extend(std::iter::repeat_n(0x01u8, pts.len())); // on-curve, 16-bit deltas
+        let mut last = (0, 0);
+        for p in &pts {
+            be16(&mut g, p.0 - last.0);
+            last.0 = p.0;
+        }
+        for p in &pts {
+            be16(&mut g, p.1 - last.1);
+            last.1 = p.1;
+        }
+        if g.len() % 2 == 1 {
+            g.push(0);
+        }
+        g
+    }
+
+    // TrueType islands run clockwise, holes counter-clockwise.
+    let glyphs = [
+        Vec::new(),
+        glyph(&[
+            &[(0, 0), (0, 700), (600, 700), (600, 0)],
+            &[(150, 150), (450, 150), (450, 550), (150, 550)],
+        ]),
+        glyph(&[&[(0, 0), (0, 700), (200, 700), (200, 0)]]),
+    ];
+    let advances = [500, 700, 300];
+    let chars = [('I', 2), ('O', 1)];
+
+    let mut head = Vec::new();
+    for v in [1, 0, 1, 0, 0, 0, 0x5F0F, 0x3CF5, 0, 1000] {
+        be16(&mut head, v);
+    }
+    head.extend([0u8; 16]); // created, modified
+    for v in [0, 0, 600, 700, 0, 3, 2, 0, 0] {
+        be16(&mut head, v);
+    }
+
+    let mut hhea = Vec::new();
+    for v in [1, 0, 800, -200, 0, 700, 0, 0, 700, 1, 0, 0, 0, 0, 0, 0, 0] {
+        be16(&mut hhea, v);
+    }
+    be16(&mut hhea, glyphs.len() as i32);
+
+    let mut maxp = Vec::new();
+    be16(&mut maxp, 1);
+    be16(&mut maxp, 0);
+    be16(&mut maxp, glyphs.len() as i32);
+    maxp.extend([0u8; 26]);
+
+    let mut hmtx = Vec::new();
+    for a in advances {
+        be16(&mut hmtx, a);
+        be16(&mut hmtx, 0);
+    }
+
+    let mut cmap = Vec::new();
+    for v in [0, 1, 3, 1, 0, 12] {
+        be16(&mut cmap, v);
+    }
+    let seg_count = chars.len() as i32 + 1;
+    let mut sub = Vec::new();
+    for v in [
+        4,
+        16 + 8 * seg_count,
+        0,
+        2 * seg_count,
+        4,
+        1,
+        2 * seg_count - 4,
+    ] {
+        be16(&mut sub, v);
+    }
+    for (c, _) in chars {
+        be16(&mut sub, c as i32);
+    }
+    be16(&mut sub, 0xFFFF);
+    be16(&mut sub, 0);
+    for (c, _) in chars {
+        be16(&mut sub, c as i32);
+    }
+    be16(&mut sub, 0xFFFF);
+    for (c, gid) in chars {
+        be16(&mut sub, gid - c as i32);
+    }
+    be16(&mut sub, 1);
+    for _ in 0..seg_count {
+        be16(&mut sub, 0);
+    }
+    cmap.extend(sub);
+
+    let mut glyf = Vec::new();
+    let mut loca = Vec::new();
+    for g in &glyphs {
+        be16(&mut loca, glyf.len() as i32 / 2);
+        glyf.extend(g);
+    }
+    be16(&mut loca, glyf.len() as i32 / 2);
+
+    let tables: [(&[u8; 4], Vec<u8>); 7] = [
+        (b"cmap", cmap),
+        (b"glyf", glyf),
+        (b"head", head),
+        (b"hhea", hhea),
+        (b"hmtx", hmtx),
+        (b"loca", loca),
+        (b"maxp", maxp),
+    ];
+    let mut font = Vec::new();
+    font.extend(0x0001_0000u32.to_be_bytes());
+    for v in [tables.len() as i32, 64, 2, 48] {
+        be16(&mut font, v);
+    }
+    let mut offset = 12 + 16 * tables.len();
+    for (tag, data) in &tables {
+        font.extend(*tag);
+        font.extend(0u32.to_be_bytes());
+        font.extend((offset as u32).to_be_bytes());
+        font.extend((data.len() as u32).to_be_bytes());
+        offset += data.len().next_multiple_of(4);
+    }
+    for (_, data) in tables {
+        let len = data.len();
+        font.extend(data);
+        font.extend(std::iter::repeat_n(0u8, len.next_multiple_of(4) - len));
+    }
+    font
+}
@@ -0,0 +1,29 @@
+use ergogen_outline::generate_outline_region;
+use ergogen_parser::PreparedConfig;
+
+#[test]
+fn outline_where_accepts_queries() {
+    let yaml = r#"
+points:
+  zones:
+    matrix:
+      columns:
+        a:
+        b:
+        c:
+      rows:
+        bottom:
+        top:
+outlines:
+  picked:
+    - what: rectangle
+      where: row:top and x > 10 order by x desc limit 1
+      size: 10
+"#;
+    let prepared = PreparedConfig::from_yaml_str(yaml).unwrap();
+    let region = generate_outline_region(&prepared, "picked").unwrap();
+    assert_eq!(region.pos.len(), 1);
+    let xs: Vec<f64> = region.pos[0].vertex_data.iter().map(|v| v.x).collect();
+    let min_x = xs.iter().copied().fold(f64::INFINITY, f64::min);
+    assert!((min_x - 33.0).abs() < 1e-9, "min_x={min_x}");
+}
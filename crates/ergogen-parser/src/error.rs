@@ -38,6 +38,9 @@ pub enum Error {
     #[error("parameterization error at \"{at}\": {message}")]
     Parameterize { at: String, message: String },
 
+    #[error("zone template error at \"{at}\": {message}")]
+    ZoneTemplate { at: String, message: String },
+
     #[error("\"units\" and \"variables\" must be YAML mappings")]
     UnitsNotMap,
 
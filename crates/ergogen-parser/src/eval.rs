@@ -33,7 +33,7 @@ fn rewrite_expr_and_context(
             i += 1;
             while i < bytes.len() {
                 let c = bytes[i] as char;
-                if is_ident_continue(c) {
+                if is_ident_continue(c) || is_ident_dot(bytes, i) {
                     i += 1;
                 } else {
                     break;
@@ -139,7 +139,10 @@ fn insert_implicit_multiplication(expr: &str) -> String {
         } else if is_ident_start(c) {
             let start = i;
             i += 1;
-            while i < chars.len() && is_ident_continue(chars[i]) {
+            while i < chars.len()
+                && (is_ident_continue(chars[i])
+                    || (chars[i] == '.' && chars.get(i + 1).is_some_and(|&c| is_ident_start(c))))
+            {
                 i += 1;
             }
             let s = chars[start..i].iter().collect::<String>();
@@ -192,6 +195,11 @@ fn is_ident_continue(c: char) -> bool {
     c.is_ascii_alphanumeric() || c == '_' || c == '$'
 }
 
+/// Dots join identifiers into dotted names like `outlines.board.width`.
+fn is_ident_dot(bytes: &[u8], i: usize) -> bool {
+    bytes[i] == b'.' && bytes.get(i + 1).is_some_and(|&b| is_ident_start(b as char))
+}
+
 fn sanitize_ident(raw: &str) -> String {
     let mut out = String::from("v_");
     for c in raw.chars() {
@@ -8,6 +8,7 @@ mod kle;
 mod prepare;
 mod units;
 mod value;
+mod zone_templates;
 
 pub use config::RawConfig;
 pub use error::Error;
@@ -17,10 +18,11 @@ pub use kle::convert_kle;
 pub use prepare::{PreparedIr, extend_all, inherit, parameterize, unnest};
 pub use units::{UnitEntry, Units};
 pub use value::Value;
+pub use zone_templates::{builtin_zone_templates, expand_zone_templates};
 
 #[derive(Debug, Clone)]
 pub struct PreparedConfig {
-    /// Canonical (preprocessed) configuration: unnest → inherit → parameterize.
+    /// Canonical (preprocessed) configuration: unnest → inherit → zone templates → parameterize.
     pub canonical: Value,
     pub units: Units,
 }
@@ -2,6 +2,7 @@ use indexmap::IndexMap;
 
 use crate::error::Error;
 use crate::value::Value;
+use crate::zone_templates::expand_zone_templates;
 
 pub fn unnest(config: &Value) -> Result<Value, Error> {
     match config {
@@ -299,7 +300,7 @@ fn apply_parameterize(at: &str, val: Value, params: Value, args: Value) -> Resul
     Ok(reparsed)
 }
 
-fn arg_to_replacement_string(v: &Value) -> String {
+pub(crate) fn arg_to_replacement_string(v: &Value) -> String {
     match v {
         Value::Null => "null".to_string(),
         Value::Bool(b) => if *b { "true" } else { "false" }.to_string(),
@@ -335,7 +336,7 @@ impl PreparedIr {
     }
 
     pub fn from_value(raw: &Value) -> Result<Self, Error> {
-        let canonical = parameterize(&inherit(&unnest(raw)?)?)?;
+        let canonical = parameterize(&expand_zone_templates(&inherit(&unnest(raw)?)?)?)?;
         Ok(Self { canonical })
     }
 }
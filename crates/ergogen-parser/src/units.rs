@@ -211,4 +211,23 @@ mod tests {
         let units = Units::parse(cfg.units.as_ref(), cfg.variables.as_ref()).unwrap();
         assert_eq!(units.get("bar"), Some(20.0));
     }
+
+    #[test]
+    fn dotted_names_are_single_variables() {
+        let units = Units::parse(None, None)
+            .unwrap()
+            .with_extra_vars([("outlines.board.width".to_string(), 100.0)]);
+        assert_eq!(
+            units.eval("<dotted>", "outlines.board.width / 2").unwrap(),
+            50.0
+        );
+        assert_eq!(
+            units.eval("<dotted>", "2outlines.board.width").unwrap(),
+            200.0
+        );
+        // Dots in numbers are still decimal points.
+        assert_eq!(units.eval("<dotted>", "u.5").unwrap(), 9.5);
+        let err = units.eval("<dotted>", "outlines.case.width").unwrap_err();
+        assert!(err.to_string().contains("\"outlines.case.width\""), "{err}");
+    }
 }
@@ -0,0 +1,164 @@
+//! Zone templates: `template: <name>` in a zone expands into an ordinary zone definition.
+//!
+//! A template file has `params` (name → default) and a `zone` body in which `"{{name}}"` stands for
+//! a parameter. A string that is exactly one placeholder takes the parameter's value as is;
+//! placeholders inside longer strings are replaced textually. The zone's own `params` override
+//! the defaults and its remaining keys are merged over the expanded body.
+//!
+//! Templates are looked up as `<name>.yaml` in `points.templates_search_paths` and the `templates`
+//! folder (virtual files first, then disk), before falling back to the built-in set.
+
+use std::path::PathBuf;
+
+use ergogen_core::vfs;
+use indexmap::IndexMap;
+
+use crate::error::Error;
+use crate::prepare::{arg_to_replacement_string, extend_all, unnest};
+use crate::value::Value;
+
+const BUILTIN: &[(&str, &str)] = &[
+    ("columns_5x3", include_str!("../templates/columns_5x3.yaml")),
+    ("thumb_arc_3", include_str!("../templates/thumb_arc_3.yaml")),
+    ("thumb_row_2", include_str!("../templates/thumb_row_2.yaml")),
+];
+
+/// Names of the templates that ship with Ergogen.
+pub fn builtin_zone_templates() -> impl Iterator<Item = &'static str> {
+    BUILTIN.iter().map(|(name, _)| *name)
+}
+
+/// Replaces every zone that names a `template` with the expanded template.
+pub fn expand_zone_templates(config: &Value) -> Result<Value, Error> {
+    let Some(Value::Map(zones)) = config.get_path("points.zones") else {
+        return Ok(config.clone());
+    };
+    if !zones
+        .values()
+        .any(|z| matches!(z, Value::Map(m) if m.contains_key("template")))
+    {
+        return Ok(config.clone());
+    }
+
+    let mut search_paths = match config.get_path("points.templates_search_paths") {
+        Some(Value::Seq(seq)) => seq
+            .iter()
+            .filter_map(|v| match v {
+                Value::String(s) => Some(PathBuf::from(s)),
+                _ => None,
+            })
+            .collect(),
+        Some(Value::String(s)) => vec![PathBuf::from(s)],
+        _ => Vec::new(),
+    };
+    search_paths.push(PathBuf::from("templates"));
+
+    let mut expanded = IndexMap::new();
+    for (zone_name, zone) in zones {
+        let at = format!("points.zones.{zone_name}");
+        let zone = match zone {
+            Value::Map(m) if m.contains_key("template") => expand_zone(m, &search_paths, &at)?,
+            other => other.clone(),
+        };
+        expanded.insert(zone_name.clone(), zone);
+    }
+
+    let mut out = config.clone();
+    if let Some(Value::Map(points)) = out.as_map_mut().and_then(|m| m.get_mut("points")) {
+        points.insert("zones".to_string(), Value::Map(expanded));
+    }
+    Ok(out)
+}
+
+fn expand_zone(
+    zone: &IndexMap<String, Value>,
+    search_paths: &[PathBuf],
+    at: &str,
+) -> Result<Value, Error> {
+    let err = |message: String| Error::ZoneTemplate {
+        at: at.to_string(),
+        message,
+    };
+    let Some(Value::String(name)) = zone.get("template") else {
+        return Err(err("template must be a name".to_string()));
+    };
+    let source = load_template(name, search_paths)
+        .ok_or_else(|| err(format!("unknown zone template \"{name}\"")))?;
+    let template = unnest(&Value::from_yaml_str(&source)?)?;
+
+    let mut params = match template.get_path("params") {
+        None | Some(Value::Null) => IndexMap::new(),
+        Some(Value::Map(m)) => m.clone(),
+        Some(_) => {
+            return Err(err(format!(
+                "params of template \"{name}\" must be an object"
+            )));
+        }
+    };
+    match zone.get("params") {
+        None | Some(Value::Null) => {}
+        Some(Value::Map(overrides)) => {
+            for (k, v) in overrides {
+                if !params.contains_key(k) {
+                    return Err(err(format!("template \"{name}\" has no parameter \"{k}\"")));
+                }
+                params.insert(k.clone(), v.clone());
+            }
+        }
+        Some(_) => return Err(err("params must be an object".to_string())),
+    }
+
+    let body = template
+        .get_path("zone")
+        .cloned()
+        .unwrap_or(Value::Map(IndexMap::new()));
+    let body = substitute(&body, &params);
+
+    let mut rest = zone.clone();
+    rest.shift_remove("template");
+    rest.shift_remove("params");
+    Ok(extend_all(&[body, Value::Map(rest)]))
+}
+
+fn load_template(name: &str, search_paths: &[PathBuf]) -> Option<String> {
+    let file = format!("{name}.yaml");
+    search_paths
+        .iter()
+        .find_map(|base| vfs::read_to_string(&base.join(&file)).ok())
+        .or_else(|| {
+            BUILTIN
+                .iter()
+                .find(|(builtin, _)| *builtin == name)
+                .map(|(_, source)| source.to_string())
+        })
+}
+
+fn substitute(v: &Value, params: &IndexMap<String, Value>) -> Value {
+    match v {
+        Value::String(s) => {
+            if let Some(name) = s
+                .strip_prefix("{{")
+                .and_then(|rest| rest.strip_suffix("}}"))
+                .map(str::trim)
+                && let Some(value) = params.get(name)
+            {
+                return value.clone();
+            }
+            let mut out = s.clone();
+            for (name, value) in params {
+                out = out.replace(
+                    &format!("{{{{{name}}}}}"),
+                    &arg_to_replacement_string(value),
+                );
+            }
+            Value::String(out)
+        }
+        Value::Map(m) => Value::Map(
+            m.iter()
+                .map(|(k, child)| (k.clone(), substitute(child, params)))
+                .collect(),
+        ),
+        Value::Seq(seq) => Value::Seq(seq.iter().map(|child| substitute(child, params)).collect()),
+        other => other.clone(),
+    }
+}
@@ -0,0 +1,113 @@
+use std::sync::{Mutex, MutexGuard};
+
+use indexmap::IndexMap;
+
+use ergogen_parser::{Error, PreparedConfig, Value, builtin_zone_templates};
+
+/// Held by every test that loads templates by name, so none sees another's virtual files.
+fn vfs_lock() -> MutexGuard<'static, ()> {
+    static LOCK: Mutex<()> = Mutex::new(());
+    LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn thumb_spread(yaml: &str) -> Option<Value> {
+    PreparedConfig::from_yaml_str(yaml)
+        .unwrap()
+        .canonical
+        .get_path("points.zones.thumb.key.spread")
+        .cloned()
+}
+
+#[test]
+fn builtin_template_expands_into_plain_zone() {
+    let prepared = PreparedConfig::from_yaml_str(
+        r#"
+points.zones.thumb:
+  template: thumb_arc_3
+  params:
+    splay: -20
+  anchor.shift: [10, -20]
+"#,
+    )
+    .unwrap();
+
+    let zone = prepared.canonical.get_path("points.zones.thumb").unwrap();
+    assert!(zone.get_path("template").is_none());
+    assert!(zone.get_path("params").is_none());
+    assert_eq!(zone.get_path("key.splay"), Some(&Value::Number(-20.0)));
+    assert_eq!(zone.get_path("key.spread"), Some(&Value::Number(20.0)));
+    assert_eq!(
+        zone.get_path("columns.near.key.splay"),
+        Some(&Value::Number(0.0))
+    );
+    assert!(zone.get_path("columns.far").is_some());
+    assert_eq!(
+        zone.get_path("anchor.shift"),
+        Some(&Value::Seq(vec![Value::Number(10.0), Value::Number(-20.0)]))
+    );
+}
+
+#[test]
+fn user_templates_are_found_on_search_paths_and_win_over_builtins() {
+    let _lock = vfs_lock();
+    ergogen_core::vfs::set(IndexMap::from([(
+        "my_templates/thumb_row_2.yaml".to_string(),
+        "params:\n  gap: 2\nzone:\n  key.spread: \"19 + {{gap}}\"\n  columns: { only: }\n"
+            .to_string(),
+    )]));
+    let prepared = PreparedConfig::from_yaml_str(
+        r#"
+points:
+  templates_search_paths: my_templates
+  zones.thumb:
+    template: thumb_row_2
+    params.gap: 3
+"#,
+    );
+    // Outside the search paths, the default `templates/` directory does not reach into
+    // `my_templates/`.
+    let builtin = thumb_spread("points.zones.thumb.template: thumb_row_2\n");
+    ergogen_core::vfs::clear();
+
+    let zone = prepared
+        .unwrap()
+        .canonical
+        .get_path("points.zones.thumb")
+        .cloned()
+        .unwrap();
+    assert_eq!(
+        zone.get_path("key.spread"),
+        Some(&Value::String("19 + 3".to_string()))
+    );
+    assert!(zone.get_path("columns.only").is_some());
+    assert!(zone.get_path("columns.near").is_none());
+    assert_eq!(builtin, Some(Value::Number(19.0)));
+}
+
+#[test]
+fn unknown_templates_and_params_are_errors() {
+    let err = PreparedConfig::from_yaml_str("points.zones.thumb.template: nope\n").unwrap_err();
+    assert!(matches!(err, Error::ZoneTemplate { ref at, .. } if at == "points.zones.thumb"));
+
+    let err = PreparedConfig::from_yaml_str(
+        "points.zones.thumb:\n  template: thumb_arc_3\n  params.radius: 3\n",
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("no parameter \"radius\""));
+}
+
+#[test]
+fn every_builtin_template_prepares() {
+    let _lock = vfs_lock();
+    for name in builtin_zone_templates() {
+        let yaml = format!("points.zones.z.template: {name}\n");
+        let prepared = PreparedConfig::from_yaml_str(&yaml).unwrap();
+        assert!(
+            prepared
+                .canonical
+                .get_path("points.zones.z.columns")
+                .is_some(),
+            "{name}"
+        );
+    }
+}
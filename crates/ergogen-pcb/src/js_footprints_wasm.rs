@@ -9,8 +9,8 @@ use crate::js_footprints_shared::{
     next_ref, resolve_designator, resolve_net_name, resolve_param_value,
 };
 use crate::js_runtime::{JsNet, JsParamSpec, parse_js_params};
-use crate::vfs;
 use crate::{NetIndex, PcbError, Placement, escape_kicad_text, fmt_num, rotate_ccw, to_kicad_xy};
+use ergogen_core::vfs;
 use ergogen_parser::Value as ErgogenValue;
 
 #[wasm_bindgen]
@@ -0,0 +1,273 @@
+//! Reads footprint placements back out of an existing `.kicad_pcb`.
+//!
+//! Only the parts needed to recover positions are understood: `module` (KiCad 5) and `footprint`
+//! (KiCad 6+) nodes, their `at` and their reference (`fp_text reference` or
+//! `property "Reference"`).
+
+use indexmap::IndexMap;
+
+use ergogen_parser::Value;
+
+use crate::PcbError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedFootprint {
+    pub reference: String,
+    /// Position in Ergogen's y-up coordinates.
+    pub x: f64,
+    pub y: f64,
+    pub r: f64,
+}
+
+/// Returns the footprints whose reference matches `pattern`, in file order.
+///
+/// `pattern` is a glob where `*` matches any run of characters and `?` a single one (e.g. `S*`).
+pub fn read_footprints(source: &str, pattern: &str) -> Result<Vec<ImportedFootprint>, PcbError> {
+    let root = parse_sexpr(source)?;
+    let mut out = Vec::new();
+    collect_footprints(&root, pattern, &mut out)?;
+    Ok(out)
+}
+
+/// Builds a `points` config with one single-key zone per matching footprint, named after its
+/// reference.
+pub fn points_config_from_kicad(source: &str, pattern: &str) -> Result<Value, PcbError> {
+    let footprints = read_footprints(source, pattern)?;
+    if footprints.is_empty() {
+        return Err(PcbError::KicadImport(format!(
+            "no footprints match reference pattern \"{pattern}\""
+        )));
+    }
+
+    let mut zones = IndexMap::new();
+    for fp in footprints {
+        if zones.contains_key(&fp.reference) {
+            return Err(PcbError::KicadImport(format!(
+                "duplicate footprint reference \"{}\"",
+                fp.reference
+            )));
+        }
+        let mut anchor = IndexMap::from([(
+            "shift".to_string(),
+            Value::Seq(vec![Value::Number(round(fp.x)), Value::Number(round(fp.y))]),
+        )]);
+        if fp.r != 0.0 {
+            anchor.insert("rotate".to_string(), Value::Number(round(fp.r)));
+        }
+        let zone = IndexMap::from([
+            ("anchor".to_string(), Value::Map(anchor)),
+            (
+                "key".to_string(),
+                Value::Map(IndexMap::from([(
+                    "name".to_string(),
+                    Value::String(fp.reference.clone()),
+                )])),
+            ),
+        ]);
+        zones.insert(fp.reference, Value::Map(zone));
+    }
+
+    Ok(Value::Map(IndexMap::from([(
+        "points".to_string(),
+        Value::Map(IndexMap::from([("zones".to_string(), Value::Map(zones))])),
+    )])))
+}
+
+fn collect_footprints(
+    node: &Sexpr,
+    pattern: &str,
+    out: &mut Vec<ImportedFootprint>,
+) -> Result<(), PcbError> {
+    let Sexpr::List(items) = node else {
+        return Ok(());
+    };
+    match head(items) {
+        Some("module" | "footprint") => {
+            let Some(reference) = footprint_reference(items) else {
+                return Ok(());
+            };
+            if !glob_match(pattern, &reference) {
+                return Ok(());
+            }
+            let at = items
+                .iter()
+                .find_map(|item| match item {
+                    Sexpr::List(l) if head(l) == Some("at") => Some(l),
+                    _ => None,
+                })
+                .ok_or_else(|| {
+                    PcbError::KicadImport(format!("footprint \"{reference}\" has no position"))
+                })?;
+            let num = |idx: usize| -> Result<f64, PcbError> {
+                match at.get(idx) {
+                    None => Ok(0.0),
+                    Some(Sexpr::Atom(s)) => s.parse().map_err(|_| {
+                        PcbError::KicadImport(format!(
+                            "footprint \"{reference}\" has an invalid position \"{s}\""
+                        ))
+                    }),
+                    Some(Sexpr::List(_)) => Err(PcbError::KicadImport(format!(
+                        "footprint \"{reference}\" has an invalid position"
+                    ))),
+                }
+            };
+            let (x, y) = from_kicad_xy(num(1)?, num(2)?);
+            let r = num(3)?;
+            out.push(ImportedFootprint { reference, x, y, r });
+            Ok(())
+        }
+        _ => {
+            for item in items {
+                collect_footprints(item, pattern, out)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn footprint_reference(items: &[Sexpr]) -> Option<String> {
+    items.iter().find_map(|item| {
+        let Sexpr::List(l) = item else {
+            return None;
+        };
+        match (head(l), l.get(1), l.get(2)) {
+            (Some("fp_text"), Some(Sexpr::Atom(kind)), Some(Sexpr::Atom(value)))
+                if kind == "reference" =>
+            {
+                Some(value.clone())
+            }
+            (Some("property"), Some(Sexpr::Atom(kind)), Some(Sexpr::Atom(value)))
+                if kind == "Reference" =>
+            {
+                Some(value.clone())
+            }
+            _ => None,
+        }
+    })
+}
+
+/// Inverse of `to_kicad_xy`: KiCad's y axis points down.
+fn from_kicad_xy(x: f64, y: f64) -> (f64, f64) {
+    (x, if y == 0.0 { 0.0 } else { -y })
+}
+
+fn round(v: f64) -> f64 {
+    let v = (v * 1e6).round() / 1e6;
+    if v == 0.0 { 0.0 } else { v }
+}
+
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            backtrack = Some((pi, ti));
+            pi += 1;
+        } else if let Some((bp, bt)) = backtrack {
+            pi = bp + 1;
+            ti = bt + 1;
+            backtrack = Some((bp, bt + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|c| *c == '*')
+}
+
+#[derive(Debug)]
+enum Sexpr {
+    /// Bare or quoted token, with quotes and escapes removed.
+    Atom(String),
+    List(Vec<Sexpr>),
+}
+
+fn head(items: &[Sexpr]) -> Option<&str> {
+    match items.first() {
+        Some(Sexpr::Atom(s)) => Some(s.as_str()),
+        _ => None,
+    }
+}
+
+fn parse_sexpr(source: &str) -> Result<Sexpr, PcbError> {
+    let mut stack: Vec<Vec<Sexpr>> = vec![Vec::new()];
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '(' => stack.push(Vec::new()),
+            ')' => {
+                let list = stack.pop().filter(|_| !stack.is_empty()).ok_or_else(|| {
+                    PcbError::KicadImport("unbalanced closing parenthesis".to_string())
+                })?;
+                stack
+                    .last_mut()
+                    .expect("non-empty stack")
+                    .push(Sexpr::List(list));
+            }
+            '"' => {
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => {
+                            if let Some(esc) = chars.next() {
+                                s.push(esc);
+                            }
+                        }
+                        Some(ch) => s.push(ch),
+                        None => {
+                            return Err(PcbError::KicadImport("unterminated string".to_string()));
+                        }
+                    }
+                }
+                push_atom(&mut stack, s);
+            }
+            c if c.is_whitespace() => {}
+            c => {
+                let mut s = String::from(c);
+                while let Some(&next) = chars.peek() {
+                    if next.is_whitespace() || next == '(' || next == ')' || next == '"' {
+                        break;
+                    }
+                    s.push(next);
+                    chars.next();
+                }
+                push_atom(&mut stack, s);
+            }
+        }
+    }
+    if stack.len() != 1 {
+        return Err(PcbError::KicadImport(
+            "unbalanced opening parenthesis".to_string(),
+        ));
+    }
+    Ok(Sexpr::List(stack.pop().expect("root list")))
+}
+
+fn push_atom(stack: &mut [Vec<Sexpr>], atom: String) {
+    stack
+        .last_mut()
+        .expect("non-empty stack")
+        .push(Sexpr::Atom(atom));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::glob_match;
+
+    #[test]
+    fn glob_matches_reference_prefixes() {
+        assert!(glob_match("S*", "S1"));
+        assert!(glob_match("S*", "S"));
+        assert!(!glob_match("S*", "D1"));
+        assert!(glob_match("SW?", "SW3"));
+        assert!(!glob_match("SW?", "SW12"));
+        assert!(glob_match("*", "anything"));
+        assert!(glob_match("S*1", "S21"));
+        assert!(!glob_match("S*1", "S12"));
+    }
+}
@@ -15,19 +15,19 @@ mod js_footprints_wasm;
     all(feature = "js-footprints-wasm", target_arch = "wasm32")
 ))]
 mod js_runtime;
+pub mod kicad_import;
 mod templates;
-mod vfs;
 
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use cavalier_contours::polyline::{PlineSource, seg_arc_radius_and_center};
-use ergogen_core::{Point, PointMeta};
+use ergogen_core::{Point, PointMeta, vfs};
 use ergogen_geometry::region::Region;
-use ergogen_layout::{PointsOutput, anchor, parse_points};
+use ergogen_layout::{Asymmetry, PointsOutput, anchor, filter};
+use ergogen_outline::OutlineContext;
 use ergogen_parser::{Error as ParserError, PreparedConfig, Units, Value, extend_all};
 use indexmap::IndexMap;
-use regex::Regex;
 
 use footprint_spec::{ResolvedPrimitive, parse_footprint_spec, resolve_footprint_spec};
 
@@ -42,15 +42,21 @@ use templates::{
     trace_template, trrs_template,
 };
 
-/// Sets a virtual file map used for footprint/spec loading.
+/// Sets a virtual file map used for footprint/spec loading and `points.import` sources.
 ///
 /// Intended for WASM consumers (no filesystem access), but it also works in native tests.
+/// This is the shared [`ergogen_core::vfs`] map.
 /// Keys should be the same strings you'd use in `what:` (e.g. `ceoloide/led.js`) or resolved
 /// relative paths like `footprints/ceoloide/led.js`.
 pub fn set_virtual_files(map: IndexMap<String, String>) {
     vfs::set(map);
 }
 
+/// Adds a binary file (e.g. a TTF/OTF font for `what: text`) to the virtual file map.
+pub fn add_virtual_binary_file(path: &str, bytes: Vec<u8>) {
+    vfs::insert_bytes(path, bytes);
+}
+
 /// Clears the virtual file map.
 pub fn clear_virtual_files() {
     vfs::clear();
@@ -62,6 +68,8 @@ pub enum PcbError {
     Parser(#[from] ParserError),
     #[error("failed to parse points: {0}")]
     Points(#[from] ergogen_layout::LayoutError),
+    #[error("failed to sample outline points: {0}")]
+    OutlineSamples(#[from] ergogen_outline::OutlineError),
     #[error("missing pcbs.{pcb}")]
     MissingPcb { pcb: String },
     #[error("footprint spec error: {0}")]
@@ -70,13 +78,8 @@ pub enum PcbError {
     FootprintSpecIo(String),
     #[error("unsupported pcb config: {0}")]
     Unsupported(&'static str),
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Asym {
-    Both,
-    Source,
-    Clone,
+    #[error("kicad import error: {0}")]
+    KicadImport(String),
 }
 
 #[derive(Debug, Clone)]
@@ -156,6 +159,15 @@ pub fn generate_kicad_pcb_from_yaml_str(yaml: &str, pcb_name: &str) -> Result<St
 }
 
 pub fn generate_kicad_pcb(prepared: &PreparedConfig, pcb_name: &str) -> Result<String, PcbError> {
+    generate_kicad_pcb_with_context(&OutlineContext::new(prepared), pcb_name)
+}
+
+/// Like [`generate_kicad_pcb`], reusing the points and outlines already generated in `ctx`.
+pub fn generate_kicad_pcb_with_context(
+    ctx: &OutlineContext<'_>,
+    pcb_name: &str,
+) -> Result<String, PcbError> {
+    let prepared = ctx.prepared();
     let pcb = prepared
         .canonical
         .get_path(&format!("pcbs.{pcb_name}"))
@@ -166,6 +178,7 @@ pub fn generate_kicad_pcb(prepared: &PreparedConfig, pcb_name: &str) -> Result<S
     let Value::Map(pcb_map) = pcb else {
         return Err(PcbError::Unsupported("pcbs.<name> must be a map"));
     };
+    ctx.check_measurements(pcb)?;
 
     let template = pcb_map
         .get("template")
@@ -192,8 +205,8 @@ pub fn generate_kicad_pcb(prepared: &PreparedConfig, pcb_name: &str) -> Result<S
         ));
     }
 
-    let points = parse_points(&prepared.canonical, &prepared.units)?;
-    let ref_points = points_to_ref(&points);
+    let points = ctx.points()?;
+    let ref_points = points_to_ref(points);
 
     let mut nets = NetIndex::default();
     let mut refs: HashMap<String, usize> = HashMap::new();
@@ -207,7 +220,8 @@ pub fn generate_kicad_pcb(prepared: &PreparedConfig, pcb_name: &str) -> Result<S
     // Outlines
     let outline_names = collect_outline_names(pcb_map.get("outlines"));
     for name in outline_names {
-        let region = ergogen_outline::generate_outline_region(prepared, &name)
+        let region = ctx
+            .region(&name)
             .map_err(|_| PcbError::Unsupported("outline generation failed"))?;
         let mut lines = if template == "kicad8" {
             outlines_to_kicad8(&region)
@@ -229,19 +243,18 @@ pub fn generate_kicad_pcb(prepared: &PreparedConfig, pcb_name: &str) -> Result<S
             let placements = placements_for_where(
                 def.where_v.as_ref(),
                 parse_asym(def.asym_v.as_ref(), def.where_v.as_ref()),
-                &points,
+                points,
                 &ref_points,
                 &prepared.units,
             )?;
 
             for p in placements {
-                let p =
-                    apply_adjust_if_present(def.adjust.as_ref(), p, &ref_points, &prepared.units)?;
+                let p = apply_adjust_if_present(def.adjust.as_ref(), p, &ref_points, ctx)?;
                 let (module, extra) = render_footprint(
                     &def,
                     p,
                     prepared,
-                    &points,
+                    points,
                     &ref_points,
                     &mut nets,
                     &mut refs,
@@ -1178,12 +1191,8 @@ fn resolve_spec_path(path: &str, search_paths: &[PathBuf]) -> Result<PathBuf, Pc
     Err(PcbError::FootprintSpecIo(format!("spec not found: {path}")))
 }
 
-fn read_file_to_string(path: &PathBuf) -> Result<String, PcbError> {
-    let key = path.to_string_lossy();
-    if let Some(s) = vfs::read(&key) {
-        return Ok(s);
-    }
-    std::fs::read_to_string(path)
+fn read_file_to_string(path: &Path) -> Result<String, PcbError> {
+    vfs::read_to_string(path)
         .map_err(|e| PcbError::FootprintSpecIo(format!("{}: {e}", path.display())))
 }
 
@@ -1998,183 +2007,38 @@ fn eval_number(units: &Units, v: &Value, at: &str) -> Result<f64, PcbError> {
 
 fn placements_for_where(
     where_v: Option<&Value>,
-    asym: Asym,
+    asym: Asymmetry,
     points: &PointsOutput,
     ref_points: &IndexMap<String, Point>,
     units: &Units,
 ) -> Result<Vec<Placement>, PcbError> {
-    let where_v = where_v.unwrap_or(&Value::Null);
-    match where_v {
-        Value::Bool(true) => {
-            let mut out = Vec::new();
-            for (name, p) in points.iter() {
-                let mirrored = p.meta.mirrored.unwrap_or(false);
-                if (asym == Asym::Source && mirrored) || (asym == Asym::Clone && !mirrored) {
-                    continue;
-                }
-                out.push(Placement {
-                    name: name.clone(),
-                    x: p.x,
-                    y: p.y,
-                    r: p.r,
-                    mirrored,
-                });
-            }
-            Ok(out)
-        }
-        Value::Null => Ok(vec![Placement {
-            name: String::new(),
-            x: 0.0,
-            y: 0.0,
-            r: 0.0,
-            mirrored: false,
-        }]),
-        Value::Bool(false) => Ok(Vec::new()),
-        Value::String(s) if looks_like_regex_literal(s) => {
-            let re = parse_regex_literal(s).map_err(|_| PcbError::Unsupported("invalid regex"))?;
-
-            let mut out = Vec::new();
-            for (name, p) in points.iter() {
-                if !re.is_match(name) && !p.meta.tags.iter().any(|t| re.is_match(t)) {
-                    continue;
-                }
-                let mirrored = p.meta.mirrored.unwrap_or(false);
-                if (asym == Asym::Source && mirrored) || (asym == Asym::Clone && !mirrored) {
-                    continue;
-                }
-                out.push(Placement {
-                    name: name.clone(),
-                    x: p.x,
-                    y: p.y,
-                    r: p.r,
-                    mirrored,
-                });
-            }
-            Ok(out)
-        }
-        Value::Seq(seq) if seq.iter().all(|v| matches!(v, Value::String(_))) => {
-            let mut wanted: Vec<&str> = Vec::with_capacity(seq.len());
-            for v in seq {
-                let Value::String(s) = v else { continue };
-                wanted.push(s.as_str());
-            }
-            if wanted.is_empty() {
-                return Ok(Vec::new());
-            }
-
-            let mut out = Vec::new();
-            for (name, p) in points.iter() {
-                let mirrored = p.meta.mirrored.unwrap_or(false);
-                if (asym == Asym::Source && mirrored) || (asym == Asym::Clone && !mirrored) {
-                    continue;
-                }
-                if !p.meta.tags.iter().any(|t| wanted.contains(&t.as_str())) {
-                    continue;
-                }
-                out.push(Placement {
-                    name: name.clone(),
-                    x: p.x,
-                    y: p.y,
-                    r: p.r,
-                    mirrored,
-                });
-            }
-            Ok(out)
-        }
-        other => {
-            let start = Point::new(0.0, 0.0, 0.0, PointMeta::default());
-            let base =
-                anchor::parse_anchor(other, "pcbs.where", ref_points, start.clone(), units, false)?;
-            match asym {
-                Asym::Source => Ok(vec![Placement {
-                    name: String::new(),
-                    x: base.x,
-                    y: base.y,
-                    r: base.r,
-                    mirrored: base.meta.mirrored,
-                }]),
-                Asym::Clone => {
-                    let m =
-                        anchor::parse_anchor(other, "pcbs.where", ref_points, start, units, true)?;
-                    Ok(vec![Placement {
-                        name: String::new(),
-                        x: m.x,
-                        y: m.y,
-                        r: m.r,
-                        mirrored: m.meta.mirrored,
-                    }])
-                }
-                Asym::Both => {
-                    let m =
-                        anchor::parse_anchor(other, "pcbs.where", ref_points, start, units, true)?;
-                    if (base.x - m.x).abs() < 1e-9
-                        && (base.y - m.y).abs() < 1e-9
-                        && (base.r - m.r).abs() < 1e-9
-                    {
-                        Ok(vec![Placement {
-                            name: String::new(),
-                            x: base.x,
-                            y: base.y,
-                            r: base.r,
-                            mirrored: base.meta.mirrored,
-                        }])
-                    } else {
-                        Ok(vec![
-                            Placement {
-                                name: String::new(),
-                                x: base.x,
-                                y: base.y,
-                                r: base.r,
-                                mirrored: base.meta.mirrored,
-                            },
-                            Placement {
-                                name: String::new(),
-                                x: m.x,
-                                y: m.y,
-                                r: m.r,
-                                mirrored: m.meta.mirrored,
-                            },
-                        ])
-                    }
-                }
-            }
-        }
-    }
-}
-
-fn looks_like_regex_literal(s: &str) -> bool {
-    s.starts_with('/') && s.len() >= 2 && s[1..].contains('/')
-}
-
-fn parse_regex_literal(raw: &str) -> Result<Regex, ()> {
-    // JS-style: /pattern/flags (we only care about `i` today)
-    let mut chars = raw.chars();
-    if chars.next() != Some('/') {
-        return Err(());
-    }
-
-    let last_slash = raw.rfind('/').ok_or(())?;
-    if last_slash == 0 {
-        return Err(());
-    }
-
-    let pat = &raw[1..last_slash];
-    let flags = &raw[last_slash + 1..];
-    let mut pat = pat.to_string();
-
-    if flags.contains('i') {
-        pat = format!("(?i){pat}");
-    }
-
-    Regex::new(&pat).map_err(|_| ())
+    let selected = filter::select_points(
+        where_v.unwrap_or(&Value::Null),
+        asym,
+        points,
+        ref_points,
+        units,
+        "pcbs.where",
+    )?;
+    Ok(selected
+        .into_iter()
+        .map(|s| Placement {
+            name: s.name,
+            x: s.x,
+            y: s.y,
+            r: s.r,
+            mirrored: s.mirrored,
+        })
+        .collect())
 }
 
 fn apply_adjust_if_present(
     adjust: Option<&Value>,
     p: Placement,
     ref_points: &IndexMap<String, Point>,
-    units: &Units,
+    ctx: &OutlineContext<'_>,
 ) -> Result<Placement, PcbError> {
+    let prepared = ctx.prepared();
     let Some(adjust) = adjust else {
         return Ok(p);
     };
@@ -2189,7 +2053,21 @@ fn apply_adjust_if_present(
             mirrored: p.mirrored,
         },
     );
-    let adjusted = anchor::parse_anchor(adjust, "pcbs.adjust", ref_points, start, units, false)?;
+    let centroid = |name: &str| -> Result<[f64; 2], String> {
+        let region = ctx.region(name).map_err(|e| e.to_string())?;
+        region
+            .centroid()
+            .ok_or_else(|| format!("outline \"{name}\" is empty"))
+    };
+    let adjusted = anchor::parse_anchor_with_outlines(
+        adjust,
+        "pcbs.adjust",
+        ref_points,
+        start,
+        &prepared.units,
+        false,
+        &centroid,
+    )?;
     Ok(Placement {
         name: p.name,
         x: adjusted.x,
@@ -2199,18 +2077,18 @@ fn apply_adjust_if_present(
     })
 }
 
-fn parse_asym(v: Option<&Value>, where_v: Option<&Value>) -> Asym {
+fn parse_asym(v: Option<&Value>, where_v: Option<&Value>) -> Asymmetry {
     let default = if matches!(where_v, Some(Value::Bool(true))) {
-        Asym::Both
+        Asymmetry::Both
     } else {
-        Asym::Source
+        Asymmetry::Source
     };
     let Some(v) = v else { return default };
     let Value::String(s) = v else { return default };
     match s.as_str() {
-        "both" => Asym::Both,
-        "source" | "origin" | "base" | "primary" | "left" => Asym::Source,
-        "clone" | "image" | "derived" | "secondary" | "right" => Asym::Clone,
+        "both" => Asymmetry::Both,
+        "source" | "origin" | "base" | "primary" | "left" => Asymmetry::Source,
+        "clone" | "image" | "derived" | "secondary" | "right" => Asymmetry::Clone,
         _ => default,
     }
 }
@@ -2586,6 +2464,14 @@ fn template_vars_for_point(
             vars.entry("row_net".to_string()).or_insert(v);
         }
 
+        // Nets from the matrix solver fill in wherever the config did not name them.
+        if let Some(net) = &p.meta.row_net {
+            vars.entry("row_net".to_string()).or_insert(net.clone());
+        }
+        if let Some(net) = &p.meta.column_net {
+            vars.entry("column_net".to_string()).or_insert(net.clone());
+        }
+
         if let Some(val) = prepared
             .canonical
             .get_path(&format!(
@@ -2642,7 +2528,7 @@ mod template_vars_tests {
             PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../knuckles/ergogen/config.yaml");
         let yaml = std::fs::read_to_string(&yaml_path).unwrap();
         let prepared = PreparedConfig::from_yaml_str(&yaml).unwrap();
-        let points = parse_points(&prepared.canonical, &prepared.units).unwrap();
+        let points = ergogen_layout::parse_points(&prepared.canonical, &prepared.units).unwrap();
 
         let ref_points: IndexMap<String, Point> = points
             .iter()
@@ -2663,7 +2549,7 @@ mod template_vars_tests {
 
         let placements = placements_for_where(
             Some(&Value::String("/key/".to_string())),
-            Asym::Both,
+            Asymmetry::Both,
             &points,
             &ref_points,
             &prepared.units,
@@ -0,0 +1,92 @@
+use ergogen_layout::parse_points;
+use ergogen_parser::PreparedConfig;
+use ergogen_pcb::kicad_import::{points_config_from_kicad, read_footprints};
+use ergogen_pcb::{PcbError, generate_kicad_pcb};
+
+const CONFIG: &str = r#"
+points:
+  zones:
+    matrix:
+      anchor:
+        shift: [20, 10]
+      columns:
+        left:
+        right:
+          key:
+            splay: -15
+            stagger: 5
+      rows:
+        bottom:
+        top:
+pcbs:
+  board:
+    footprints:
+      switch:
+        what: mx
+        where: true
+      diode:
+        what: diode
+        where: true
+        params:
+          from: "{{colrow}}"
+          to: "{{colrow}}"
+"#;
+
+fn assert_close(got: f64, expected: f64, what: &str) {
+    assert!(
+        (got - expected).abs() <= 1e-6,
+        "{what}: got={got} expected={expected}"
+    );
+}
+
+#[test]
+fn kicad_import_round_trips_generated_switch_positions() {
+    let prepared = PreparedConfig::from_yaml_str(CONFIG).unwrap();
+    let original = parse_points(&prepared.canonical, &prepared.units).unwrap();
+    let pcb = generate_kicad_pcb(&prepared, "board").unwrap();
+
+    let footprints = read_footprints(&pcb, "S*").unwrap();
+    let refs: Vec<&str> = footprints.iter().map(|f| f.reference.as_str()).collect();
+    assert_eq!(refs, ["S1", "S2", "S3", "S4"]);
+
+    let imported =
+        PreparedConfig::from_value(&points_config_from_kicad(&pcb, "S*").unwrap()).unwrap();
+    let points = parse_points(&imported.canonical, &imported.units).unwrap();
+    assert_eq!(points.len(), original.len());
+    for ((name, got), expected) in points.iter().zip(original.values()) {
+        assert_eq!(name, &got.meta.name);
+        assert_close(got.x, expected.x, &format!("{name}.x"));
+        assert_close(got.y, expected.y, &format!("{name}.y"));
+        assert_close(got.r, expected.r, &format!("{name}.r"));
+    }
+}
+
+#[test]
+fn kicad_import_reads_kicad8_footprints_and_filters_by_reference() {
+    let pcb = r#"(kicad_pcb (version 20240108) (generator "pcbnew")
+  (footprint "Switch:MX" (layer "F.Cu")
+    (at 100 50.5 90)
+    (property "Reference" "SW1" (at 0 0 0) (layer "F.SilkS"))
+  )
+  (footprint "Diode:D" (layer "B.Cu")
+    (at 10 10)
+    (property "Reference" "D1" (at 0 0 0) (layer "B.SilkS"))
+  )
+  (footprint "Switch:MX" (layer "F.Cu")
+    (at 119.05 50.5)
+    (property "Reference" "SW2" (at 0 0 0) (layer "F.SilkS"))
+  )
+)"#;
+
+    let footprints = read_footprints(pcb, "SW?").unwrap();
+    assert_eq!(footprints.len(), 2);
+    assert_eq!(footprints[0].reference, "SW1");
+    assert_close(footprints[0].x, 100.0, "SW1.x");
+    assert_close(footprints[0].y, -50.5, "SW1.y");
+    assert_close(footprints[0].r, 90.0, "SW1.r");
+    assert_close(footprints[1].r, 0.0, "SW2.r");
+
+    let err = points_config_from_kicad(pcb, "J*").unwrap_err();
+    assert!(matches!(err, PcbError::KicadImport(_)), "{err}");
+    assert!(read_footprints("(kicad_pcb (footprint", "*").is_err());
+}
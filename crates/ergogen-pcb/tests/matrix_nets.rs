@@ -0,0 +1,38 @@
+use ergogen_parser::PreparedConfig;
+use ergogen_pcb::generate_kicad_pcb;
+
+const CONFIG: &str = r#"
+points:
+  matrix: true
+  zones:
+    matrix:
+      columns:
+        left:
+        right:
+      rows:
+        bottom:
+        top:
+          row_net: TOP
+pcbs:
+  board:
+    footprints:
+      diode:
+        what: diode
+        where: true
+        params:
+          from: "{{column_net}}"
+          to: "{{row_net}}"
+"#;
+
+#[test]
+fn footprint_params_use_solved_matrix_nets() {
+    let prepared = PreparedConfig::from_yaml_str(CONFIG).unwrap();
+    let pcb = generate_kicad_pcb(&prepared, "board").unwrap();
+
+    for net in ["col1", "col2", "row1"] {
+        assert!(pcb.contains(&format!("\"{net}\"")), "{net}");
+    }
+    // A net named in the config wins over the solver's.
+    assert!(pcb.contains("\"TOP\""));
+    assert!(!pcb.contains("\"row2\""));
+}
@@ -3,7 +3,8 @@ use wasm_bindgen::prelude::wasm_bindgen;
 
 use ergogen_export::dxf::{Dxf, Entity, Line, NormalizeOptions, Point2};
 use ergogen_export::{dxf_geom, svg};
-use ergogen_layout::{PointsOutput, parse_points};
+use ergogen_layout::PointsOutput;
+use ergogen_outline::{OutlineContext, compensation};
 use ergogen_parser::{PreparedConfig, Value, convert_kle};
 use indexmap::IndexMap;
 use serde::Serialize;
@@ -22,6 +23,12 @@ pub fn set_virtual_fs(files: JsValue) -> Result<(), JsValue> {
     Ok(())
 }
 
+/// Adds one binary file (a `Uint8Array`, e.g. a font) to the map set by `set_virtual_fs`.
+#[wasm_bindgen]
+pub fn add_virtual_binary_file(path: &str, bytes: &[u8]) {
+    ergogen_pcb::add_virtual_binary_file(path, bytes.to_vec());
+}
+
 #[wasm_bindgen]
 pub fn clear_virtual_fs() {
     ergogen_pcb::clear_virtual_files();
@@ -70,13 +77,17 @@ fn push_error(errors: &mut Vec<ErgogenError>, kind: &str, target: &str, message:
     });
 }
 
-fn outline_region(
-    yaml: &str,
+/// The outline as it is cut: with the `export` kerf and tool compensation applied.
+fn export_region(
+    ctx: &OutlineContext<'_>,
     outline_name: &str,
 ) -> Result<ergogen_geometry::region::Region, JsValue> {
-    let prepared = prepare_config(yaml)?;
-    ergogen_outline::generate_outline_region(&prepared, outline_name)
-        .map_err(|e| to_js_error("outline", e.to_string()))
+    let region = ctx
+        .region(outline_name)
+        .map_err(|e| to_js_error("outline", e.to_string()))?;
+    let options = compensation::export_options(ctx.prepared(), outline_name)
+        .map_err(|e| to_js_error("export", e.to_string()))?;
+    compensation::compensate(&region, &options).map_err(|e| to_js_error("export", e.to_string()))
 }
 
 fn prepare_config(raw: &str) -> Result<PreparedConfig, JsValue> {
@@ -103,11 +114,16 @@ struct RenderAllOutput {
 #[wasm_bindgen]
 pub fn render_all(config_yaml: &str) -> Result<JsValue, JsValue> {
     let prepared = prepare_config(config_yaml)?;
+    // Every output below shares the points and the generated outlines. A measurement that fails
+    // is reported by the outlines, PCBs and cases using it; the rest renders.
+    let ctx = OutlineContext::new(&prepared);
 
     let canonical = prepared.canonical.clone();
     let units = prepared.units.vars().clone();
-    let points = parse_points(&prepared.canonical, &prepared.units)
-        .map_err(|e| to_js_error("points", e.to_string()))?;
+    let points = ctx
+        .points()
+        .map_err(|e| to_js_error("points", e.to_string()))?
+        .clone();
     let demo = demo_from_points(&points).map_err(|e| to_js_error("demo", e))?;
 
     let mut pcbs = IndexMap::new();
@@ -117,7 +133,7 @@ pub fn render_all(config_yaml: &str) -> Result<JsValue, JsValue> {
 
     if let Some(map) = prepared.canonical.get_path("pcbs").and_then(|v| v.as_map()) {
         for name in map.keys() {
-            match render_pcb(config_yaml, name) {
+            match pcb_string(&ctx, name) {
                 Ok(pcb) => {
                     pcbs.insert(name.clone(), pcb);
                 }
@@ -136,13 +152,21 @@ pub fn render_all(config_yaml: &str) -> Result<JsValue, JsValue> {
         for name in map.keys() {
             let mut dxf = String::new();
             let mut svg_str = String::new();
-            match render_dxf(config_yaml, name) {
-                Ok(value) => dxf = value,
-                Err(err) => push_error(&mut errors, "outline", name, format!("{err:?}")),
-            }
-            match render_svg(config_yaml, name) {
-                Ok(value) => svg_str = value,
-                Err(err) => push_error(&mut errors, "svg", name, format!("{err:?}")),
+            match export_region(&ctx, name) {
+                Ok(region) => {
+                    match dxf_string(&region) {
+                        Ok(value) => dxf = value,
+                        Err(err) => push_error(&mut errors, "outline", name, format!("{err:?}")),
+                    }
+                    match svg_string(&region) {
+                        Ok(value) => svg_str = value,
+                        Err(err) => push_error(&mut errors, "svg", name, format!("{err:?}")),
+                    }
+                }
+                Err(err) => {
+                    push_error(&mut errors, "outline", name, format!("{err:?}"));
+                    push_error(&mut errors, "svg", name, format!("{err:?}"));
+                }
             }
             if !dxf.is_empty() || !svg_str.is_empty() {
                 outlines.insert(name.clone(), OutlineOutput { dxf, svg: svg_str });
@@ -159,11 +183,11 @@ pub fn render_all(config_yaml: &str) -> Result<JsValue, JsValue> {
             let mut v1: Option<String> = None;
             let mut v2: Option<String> = None;
 
-            match ergogen_export::jscad::generate_cases_jscad(&prepared, name) {
+            match ergogen_export::jscad::generate_cases_jscad_with_context(&ctx, name) {
                 Ok(jscad) => v1 = Some(jscad),
                 Err(err) => push_error(&mut errors, "case", name, format!("{err:?}")),
             }
-            match ergogen_export::jscad::generate_cases_jscad_v2(&prepared, name) {
+            match ergogen_export::jscad::generate_cases_jscad_v2_with_context(&ctx, name) {
                 Ok(jscad) => v2 = Some(jscad),
                 Err(err) => push_error(&mut errors, "case_v2", name, format!("{err:?}")),
             }
@@ -206,15 +230,26 @@ pub fn render_all(config_yaml: &str) -> Result<JsValue, JsValue> {
 #[wasm_bindgen]
 pub fn render_pcb(config_yaml: &str, pcb_name: &str) -> Result<String, JsValue> {
     let prepared = prepare_config(config_yaml)?;
-    ergogen_pcb::generate_kicad_pcb(&prepared, pcb_name)
+    pcb_string(&OutlineContext::new(&prepared), pcb_name)
+}
+
+fn pcb_string(ctx: &OutlineContext<'_>, pcb_name: &str) -> Result<String, JsValue> {
+    ergogen_pcb::generate_kicad_pcb_with_context(ctx, pcb_name)
         .map_err(|e| to_js_error("pcb", e.to_string()))
 }
 
 #[wasm_bindgen]
 pub fn render_dxf(config_yaml: &str, outline_name: &str) -> Result<String, JsValue> {
-    let region = outline_region(config_yaml, outline_name)?;
+    let prepared = prepare_config(config_yaml)?;
+    dxf_string(&export_region(
+        &OutlineContext::new(&prepared),
+        outline_name,
+    )?)
+}
+
+fn dxf_string(region: &ergogen_geometry::region::Region) -> Result<String, JsValue> {
     let dxf =
-        dxf_geom::dxf_from_region(&region).map_err(|e| to_js_error("export", e.to_string()))?;
+        dxf_geom::dxf_from_region(region).map_err(|e| to_js_error("export", e.to_string()))?;
     let normalized = dxf
         .normalize(NormalizeOptions::default())
         .map_err(|e| to_js_error("export", e.to_string()))?;
@@ -225,9 +260,16 @@ pub fn render_dxf(config_yaml: &str, outline_name: &str) -> Result<String, JsVal
 
 #[wasm_bindgen]
 pub fn render_svg(config_yaml: &str, outline_name: &str) -> Result<String, JsValue> {
-    let region = outline_region(config_yaml, outline_name)?;
+    let prepared = prepare_config(config_yaml)?;
+    svg_string(&export_region(
+        &OutlineContext::new(&prepared),
+        outline_name,
+    )?)
+}
+
+fn svg_string(region: &ergogen_geometry::region::Region) -> Result<String, JsValue> {
     let dxf =
-        dxf_geom::dxf_from_region(&region).map_err(|e| to_js_error("export", e.to_string()))?;
+        dxf_geom::dxf_from_region(region).map_err(|e| to_js_error("export", e.to_string()))?;
     let normalized = dxf
         .normalize(NormalizeOptions::default())
         .map_err(|e| to_js_error("export", e.to_string()))?;
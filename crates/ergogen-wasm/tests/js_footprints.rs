@@ -134,3 +134,34 @@ fn render_all_accepts_kle_json() {
     };
     assert_eq!(label, "0_0");
 }
+
+#[wasm_bindgen_test]
+fn render_all_reports_failed_measurements_per_output() {
+    let yaml = r#"
+points.zones.matrix:
+outlines:
+  board:
+    - what: rectangle
+      size: [outlines.missing.width, 20]
+  plate:
+    - what: rectangle
+      size: 10
+"#;
+    let value = ergogen_wasm::render_all(yaml).unwrap();
+    let output: RenderAllOutput = serde_wasm_bindgen::from_value(value).unwrap();
+
+    assert!(!output.points.is_empty());
+    assert!(!output.demo.svg.trim().is_empty());
+    // Only `board` uses the failed measurement.
+    assert_eq!(output.outlines.keys().collect::<Vec<_>>(), ["plate"]);
+    let targets: Vec<_> = output
+        .errors
+        .iter()
+        .map(|e| (e.kind.as_str(), e.target.as_deref()))
+        .collect();
+    assert_eq!(
+        targets,
+        [("outline", Some("board")), ("svg", Some("board"))]
+    );
+    assert!(output.errors[0].message.contains("outlines.missing"));
+}